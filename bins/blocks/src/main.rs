@@ -0,0 +1,131 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use clap::Parser;
+use comrak::Arena;
+
+use obsidian_core::{
+    blocks::{self, BlockInventory},
+    parser::{self, ParseReport},
+    printer::{self, Format},
+    reader,
+};
+
+/// Inventory the callouts and code blocks in markdown files in an Obsidian vault.
+///
+/// By default, this tool will read all markdown files in the specified directory and report every
+/// callout (`> [!type] Title`) and code block found in them. This is useful for auditing which
+/// notes contain runnable snippets in a given language or a specific type of callout.
+///
+/// Each code block includes a stable hash of its content, so the same snippet copied across
+/// multiple notes can be found by comparing hashes.
+///
+/// For plain text output, each block is printed on its own line prefixed with its file and line
+/// number, similar to grep. For structured formats, the output is a mapping of file paths to the
+/// callouts and code blocks in that file. Files without any matching blocks are omitted.
+///
+/// Example output (plain):
+/// /path/to/vault/Scripts.md:5: code rust (12 lines) 85944171f73967e8
+/// /path/to/vault/Scripts.md:20: callout warning: Don't run this in prod
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-blocks", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    #[command(flatten)]
+    pub read_opts: reader::ReaderOpts,
+
+    /// Only include code blocks in the given language. Can be passed multiple times. Callouts are
+    /// excluded unless `--callout` is also passed
+    #[arg(short = 'l', long = "language")]
+    pub languages: Vec<String>,
+
+    /// Only include callouts of the given type (e.g. "note" or "warning"). Can be passed multiple
+    /// times. Code blocks are excluded unless `--language` is also passed
+    #[arg(short = 'c', long = "callout")]
+    pub callouts: Vec<String>,
+}
+
+impl Cli {
+    fn filter(&self, mut inventory: BlockInventory) -> BlockInventory {
+        let filtering = !self.languages.is_empty() || !self.callouts.is_empty();
+        if filtering {
+            inventory.code_blocks.retain(|block| {
+                block.language.as_ref().is_some_and(|lang| {
+                    self.languages
+                        .iter()
+                        .any(|wanted| wanted.eq_ignore_ascii_case(lang))
+                })
+            });
+            inventory.callouts.retain(|callout| {
+                self.callouts
+                    .iter()
+                    .any(|wanted| wanted.eq_ignore_ascii_case(&callout.kind))
+            });
+        }
+        inventory
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli: Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+
+    let entries = cli.read_opts.read_files()?;
+
+    let arena = Arena::with_capacity(entries.len());
+    let mut report = ParseReport::default();
+    let parsed_files = parser::report_error_iter(
+        parser::parse_files_with_progress(&arena, entries, cli.read_opts.progress_bar()),
+        &mut report,
+    );
+    let inventories: BTreeMap<PathBuf, BlockInventory> = blocks::parse_blocks(parsed_files)
+        .filter_map(|(pf, inventory)| {
+            let inventory = cli.filter(inventory);
+            (!inventory.is_empty()).then_some((pf.path, inventory))
+        })
+        .collect();
+
+    cli.read_opts.print_report(&report);
+
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+    match format {
+        Format::Plain => format.print_plain(
+            inventories.iter().flat_map(|(path, inventory)| {
+                let mut lines: Vec<(usize, String)> = inventory
+                    .code_blocks
+                    .iter()
+                    .map(|block| {
+                        (
+                            block.span.start_line,
+                            format!(
+                                "code {} ({} lines) {}",
+                                block.language.as_deref().unwrap_or("-"),
+                                block.lines,
+                                block.hash
+                            ),
+                        )
+                    })
+                    .chain(inventory.callouts.iter().map(|callout| {
+                        let title = callout
+                            .title
+                            .as_deref()
+                            .map(|t| format!(": {t}"))
+                            .unwrap_or_default();
+                        (
+                            callout.span.start_line,
+                            format!("callout {}{title}", callout.kind),
+                        )
+                    }))
+                    .collect();
+                lines.sort_by_key(|(line, _)| *line);
+                lines
+                    .into_iter()
+                    .map(move |(line, desc)| format!("{}:{line}: {desc}", path.display()))
+            }),
+            &mut writer,
+        ),
+        Format::Json | Format::Binary => format.print_structured(inventories, &mut writer),
+    }
+}
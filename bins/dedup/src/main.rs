@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+
+use anyhow::bail;
+use clap::Parser;
+use comrak::Arena;
+
+use obsidian_core::{
+    dedup::{self, Fingerprint},
+    parser::{self, ParseReport},
+    printer::{self, Format},
+    reader,
+};
+
+/// Find duplicate and near-duplicate notes in an Obsidian vault.
+///
+/// By default, this tool will read all markdown files in the specified directory and compare the
+/// content of every note with every other note. Content is compared after removing frontmatter,
+/// formatting, punctuation, and case, so two notes with the same text but different tags are still
+/// reported as exact duplicates.
+///
+/// Near-duplicates are found by estimating how many runs of consecutive words the notes share
+/// (using MinHash), so notes where only a few words were edited will still match. The similarity
+/// needed to report a pair can be set with `--threshold`. Empty notes are never reported.
+///
+/// For plain text output, each pair of duplicates is printed on its own line with its similarity,
+/// most similar first. For structured formats, the output is a list of objects with the paths of
+/// both notes, their similarity (from 0 to 1), and whether they are exact duplicates.
+///
+/// Example output (plain):
+/// /path/to/vault/Inbox/Meeting.md <-> /path/to/vault/Work/Meeting.md: exact duplicate
+/// /path/to/vault/Ideas.md <-> /path/to/vault/Ideas (copy).md: 91% similar
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-dedup", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    #[command(flatten)]
+    pub read_opts: reader::ReaderOpts,
+
+    /// The minimum similarity (from 0 to 1) for two notes to be reported as near-duplicates.
+    /// Defaults to 0.8
+    #[arg(short = 't', long = "threshold", default_value_t = dedup::DEFAULT_THRESHOLD)]
+    pub threshold: f64,
+
+    /// Only report notes with exactly the same content, ignoring near-duplicates even if every
+    /// run of words matches (like a paragraph repeated a different number of times). This is the
+    /// same as `--threshold 1`
+    #[arg(long = "exact", default_value_t = false)]
+    pub exact: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli: Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+
+    if !(0.0..=1.0).contains(&cli.threshold) {
+        bail!("Threshold must be between 0 and 1, got {}", cli.threshold);
+    }
+    let threshold = if cli.exact { 1.0 } else { cli.threshold };
+
+    let entries = cli.read_opts.read_files()?;
+
+    let arena = Arena::with_capacity(entries.len());
+    let mut report = ParseReport::default();
+    let parsed_files = parser::report_error_iter(
+        parser::parse_files_with_progress(&arena, entries, cli.read_opts.progress_bar()),
+        &mut report,
+    );
+    let fingerprints: Vec<(PathBuf, Fingerprint)> = dedup::parse_fingerprints(parsed_files)
+        .map(|(pf, fingerprint)| (pf.path, fingerprint))
+        .collect();
+    let duplicates = dedup::find_duplicates(&fingerprints, threshold);
+
+    cli.read_opts.print_report(&report);
+
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+    match format {
+        Format::Plain => format.print_plain(
+            duplicates.iter().map(|dup| {
+                let similarity = if dup.exact {
+                    "exact duplicate".to_string()
+                } else {
+                    format!("{:.0}% similar", dup.similarity * 100.0)
+                };
+                format!(
+                    "{} <-> {}: {similarity}",
+                    dup.first.display(),
+                    dup.second.display()
+                )
+            }),
+            &mut writer,
+        ),
+        Format::Json | Format::Binary => format.print_structured(duplicates, &mut writer),
+    }
+}
@@ -0,0 +1,147 @@
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+
+use obsidian_core::{
+    parser::ParseReport,
+    printer::{self, Format},
+    reader::{self, FileEntry},
+};
+use obsidian_export::ExportReport;
+use obsidian_links::parser::LinkStyle;
+
+/// Export the notes in an Obsidian vault to a SQLite database, Parquet files, or a static site.
+///
+/// With `--sqlite` (only available when built with the `sqlite` feature), each note is written to
+/// the `notes` table (path, modified time, size, and word count) along with its frontmatter tags
+/// (`tags`), outgoing links (`links`), and frontmatter properties (`properties`, with values stored
+/// as JSON). Paths and link targets are relative to the vault root, so backlinks can be found by
+/// joining `links.target` against `notes.path`.
+///
+/// Running the export again against the same database only reparses notes that have been modified
+/// since the last export, and removes notes that no longer exist.
+///
+/// With `--parquet` (only available when built with the `parquet` feature), a `notes.parquet` file
+/// (one row per note with its tags and links as lists) and a `properties.parquet` file (one row per
+/// frontmatter property) are written to the given directory, for use with pandas, polars, DuckDB,
+/// and similar tools. Property values are stored as JSON along with typed `text`, `number`, and
+/// `boolean` columns. Parquet exports always include every note.
+///
+/// With `--site`, every note is rendered to an HTML page in the given directory (with other files
+/// like images copied alongside) so the vault can be published as a static site. Links between
+/// notes, including wikilinks, are rewritten to relative URLs of the generated pages, and an
+/// `index.html` listing every note is added unless the vault has its own `index.md`. Notes that
+/// can't be read (like binary files with a `.md` extension) are skipped and can be listed with
+/// `--report-errors`.
+///
+/// Example query:
+/// SELECT n.path FROM notes n JOIN tags t ON t.note_id = n.id WHERE t.tag = 'project';
+///
+/// Example output (plain):
+/// exported: 12
+/// unchanged: 408
+/// removed: 1
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-export", version)]
+#[command(group = clap::ArgGroup::new("target").required(true))]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    #[command(flatten)]
+    pub read_opts: reader::ReaderOpts,
+
+    /// The SQLite database to export to. It is created if it doesn't exist
+    #[cfg(feature = "sqlite")]
+    #[arg(long = "sqlite", group = "target")]
+    pub sqlite: Option<PathBuf>,
+
+    /// The directory to write Parquet files to. It is created if it doesn't exist
+    #[cfg(feature = "parquet")]
+    #[arg(long = "parquet", group = "target")]
+    pub parquet: Option<PathBuf>,
+
+    /// The directory to write a static HTML site to. It is created if it doesn't exist
+    #[arg(long = "site", group = "target")]
+    pub site: Option<PathBuf>,
+
+    /// The vault directory to use as the root for resolving links and paths. If not specified, the
+    /// directory specified in the reader options will be used. If neither is specified, the
+    /// current working directory is used.
+    #[arg(long = "vault-dir")]
+    pub vault_dir: Option<PathBuf>,
+
+    /// The style of links used in the vault. Valid options are "infer", "from_vault_root", and
+    /// "relative_to_file". Default is "infer". See `obsidian-links --help` for more details
+    #[arg(long = "link-style")]
+    pub link_style: Option<LinkStyle>,
+
+    /// Also export tags from this frontmatter property, for vaults that use something like
+    /// `keywords` instead of (or as well as) `tags`. Can be passed multiple times
+    #[arg(long = "tag-key")]
+    pub tag_keys: Vec<String>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli: Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+
+    let entries = cli.read_opts.read_files()?;
+    let vault_root = cli
+        .vault_dir
+        .clone()
+        .or_else(|| cli.read_opts.dir.clone())
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let link_style = cli.link_style.unwrap_or_default();
+    let report = export(&cli, entries, &vault_root, link_style)?;
+
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+    match format {
+        Format::Plain => format.print_plain(
+            [
+                format!("exported: {}", report.exported),
+                format!("unchanged: {}", report.unchanged),
+                format!("removed: {}", report.removed),
+            ]
+            .into_iter(),
+            &mut writer,
+        ),
+        Format::Json | Format::Binary => format.print_structured(report, &mut writer),
+    }
+}
+
+/// Run the export for whichever target was passed
+fn export(
+    cli: &Cli,
+    entries: Vec<FileEntry>,
+    vault_root: &Path,
+    link_style: LinkStyle,
+) -> anyhow::Result<ExportReport> {
+    #[cfg(feature = "sqlite")]
+    if let Some(db) = &cli.sqlite {
+        return obsidian_export::sqlite::export(db, entries, vault_root, link_style, &cli.tag_keys);
+    }
+    #[cfg(feature = "parquet")]
+    if let Some(dir) = &cli.parquet {
+        let records =
+            obsidian_export::note_records(entries, vault_root, link_style, &cli.tag_keys)?;
+        return obsidian_export::parquet::export(dir, &records);
+    }
+    match &cli.site {
+        Some(dir) => {
+            let mut failures = ParseReport::default();
+            let report = obsidian_export::site::export_site(
+                entries,
+                vault_root,
+                link_style,
+                dir,
+                &mut failures,
+            )?;
+            cli.read_opts.print_report(&failures);
+            Ok(report)
+        }
+        None => unreachable!("clap requires an export target"),
+    }
+}
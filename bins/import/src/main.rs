@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Parser;
+
+use obsidian_core::{
+    import::{self, ImportFormat, Importer},
+    printer::{self, Format},
+    reader,
+    writer::Transaction,
+};
+
+/// Bulk import property values into the frontmatter of notes in an Obsidian vault.
+///
+/// The input is a CSV or JSON file (or stdin) containing one record per note. Each record is
+/// matched to a note by either a `path` (relative to the vault root, `.md` optional) or a `title`
+/// (the file name without the extension). All other columns/fields are set as properties in the
+/// note's frontmatter. This makes it easy to load metadata from external tools (e.g. ratings or
+/// URLs from a Readwise export) and then query it like any other property.
+///
+/// Example CSV input:
+///
+/// title,rating,url
+/// Aider,5,https://aider.chat
+/// Ghostty,4,https://ghostty.org
+///
+/// Example JSON input:
+///
+/// [{"path": "References/Aider.md", "rating": 5, "tags": ["ai", "cli"]}]
+///
+/// By default, existing properties are left untouched. Pass `--overwrite` to replace them. All
+/// changes are computed and validated before anything is written, so if any file can't be updated
+/// (e.g. its frontmatter is invalid), no files are written.
+///
+/// The output is a report of which files were updated along with any records that didn't match
+/// exactly one note.
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-import", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    /// The vault directory containing the notes to update
+    pub vault_dir: PathBuf,
+
+    /// The file to import from. If not specified, or set to `-`, the records are read from stdin
+    #[arg(short = 'i', long)]
+    pub input: Option<PathBuf>,
+
+    /// The format of the input. Valid options are "csv" and "json". If not specified, the format is
+    /// inferred from the input file extension
+    #[arg(long)]
+    pub format: Option<ImportFormat>,
+
+    /// Overwrite properties that already exist in a note's frontmatter
+    #[arg(long, default_value_t = false)]
+    pub overwrite: bool,
+
+    /// Report what would change without writing anything to disk
+    #[arg(long = "dry-run", default_value_t = false)]
+    pub dry_run: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli: Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+
+    let input = cli.input.as_ref().filter(|p| p.as_os_str() != "-");
+    let format = cli
+        .format
+        .or_else(|| input.and_then(ImportFormat::from_path))
+        .context("Unable to infer the input format, please specify --format")?;
+    let records = match input {
+        Some(path) => import::read_records(
+            std::fs::File::open(path)
+                .with_context(|| format!("Failed to open {}", path.display()))?,
+            format,
+        )?,
+        None => import::read_records(std::io::stdin().lock(), format)?,
+    };
+
+    let entries = reader::read_dir(&cli.vault_dir, true)?;
+    let importer = Importer::new(&cli.vault_dir, entries).overwrite(cli.overwrite);
+    let mut tx = Transaction::new();
+    let report = importer.apply(&mut tx, records)?;
+
+    if cli.dry_run {
+        log::info!(
+            "Dry run, not writing {} changed files",
+            report.updated.len()
+        );
+    } else {
+        tx.commit()?;
+    }
+
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+    match format {
+        Format::Plain => format.print_plain(
+            report
+                .updated
+                .iter()
+                .map(|p| format!("updated: {}", p.display()))
+                .chain(
+                    report
+                        .unchanged
+                        .iter()
+                        .map(|p| format!("unchanged: {}", p.display())),
+                )
+                .chain(report.unmatched.iter().map(|k| format!("unmatched: {k}")))
+                .chain(report.ambiguous.iter().map(|k| format!("ambiguous: {k}"))),
+            &mut writer,
+        ),
+        Format::Json | Format::Binary => format.print_structured(report, &mut writer),
+    }
+}
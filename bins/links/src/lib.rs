@@ -0,0 +1,243 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use clap::Parser;
+use comrak::Arena;
+use serde::Serialize;
+
+use obsidian_core::{
+    parser::{self, ParseReport},
+    printer::{self, Format},
+    reader,
+    writer::Transaction,
+};
+use obsidian_links::fix::{self, LinkSuggestion};
+
+/// Generate and print an adjacency list of links between markdown files in an Obsidian vault.
+///
+/// By default, this tool will read all markdown files in the specified directory and parse all
+/// links found within them. If passing via stdin, it is highly recommended you set the
+/// `--vault-dir` option to ensure links are resolved correctly.
+///
+/// The output will be an adjacency list where each line contains a source file followed by an
+/// object that indicate whether the file exists and any links and backlinks it has. For this tool,
+/// the plain text output format is mostly useful for human consumption as there isn't a great way
+/// to represent a graph in plain text.
+///
+/// Example output (json):
+/// {
+///   "/path/to/vault/References/Aider.md": {
+///     "exists": true,
+///     "links": [
+///       "/path/to/vault/References/Claude Code.md",
+///       "/path/to/vault/References/Roo Code.md"
+///     ],
+///     "backlinks": []
+///   },
+///   "/path/to/vault/References/Claude Code.md": {
+///     "exists": true,
+///     "links": [],
+///     "backlinks": [
+///       "/path/to/vault/References/Aider.md"
+///     ]
+///   },
+///   "/path/to/vault/References/Ghostty.md": {
+///     "exists": true,
+///     "links": [
+///       "/path/to/vault/References/Warp"
+///     ],
+///     "backlinks": []
+///   },
+///   "/path/to/vault/References/OpenRouter.md": {
+///     "exists": false,
+///     "links": [],
+///     "backlinks": [
+///       "/path/to/vault/References/aichat.md"
+///     ]
+///   },
+///   "/path/to/vault/References/Roo Code.md": {
+///     "exists": false,
+///     "links": [],
+///     "backlinks": [
+///       "/path/to/vault/References/Aider.md"
+///     ]
+///   },
+///   "/path/to/vault/References/Warp": {
+///     "exists": false,
+///     "links": [],
+///     "backlinks": [
+///       "/path/to/vault/References/Ghostty.md"
+///     ]
+///   },
+///   "/path/to/vault/References/aichat.md": {
+///     "exists": true,
+///     "links": [
+///       "/path/to/vault/References/OpenRouter.md"
+///     ],
+///     "backlinks": []
+///   }
+/// }
+///
+/// With `--fix-links`, the tool instead suggests the closest existing note (by file name) for each
+/// broken link and rewrites the links to point at it. Pass `--dry-run` along with it to only print
+/// the suggestions and a diff of each change.
+///
+/// Example output (plain, with --fix-links):
+/// /path/to/vault/References/Roo Code.md -> /path/to/vault/References/RooCode.md (0.88)
+/// updated: /path/to/vault/References/Aider.md
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-links", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    #[command(flatten)]
+    pub read_opts: reader::ReaderOpts,
+
+    /// The vault directory to use as the root for resolving links. If not specified, the directory
+    /// specified in the reader options will be used. If neither is specified, links will be
+    /// resolved relative to the current working directory.
+    #[arg(long = "vault-dir")]
+    pub vault_dir: Option<PathBuf>,
+
+    /// The style of link to parse from the markdown files. Valid options are "infer", "from_vault_root",
+    /// and "relative_to_file". Default is "infer".
+    ///
+    /// "infer": If the link starts with `./` or `../` or it is a path with a single element (e.g.
+    /// `file.md`), it is considered relative to the file. Otherwise, it is considered relative to
+    /// the vault root.
+    ///
+    /// "from_vault_root": All links are considered relative to the vault root.
+    ///
+    /// "relative_to_file": All links are considered relative to the file they are found in.
+    #[arg(long = "link-style")]
+    pub link_style: Option<obsidian_links::parser::LinkStyle>,
+
+    /// Whether to include orphaned files (i.e. files with no links and no backlinks) in the output.
+    /// Defaults to false
+    #[arg(long = "include-orphans", default_value_t = false)]
+    pub include_orphans: bool,
+
+    /// Suggest the closest existing note for each broken link and update the links to point at it
+    #[arg(long = "fix-links", default_value_t = false)]
+    pub fix_links: bool,
+
+    /// When used with `--fix-links`, print the suggestions and a diff of the changes without
+    /// writing anything to disk
+    #[arg(long = "dry-run", default_value_t = false, requires = "fix_links")]
+    pub dry_run: bool,
+
+    /// The minimum similarity (from 0 to 1) between a broken link and a note name for the note to
+    /// be suggested by `--fix-links`
+    #[arg(long = "min-similarity", default_value_t = fix::DEFAULT_MIN_SIMILARITY)]
+    pub min_similarity: f64,
+}
+
+#[derive(Serialize)]
+struct FixOutput {
+    suggestions: Vec<LinkSuggestion>,
+    updated: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    diffs: BTreeMap<PathBuf, String>,
+}
+
+/// Run the command with the given arguments
+pub fn run(cli: Cli) -> anyhow::Result<()> {
+    let entries = cli.read_opts.read_files()?;
+    let vault_root = cli
+        .vault_dir
+        .clone()
+        .or_else(|| cli.read_opts.dir.clone())
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let arena = Arena::with_capacity(entries.len());
+    let mut report = ParseReport::default();
+    let parsed_files = parser::report_error_iter(
+        parser::parse_files_with_progress(&arena, entries, cli.read_opts.progress_bar()),
+        &mut report,
+    );
+    let link_style = cli.link_style.unwrap_or_default();
+    let mut links = obsidian_links::parser::parse_links(parsed_files, &vault_root, link_style)
+        .try_fold(obsidian_links::Links::new(), |mut acc, (from, to)| {
+            // Unlike below, this file should exist, so we can canonicalize it
+            let from_path = from.path.canonicalize()?;
+            let to = to
+                .iter()
+                .map(|p| obsidian_links::resolve_link_path(p))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            acc.insert_links(from_path, to);
+            anyhow::Ok(acc)
+        })?;
+
+    cli.read_opts.print_report(&report);
+
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+
+    if cli.fix_links {
+        let suggestions = fix::suggest_fixes(&links, cli.min_similarity);
+        let mut tx = Transaction::new();
+        let updated = fix::apply_fixes(&vault_root, link_style, &suggestions, &mut tx)?;
+        let diffs: BTreeMap<PathBuf, String> = if cli.dry_run {
+            tx.diffs()?.into_iter().collect()
+        } else {
+            tx.commit()?;
+            BTreeMap::new()
+        };
+        return match format {
+            Format::Plain => format.print_plain(
+                suggestions
+                    .iter()
+                    .map(|s| {
+                        format!(
+                            "{} -> {} ({:.2})",
+                            s.target.display(),
+                            s.suggestion.display(),
+                            s.similarity
+                        )
+                    })
+                    .chain(if cli.dry_run {
+                        diffs.values().cloned().collect::<Vec<_>>()
+                    } else {
+                        updated
+                            .iter()
+                            .map(|p| format!("updated: {}", p.display()))
+                            .collect()
+                    }),
+                &mut writer,
+            ),
+            Format::Json | Format::Binary => format.print_structured(
+                FixOutput {
+                    suggestions,
+                    updated,
+                    diffs,
+                },
+                &mut writer,
+            ),
+        };
+    }
+
+    if !cli.include_orphans {
+        links.prune_orphans();
+    }
+
+    match format {
+        Format::Plain => format.print_plain(
+            links.into_iter().map(|(p, info)| {
+                format!(
+                    "{}: [{}]",
+                    p.display(),
+                    // This does a bunch of allocations. If for some reason this slows things down
+                    // or takes up a lot of memory with big vaults, we can optimize by converting to
+                    // a string and then building the final string manually
+                    info.links
+                        .into_iter()
+                        .map(|p| p.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }),
+            &mut writer,
+        ),
+        Format::Json | Format::Binary => format.print_structured(links, &mut writer),
+    }
+}
@@ -0,0 +1,73 @@
+use std::{
+    io::{BufRead, Write},
+    path::PathBuf,
+};
+
+use clap::Parser;
+
+mod server;
+
+use server::{Server, Vault};
+
+/// Serve vault data to LLM agents over the Model Context Protocol (MCP).
+///
+/// This tool indexes the vault in the given directory once at startup and then speaks MCP over
+/// stdin/stdout (one JSON-RPC message per line), so it can be configured as a local MCP server in
+/// any MCP capable client. Logs are written to stderr so they don't interfere with the protocol.
+///
+/// The following tools are exposed:
+/// - list_tags: All frontmatter tags in the vault and the notes that use them
+/// - get_links: The links and backlinks of a note
+/// - get_backlinks: Just the backlinks of a note
+/// - search_notes: Full text search over note content and frontmatter
+///
+/// Paths passed to the tools may be absolute or relative to the vault directory.
+///
+/// Example client configuration:
+/// {
+///   "mcpServers": {
+///     "obsidian": {
+///       "command": "obsidian-mcp",
+///       "args": ["/path/to/vault"]
+///     }
+///   }
+/// }
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-mcp", version)]
+pub struct Cli {
+    /// The vault directory to serve. Unlike the other tools this can't be read from stdin, since
+    /// stdin is used for the protocol
+    pub dir: PathBuf,
+
+    /// Whether to recurse into subdirectories when reading the vault. Defaults to true
+    #[arg(long, default_value_t = true)]
+    pub recurse: bool,
+
+    /// Also list tags from this frontmatter property, for vaults that use something like
+    /// `keywords` instead of (or as well as) `tags`. Can be passed multiple times
+    #[arg(long = "tag-key")]
+    pub tag_keys: Vec<String>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli: Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+
+    let vault = Vault::load(&cli.dir, cli.recurse, &cli.tag_keys)?;
+    log::info!("Indexed {} notes in {}", vault.len(), cli.dir.display());
+    let server = Server::new(vault);
+
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_message(&line) {
+            writeln!(stdout, "{response}")?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
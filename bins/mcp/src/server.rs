@@ -0,0 +1,329 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use comrak::Arena;
+use serde_json::{Value, json};
+
+use obsidian_core::{
+    frontmatter, parser, reader,
+    search::{self, VaultIndex},
+};
+use obsidian_links::{Links, parser::LinkStyle};
+
+/// The MCP protocol version we implement. Clients asking for a different version get this one back
+/// and can decide whether to continue
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+// Standard JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// All of the indexed data for a vault. This is built once on startup
+pub struct Vault {
+    root: PathBuf,
+    tags: BTreeMap<String, BTreeSet<PathBuf>>,
+    links: Links,
+    index: VaultIndex,
+}
+
+impl Vault {
+    /// Read, parse, and index the vault at the given directory. Tags are also read from any of the
+    /// given tag keys
+    pub fn load(dir: &Path, recurse: bool, tag_keys: &[String]) -> Result<Self> {
+        let root = dir
+            .canonicalize()
+            .with_context(|| format!("Unable to open vault {}", dir.display()))?;
+        let entries = reader::read_dir(&root, recurse)?;
+        let arena = Arena::with_capacity(entries.len());
+        let parsed_files = parser::ignore_error_iter(parser::parse_files(&arena, entries));
+
+        let mut tags: BTreeMap<String, BTreeSet<PathBuf>> = BTreeMap::new();
+        let mut links = Links::new();
+        let mut index = VaultIndex::new();
+        for (pf, targets) in
+            obsidian_links::parser::parse_links(parsed_files, &root, LinkStyle::default())
+        {
+            let path = pf.path.canonicalize()?;
+            if let Some(fm) = frontmatter::parse_frontmatter_from_ast(pf.ast) {
+                for tag in fm.with_tag_keys(tag_keys).tags.unwrap_or_default() {
+                    tags.entry(tag).or_default().insert(path.clone());
+                }
+            }
+            index.add(&path, pf.ast);
+            let targets = targets
+                .iter()
+                .map(|p| obsidian_links::resolve_link_path(p))
+                .collect::<Result<Vec<_>>>()?;
+            links.insert_links(path, targets);
+        }
+
+        Ok(Self {
+            root,
+            tags,
+            links,
+            index,
+        })
+    }
+
+    /// The number of notes in the vault
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Resolve a path given by a client, which may be relative to the vault root
+    fn resolve(&self, path: &str) -> Result<PathBuf> {
+        obsidian_links::resolve_link_path(&self.root.join(path))
+    }
+}
+
+/// A minimal MCP server that handles one JSON-RPC message at a time
+pub struct Server {
+    vault: Vault,
+}
+
+impl Server {
+    pub fn new(vault: Vault) -> Self {
+        Self { vault }
+    }
+
+    /// Handle a single JSON-RPC message, returning the serialized response. Notifications (messages
+    /// without an id) don't get a response
+    pub fn handle_message(&self, message: &str) -> Option<String> {
+        let request: Value = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(e) => {
+                return Some(error_response(Value::Null, PARSE_ERROR, e.to_string()).to_string());
+            }
+        };
+        // Notifications (like `notifications/initialized`) need no response
+        let id = request.get("id")?.clone();
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            return Some(
+                error_response(id, INVALID_REQUEST, "Missing method".to_string()).to_string(),
+            );
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let response = match method {
+            "initialize" => success_response(id, self.initialize(&params)),
+            "ping" => success_response(id, json!({})),
+            "tools/list" => success_response(id, json!({ "tools": tool_definitions() })),
+            "tools/call" => success_response(id, self.call_tool(&params)),
+            _ => error_response(id, METHOD_NOT_FOUND, format!("Unknown method {method}")),
+        };
+        Some(response.to_string())
+    }
+
+    fn initialize(&self, _params: &Value) -> Value {
+        json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": {
+                "name": "obsidian-mcp",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        })
+    }
+
+    /// Call a tool. Errors from tools are reported in the result (rather than as JSON-RPC errors)
+    /// so the model can see them, as the MCP spec recommends
+    fn call_tool(&self, params: &Value) -> Value {
+        let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+        let args = params.get("arguments").cloned().unwrap_or(Value::Null);
+        let result = match name {
+            "list_tags" => Ok(json!(self.vault.tags)),
+            "get_links" => self.file_links(&args, |links| json!(links)),
+            "get_backlinks" => self.file_links(&args, |links| json!(links.backlinks)),
+            "search_notes" => self.search(&args),
+            _ => Err(anyhow::anyhow!("Unknown tool {name}")),
+        };
+        match result {
+            Ok(value) => json!({
+                "content": [{ "type": "text", "text": value.to_string() }],
+                "isError": false,
+            }),
+            Err(e) => json!({
+                "content": [{ "type": "text", "text": format!("{e:#}") }],
+                "isError": true,
+            }),
+        }
+    }
+
+    fn file_links(
+        &self,
+        args: &Value,
+        f: impl FnOnce(&obsidian_links::FileLinks) -> Value,
+    ) -> Result<Value> {
+        let path = self.vault.resolve(string_arg(args, "path")?)?;
+        let links = self
+            .vault
+            .links
+            .get(&path)
+            .with_context(|| format!("{} is not a note in the vault", path.display()))?;
+        Ok(f(links))
+    }
+
+    fn search(&self, args: &Value) -> Result<Value> {
+        let query = string_arg(args, "query")?;
+        let mut hits: Vec<search::SearchHit> = self.vault.index.search(query);
+        if let Some(limit) = args.get("limit").and_then(Value::as_u64) {
+            hits.truncate(limit as usize);
+        }
+        Ok(json!(hits))
+    }
+}
+
+fn string_arg<'a>(args: &'a Value, name: &str) -> Result<&'a str> {
+    args.get(name)
+        .and_then(Value::as_str)
+        .with_context(|| format!("Missing required string argument {name}"))
+}
+
+fn tool_definitions() -> Value {
+    let path_schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "The path of the note, either absolute or relative to the vault root (e.g. `Projects/Homelab.md`)"
+            }
+        },
+        "required": ["path"]
+    });
+    json!([
+        {
+            "name": "list_tags",
+            "description": "List all frontmatter tags in the vault along with the paths of the notes using each tag",
+            "inputSchema": { "type": "object", "properties": {} }
+        },
+        {
+            "name": "get_links",
+            "description": "Get whether a note exists, the notes it links to, and the notes linking to it",
+            "inputSchema": path_schema
+        },
+        {
+            "name": "get_backlinks",
+            "description": "Get the paths of all notes that link to a note",
+            "inputSchema": path_schema
+        },
+        {
+            "name": "search_notes",
+            "description": "Full text search over note content and frontmatter. Every term must match and a trailing `*` matches prefixes. Returns note paths with the most relevant first",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "The search query" },
+                    "limit": { "type": "integer", "description": "The maximum number of results" }
+                },
+                "required": ["query"]
+            }
+        }
+    ])
+}
+
+fn success_response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> Server {
+        let vault = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../test-vault");
+        Server::new(Vault::load(&vault, true, &[]).expect("test vault should load"))
+    }
+
+    fn call(server: &Server, request: Value) -> Value {
+        let response = server
+            .handle_message(&request.to_string())
+            .expect("expected a response");
+        serde_json::from_str(&response).unwrap()
+    }
+
+    fn tool_text(server: &Server, name: &str, arguments: Value) -> (bool, String) {
+        let response = call(
+            server,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": { "name": name, "arguments": arguments }
+            }),
+        );
+        let result = &response["result"];
+        (
+            result["isError"].as_bool().unwrap(),
+            result["content"][0]["text"].as_str().unwrap().to_string(),
+        )
+    }
+
+    #[test]
+    fn handles_lifecycle_messages() {
+        let server = server();
+        let init = call(
+            &server,
+            json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {} }),
+        );
+        assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSION);
+
+        assert!(
+            server
+                .handle_message(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+                .is_none()
+        );
+
+        let tools = call(
+            &server,
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }),
+        );
+        assert_eq!(tools["result"]["tools"].as_array().unwrap().len(), 4);
+
+        let unknown = call(
+            &server,
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "nope" }),
+        );
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+
+        let invalid: Value =
+            serde_json::from_str(&server.handle_message("not json").unwrap()).unwrap();
+        assert_eq!(invalid["error"]["code"], PARSE_ERROR);
+    }
+
+    #[test]
+    fn calls_tools() {
+        let server = server();
+
+        let (is_error, text) = tool_text(&server, "list_tags", json!({}));
+        assert!(!is_error);
+        let tags: BTreeMap<String, Vec<PathBuf>> = serde_json::from_str(&text).unwrap();
+        assert_eq!(tags.keys().collect::<Vec<_>>(), vec!["bar", "foo"]);
+
+        let (is_error, text) = tool_text(&server, "get_backlinks", json!({ "path": "Test.md" }));
+        assert!(!is_error);
+        let backlinks: Vec<PathBuf> = serde_json::from_str(&text).unwrap();
+        assert_eq!(backlinks.len(), 1);
+        assert!(backlinks[0].ends_with("links/Source.md"));
+
+        let (is_error, text) = tool_text(
+            &server,
+            "search_notes",
+            json!({ "query": "placeholder", "limit": 2 }),
+        );
+        assert!(!is_error);
+        let hits: Vec<Value> = serde_json::from_str(&text).unwrap();
+        assert_eq!(hits.len(), 2);
+
+        let (is_error, text) = tool_text(&server, "get_links", json!({}));
+        assert!(is_error);
+        assert!(text.contains("path"), "got {text}");
+    }
+}
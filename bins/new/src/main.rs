@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use chrono::{Local, NaiveDate};
+use clap::Parser;
+
+use obsidian_core::{
+    periodic::{self, NoteDateFormat},
+    printer::{self, Format},
+    scaffold::Scaffold,
+};
+
+/// Create a new note in an Obsidian vault from a template.
+///
+/// Templates use the same variables as the core Templates plugin: `{{title}}`, `{{date}}`,
+/// `{{time}}`, and custom formats like `{{date:dddd, MMMM D}}`. The templates folder and the default
+/// date and time formats are read from the vault's Templates plugin settings. Templates are looked
+/// up by name in the templates folder first and then relative to the vault root.
+///
+/// Pass `--daily` to create the daily note for today (or `--date`) using the name, folder, and
+/// template from the Daily Notes plugin settings. Existing notes are never overwritten.
+///
+/// The output is the path of the created note.
+///
+/// Example output (plain):
+/// /path/to/vault/Projects/Homelab.md
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-new", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    /// The vault directory to create the note in
+    pub vault_dir: PathBuf,
+
+    /// The path of the new note relative to the vault root. The `.md` extension is optional. Not
+    /// needed when using `--daily`
+    #[arg(required_unless_present = "daily")]
+    pub name: Option<PathBuf>,
+
+    /// The name of the template to use. If not specified, an empty note is created (or the daily
+    /// note template is used with `--daily`)
+    #[arg(short = 't', long = "template")]
+    pub template: Option<String>,
+
+    /// Create a daily note instead of a named note
+    #[arg(long = "daily", default_value_t = false, conflicts_with = "name")]
+    pub daily: bool,
+
+    /// The date (YYYY-MM-DD) to use for template variables and daily notes. Defaults to today
+    #[arg(long = "date")]
+    pub date: Option<NaiveDate>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli: Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+
+    let scaffold = Scaffold::from_vault(&cli.vault_dir)?;
+    let now = Local::now().naive_local();
+    let now = cli.date.map(|d| d.and_time(now.time())).unwrap_or(now);
+
+    let (name, template) = if cli.daily {
+        let format = NoteDateFormat::daily_notes(&cli.vault_dir)?;
+        let template = match cli.template {
+            Some(template) => Some(template),
+            None => periodic::daily_notes_template(&cli.vault_dir)?,
+        };
+        (format.note_path(&cli.vault_dir, now.date()), template)
+    } else {
+        (cli.name.context("A note name is required")?, cli.template)
+    };
+    let template = template
+        .map(|name| scaffold.find_template(&name))
+        .transpose()?;
+    let path = scaffold.create_note(template.as_deref(), name, now)?;
+
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+    match format {
+        Format::Plain => format.print_plain(std::iter::once(path.display()), &mut writer),
+        Format::Json | Format::Binary => format.print_structured(path, &mut writer),
+    }
+}
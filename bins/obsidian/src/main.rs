@@ -0,0 +1,38 @@
+use clap::{Parser, Subcommand};
+
+/// All of the most common Obsidian utilities in a single command.
+///
+/// Each subcommand takes exactly the same flags and produces the same output as the standalone
+/// tool it is named after (e.g. `obsidian tags` is the same as `obsidian-tags`), so output can be
+/// piped between them the same way. Run `obsidian <command> --help` for the details of each one.
+///
+/// Example:
+///
+/// obsidian tags /path/to/vault --filter 'tag:technology,cli' | obsidian links --vault-dir /path/to/vault
+#[derive(Parser, Debug)]
+#[command(name = "obsidian", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    // The help for each of these comes from the standalone tool
+    Tags(tags::Cli),
+    Links(links::Cli),
+    Stats(stats::Cli),
+    Search(search::Cli),
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli: Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+
+    match cli.command {
+        Command::Tags(cli) => tags::run(cli),
+        Command::Links(cli) => links::run(cli),
+        Command::Stats(cli) => stats::run(cli),
+        Command::Search(cli) => search::run(cli),
+    }
+}
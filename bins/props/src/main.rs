@@ -0,0 +1,269 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use clap::Parser;
+use comrak::Arena;
+use serde_norway::Value;
+
+use obsidian_core::{
+    coerce::{CoercionMode, CoercionPolicy, PropertyType},
+    frontmatter,
+    migrate::{self, Migration},
+    parser::{self, ParseReport},
+    periodic::NoteDateFormat,
+    printer::{self, Format},
+    properties::PropertyFilter,
+    reader,
+    writer::Transaction,
+};
+
+/// Query frontmatter properties across markdown files in an Obsidian vault.
+///
+/// By default, this tool will read all markdown files in the specified directory and list every
+/// frontmatter property used in the vault along with how many files set it.
+///
+/// When `--key` is passed, the value of that property is printed for every file that sets it
+/// instead. When `--where` is passed (without `--key`), the files matching all of the filters are
+/// printed, so the output can be piped into the other tools. Filters are also applied before
+/// listing properties or values.
+///
+/// Filter syntax:
+/// - key : The property is set
+/// - !key : The property is not set
+/// - key=value / key!=value : The property equals (or doesn't equal) the value. For lists (like
+///   tags), this checks whether any item equals the value. Links (like `[[Note]]`) match either the
+///   whole link or just the note it points to
+/// - key<value, key<=value, key>value, key>=value : Compares numbers numerically and anything else
+///   as text (which works for ISO dates like 2025-01-15)
+///
+/// Before comparing, property values and filter values are coerced to a type. In the default
+/// `--coerce lenient` mode, the type is inferred from the value, so quoted values like '5',
+/// 'true', or '2025-01-15' compare as numbers, booleans, and dates. In `--coerce strict` mode,
+/// only unquoted YAML values are typed and everything else compares as text. A property's type can
+/// also be declared with `--type key=type` (text, list, number, checkbox, date, datetime, or link)
+/// to skip inference for it. Types set in Obsidian's Properties view (stored in
+/// `.obsidian/types.json`) are used automatically, with `--type` taking priority over them.
+///
+/// Properties can also be migrated across the vault (only touching files matching any `--where`
+/// filters) with `--rename`, `--to-list`, and `--normalize-date`. Renames are applied first, then
+/// list conversions, then date normalization. All changes are computed and validated before
+/// anything is written, so if any file can't be updated (e.g. a rename where both properties are
+/// set), no files are written. Pass `--dry-run` to print a diff of each change instead of writing
+/// it.
+///
+/// Example output (plain):
+/// aliases: 12
+/// status: 48
+/// tags: 301
+///
+/// Example output (plain, with --key status):
+/// /path/to/vault/Projects/Website.md: in-progress
+/// /path/to/vault/Projects/Homelab.md: done
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-props", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    #[command(flatten)]
+    pub read_opts: reader::ReaderOpts,
+
+    /// Print the value of this property for each file that sets it
+    #[arg(short = 'k', long = "key")]
+    pub key: Option<String>,
+
+    /// Only include files matching this filter (see above for the syntax). Can be passed multiple
+    /// times, in which case files must match all filters
+    #[arg(short = 'w', long = "where")]
+    pub filters: Vec<PropertyFilter>,
+
+    /// How to coerce property values when filtering. Valid options are "lenient" (infer the type
+    /// of quoted values too) and "strict" (only use the YAML type). Default is "lenient"
+    #[arg(long = "coerce", default_value = "lenient")]
+    pub coerce: CoercionMode,
+
+    /// Declare the type of a property for filtering, written as `key=type`. Can be passed multiple
+    /// times
+    #[arg(long = "type", value_parser = parse_type)]
+    pub types: Vec<(String, PropertyType)>,
+
+    /// The vault directory to load property types from. If not specified, the directory specified
+    /// in the reader options will be used. If neither is specified, the current working directory
+    /// is used.
+    #[arg(long = "vault-dir")]
+    pub vault_dir: Option<PathBuf>,
+
+    /// Rename a property, written as `old=new`. Can be passed multiple times
+    #[arg(long = "rename", value_parser = parse_rename)]
+    pub rename: Vec<(String, String)>,
+
+    /// Convert a scalar property to a list containing that value (e.g. `alias: foo` becomes
+    /// `alias: [foo]`). Can be passed multiple times
+    #[arg(long = "to-list")]
+    pub to_list: Vec<String>,
+
+    /// Rewrite the dates in a property using `--date-format`. Values that aren't recognized as a
+    /// date are left alone. Can be passed multiple times
+    #[arg(long = "normalize-date")]
+    pub normalize_date: Vec<String>,
+
+    /// The Moment.js format (the same formats used in the Obsidian settings) to write dates in when
+    /// using `--normalize-date`
+    #[arg(long = "date-format", default_value = "YYYY-MM-DD")]
+    pub date_format: String,
+
+    /// Print a diff of what migrations would change without writing anything to disk
+    #[arg(long = "dry-run", default_value_t = false)]
+    pub dry_run: bool,
+}
+
+impl Cli {
+    fn migrations(&self) -> anyhow::Result<Vec<Migration>> {
+        let date_format = NoteDateFormat::new(&self.date_format)?;
+        let renames = self.rename.iter().map(|(from, to)| Migration::Rename {
+            from: from.clone(),
+            to: to.clone(),
+        });
+        let to_list = self
+            .to_list
+            .iter()
+            .map(|key| Migration::ToList { key: key.clone() });
+        let dates = self
+            .normalize_date
+            .iter()
+            .map(|key| Migration::NormalizeDate {
+                key: key.clone(),
+                format: date_format.clone(),
+            });
+        Ok(renames.chain(to_list).chain(dates).collect())
+    }
+
+    fn coercion_policy(&self) -> anyhow::Result<CoercionPolicy> {
+        let vault_root = self
+            .vault_dir
+            .clone()
+            .or_else(|| self.read_opts.dir.clone())
+            .unwrap_or_else(|| PathBuf::from("."));
+        let policy = CoercionPolicy::from_vault(vault_root, self.coerce)?;
+        Ok(self.types.iter().fold(policy, |policy, (key, ty)| {
+            policy.with_type(key.clone(), *ty)
+        }))
+    }
+}
+
+fn parse_type(s: &str) -> anyhow::Result<(String, PropertyType)> {
+    let (key, ty) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("Expected a type in the form key=type, got {s}"))?;
+    let key = key.trim();
+    anyhow::ensure!(!key.is_empty(), "Property name in {s} must not be empty");
+    Ok((key.to_string(), ty.trim().parse()?))
+}
+
+fn parse_rename(s: &str) -> anyhow::Result<(String, String)> {
+    let (from, to) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("Expected a rename in the form old=new, got {s}"))?;
+    let (from, to) = (from.trim(), to.trim());
+    anyhow::ensure!(
+        !from.is_empty() && !to.is_empty(),
+        "Property names in {s} must not be empty"
+    );
+    Ok((from.to_string(), to.to_string()))
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli: Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+
+    let migrations = cli.migrations()?;
+    let policy = cli.coercion_policy()?;
+    let entries = cli.read_opts.read_files()?;
+
+    let arena = Arena::with_capacity(entries.len());
+    let mut report = ParseReport::default();
+    let parsed_files = parser::report_error_iter(
+        parser::parse_files_with_progress(&arena, entries, cli.read_opts.progress_bar()),
+        &mut report,
+    );
+    let matching: Vec<_> = frontmatter::parse_frontmatter(parsed_files)
+        .filter(|(_, fm)| {
+            cli.filters
+                .iter()
+                .all(|f| f.matches_with(fm.as_ref(), &policy))
+        })
+        .map(|(pf, fm)| (pf.path, fm))
+        .collect();
+
+    cli.read_opts.print_report(&report);
+
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+    if !migrations.is_empty() {
+        let mut tx = Transaction::new();
+        let changed =
+            migrate::migrate_files(&mut tx, matching.iter().map(|(path, _)| path), &migrations)?;
+        if cli.dry_run {
+            let diffs: BTreeMap<PathBuf, String> = tx.diffs()?.into_iter().collect();
+            return match format {
+                Format::Plain => format.print_plain(diffs.values(), &mut writer),
+                Format::Json | Format::Binary => format.print_structured(diffs, &mut writer),
+            };
+        }
+        tx.commit()?;
+        match format {
+            Format::Plain => format.print_plain(
+                changed.iter().map(|p| format!("updated: {}", p.display())),
+                &mut writer,
+            ),
+            Format::Json | Format::Binary => format.print_structured(changed, &mut writer),
+        }
+    } else if let Some(key) = &cli.key {
+        let values: BTreeMap<PathBuf, Value> = matching
+            .into_iter()
+            .filter_map(|(path, fm)| Some((path, fm?.get(key)?)))
+            .collect();
+        match format {
+            Format::Plain => format.print_plain(
+                values
+                    .iter()
+                    .map(|(path, value)| format!("{}: {}", path.display(), display_value(value))),
+                &mut writer,
+            ),
+            Format::Json | Format::Binary => format.print_structured(values, &mut writer),
+        }
+    } else if !cli.filters.is_empty() {
+        let files: BTreeSet<PathBuf> = matching.into_iter().map(|(path, _)| path).collect();
+        match format {
+            Format::Plain => format.print_plain(files.iter().map(|p| p.display()), &mut writer),
+            Format::Json | Format::Binary => format.print_structured(files, &mut writer),
+        }
+    } else {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for fm in matching.iter().filter_map(|(_, fm)| fm.as_ref()) {
+            for key in fm.keys() {
+                *counts.entry(key.to_string()).or_default() += 1;
+            }
+        }
+        match format {
+            Format::Plain => format.print_plain(
+                counts.iter().map(|(key, count)| format!("{key}: {count}")),
+                &mut writer,
+            ),
+            Format::Json | Format::Binary => format.print_structured(counts, &mut writer),
+        }
+    }
+}
+
+/// Print scalars as is and anything else (like lists) as inline JSON
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => String::new(),
+        other => serde_json::to_string(other).unwrap_or_else(|_| format!("{other:?}")),
+    }
+}
@@ -0,0 +1,122 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use clap::Parser;
+use serde::Serialize;
+
+use obsidian_core::{
+    printer::{self, Format},
+    writer::Transaction,
+};
+use obsidian_links::rename::{self, RenameReport};
+
+/// Rename or move a note in an Obsidian vault, updating every link that points to it.
+///
+/// All wiki and markdown links to the note are rewritten to the new path, keeping any headings,
+/// aliases, and percent encoding in the links. If the note moves to a different folder, the
+/// relative links inside the note are updated too. All changes are computed before anything is
+/// written, and nothing is changed if the rename conflicts with an existing file.
+///
+/// Pass `--dry-run` to print a diff of every file that would change instead.
+///
+/// Example output (plain):
+/// renamed: /path/to/vault/Inbox/Idea.md -> /path/to/vault/Projects/Homelab.md
+/// updated: /path/to/vault/Daily/2025-01-15.md
+/// updated: /path/to/vault/Inbox/Idea.md
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-rename", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    /// The vault directory containing the note
+    pub vault_dir: PathBuf,
+
+    /// The current path of the note
+    pub from: PathBuf,
+
+    /// The new path of the note. The `.md` extension is optional
+    pub to: PathBuf,
+
+    /// The style of links used in the vault. Valid options are "infer", "from_vault_root", and
+    /// "relative_to_file". Default is "infer". See `obsidian-links --help` for more details
+    #[arg(long = "link-style")]
+    pub link_style: Option<obsidian_links::parser::LinkStyle>,
+
+    /// Print a diff of the changes without writing anything to disk
+    #[arg(long = "dry-run", default_value_t = false)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize)]
+struct Output {
+    #[serde(flatten)]
+    report: RenameReport,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    diffs: BTreeMap<PathBuf, String>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli: Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+
+    let mut tx = Transaction::new();
+    let report = rename::rename_note(
+        &cli.vault_dir,
+        &cli.from,
+        &cli.to,
+        cli.link_style.unwrap_or_default(),
+        &mut tx,
+    )?;
+    if report.has_conflicts() {
+        let conflicts = report
+            .conflicts
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        anyhow::bail!(
+            "Unable to rename {} to {}: {conflicts}",
+            report.from.display(),
+            report.to.display()
+        );
+    }
+
+    let diffs: BTreeMap<PathBuf, String> = if cli.dry_run {
+        tx.diffs()?.into_iter().collect()
+    } else {
+        tx.commit()?;
+        BTreeMap::new()
+    };
+
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+    match format {
+        Format::Plain => {
+            let verb = if cli.dry_run {
+                "would rename"
+            } else {
+                "renamed"
+            };
+            format.print_plain(
+                std::iter::once(format!(
+                    "{verb}: {} -> {}",
+                    report.from.display(),
+                    report.to.display()
+                ))
+                .chain(if cli.dry_run {
+                    diffs.values().cloned().collect::<Vec<_>>()
+                } else {
+                    report
+                        .updated
+                        .iter()
+                        .map(|p| format!("updated: {}", p.display()))
+                        .collect()
+                }),
+                &mut writer,
+            )
+        }
+        Format::Json | Format::Binary => {
+            format.print_structured(Output { report, diffs }, &mut writer)
+        }
+    }
+}
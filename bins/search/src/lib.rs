@@ -0,0 +1,66 @@
+use clap::Parser;
+use comrak::Arena;
+
+use obsidian_core::{
+    parser::{self, ParseReport},
+    printer::{self, Format},
+    reader, search,
+};
+
+/// Full text search over markdown files in an Obsidian vault.
+///
+/// By default, this tool will read all markdown files in the specified directory, index their
+/// content and frontmatter, and print the notes matching the query with the most relevant first.
+/// Every term in the query must appear in a note for it to match. Terms are matched case
+/// insensitively and a term ending in `*` matches any word starting with it (e.g. `kube*`).
+///
+/// For plain text output, each matching file path is printed on its own line so results can be
+/// piped into other tools. For structured formats, the output is a list of objects with the path
+/// and relevance score of each match.
+///
+/// Example output (plain):
+/// /path/to/vault/References/Kubernetes.md
+/// /path/to/vault/Projects/Homelab.md
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-search", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    #[command(flatten)]
+    pub read_opts: reader::ReaderOpts,
+
+    /// The search query
+    #[arg(short = 'q', long = "query")]
+    pub query: String,
+
+    /// The maximum number of results to print. Defaults to all results
+    #[arg(short = 'n', long = "limit")]
+    pub limit: Option<usize>,
+}
+
+/// Run the command with the given arguments
+pub fn run(cli: Cli) -> anyhow::Result<()> {
+    let entries = cli.read_opts.read_files()?;
+
+    let arena = Arena::with_capacity(entries.len());
+    let mut report = ParseReport::default();
+    let parsed_files = parser::report_error_iter(
+        parser::parse_files_with_progress(&arena, entries, cli.read_opts.progress_bar()),
+        &mut report,
+    );
+    let index = search::index_files(parsed_files);
+    let mut hits = index.search(&cli.query);
+    if let Some(limit) = cli.limit {
+        hits.truncate(limit);
+    }
+
+    cli.read_opts.print_report(&report);
+
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+    match format {
+        Format::Plain => format.print_plain(hits.iter().map(|hit| hit.path.display()), &mut writer),
+        Format::Json | Format::Binary => format.print_structured(hits, &mut writer),
+    }
+}
@@ -0,0 +1,5 @@
+fn main() -> anyhow::Result<()> {
+    let cli: search::Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+    search::run(cli)
+}
@@ -0,0 +1,256 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use chrono::NaiveDate;
+use clap::Parser;
+use comrak::Arena;
+
+use obsidian_core::{
+    parser::{self, ParseReport},
+    periodic::NoteDateFormat,
+    printer::{self, Format},
+    reader,
+    stats::{self, ContentStats},
+};
+use obsidian_links::parser::LinkStyle;
+use obsidian_stats::{VaultSummary, periodic::PeriodicReport};
+
+/// Print content statistics for markdown files in an Obsidian vault.
+///
+/// By default, this tool will read all markdown files in the specified directory and compute the
+/// word count, character count, number of headings, number of links, and estimated reading time
+/// (at 200 words per minute) for each of them. Frontmatter and code blocks are not counted as
+/// words.
+///
+/// For plain text output, each file is printed on its own line with its stats. For structured
+/// formats, the output is a mapping of file paths to their stats.
+///
+/// With `--summary`, a single summary of the whole vault is printed instead. This includes the
+/// number of notes and attachments, tag usage, link counts (including broken links and orphaned
+/// notes), word counts, average note size, and how many notes were created each month. Links are
+/// resolved the same way as `obsidian-links`, so `--vault-dir` and `--link-style` work the same.
+///
+/// With `--periodic`, the tool instead reports how consistently periodic notes (like daily or
+/// weekly notes) have been written: how many periods have a note, the coverage percentage, the
+/// current and longest streaks, and every missing period. Notes are matched by name using
+/// `--date-format` (a Moment.js format like `YYYY-MM-DD` or `gggg-[W]ww`), which defaults to the
+/// format and folder from the vault's Daily notes settings. Formats with a week number and no day
+/// are treated as weekly notes. The range defaults to the first and last note, but can be set with
+/// `--since` and `--until`.
+///
+/// Example output (json):
+/// {
+///   "/path/to/vault/References/Aider.md": {
+///     "words": 412,
+///     "characters": 2650,
+///     "headings": 4,
+///     "links": 7,
+///     "reading_time_minutes": 3
+///   }
+/// }
+///
+/// Example output (plain, with --summary):
+/// notes: 120
+/// attachments: 14
+/// tags: 23 (87 usages)
+/// links: 342 (5 broken)
+/// orphans: 9
+/// words: 48210 (401 per note)
+/// average note size: 2841 bytes
+/// 2025-01: 12
+/// 2025-02: 9
+///
+/// Example output (plain, with --periodic):
+/// range: 2025-01-01 to 2025-01-31
+/// notes: 29 of 31 (93.5%)
+/// current streak: 12
+/// longest streak: 15
+/// missing: 2025-01-04
+/// missing: 2025-01-19
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-stats", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    #[command(flatten)]
+    pub read_opts: reader::ReaderOpts,
+
+    /// Print a summary of the whole vault instead of per file stats
+    #[arg(long = "summary", default_value_t = false)]
+    pub summary: bool,
+
+    /// Report missing dates, streaks, and coverage for periodic notes instead of per file stats
+    #[arg(long = "periodic", default_value_t = false, conflicts_with = "summary")]
+    pub periodic: bool,
+
+    /// The Moment.js format of periodic note names (e.g. `YYYY-MM-DD` or `gggg-[W]ww`). Defaults to
+    /// the format from the vault's Daily notes settings
+    #[arg(long = "date-format", requires = "periodic")]
+    pub date_format: Option<String>,
+
+    /// Only report periods on or after this date (YYYY-MM-DD)
+    #[arg(long = "since", requires = "periodic")]
+    pub since: Option<NaiveDate>,
+
+    /// Only report periods on or before this date (YYYY-MM-DD)
+    #[arg(long = "until", requires = "periodic")]
+    pub until: Option<NaiveDate>,
+
+    /// The vault directory to use as the root for resolving links in the summary. If not
+    /// specified, the directory specified in the reader options will be used. If neither is
+    /// specified, links will be resolved relative to the current working directory.
+    #[arg(long = "vault-dir")]
+    pub vault_dir: Option<PathBuf>,
+
+    /// The style of link to parse when building the summary. Valid options are "infer",
+    /// "from_vault_root", and "relative_to_file". Default is "infer". See `obsidian-links --help`
+    /// for details
+    #[arg(long = "link-style")]
+    pub link_style: Option<LinkStyle>,
+
+    /// Also count tags from this frontmatter property in the summary, for vaults that use
+    /// something like `keywords` instead of (or as well as) `tags`. Can be passed multiple times
+    #[arg(long = "tag-key")]
+    pub tag_keys: Vec<String>,
+}
+
+/// Run the command with the given arguments
+pub fn run(cli: Cli) -> anyhow::Result<()> {
+    let entries = cli.read_opts.read_files()?;
+    let vault_root = cli
+        .vault_dir
+        .clone()
+        .or_else(|| cli.read_opts.dir.clone())
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    if cli.periodic {
+        let format = match &cli.date_format {
+            Some(format) => NoteDateFormat::new(format)?,
+            None => NoteDateFormat::daily_notes(&vault_root)?,
+        };
+        let report = obsidian_stats::periodic::analyze(
+            entries.iter().map(|entry| &entry.path),
+            &format,
+            cli.since,
+            cli.until,
+        );
+        return print_periodic(cli.printer.output, &format, report);
+    }
+
+    if cli.summary {
+        let mut report = ParseReport::default();
+        let summary = obsidian_stats::summarize_with_progress(
+            entries,
+            &vault_root,
+            cli.link_style.unwrap_or_default(),
+            &cli.tag_keys,
+            cli.read_opts.progress_bar(),
+            &mut report,
+        )?;
+        cli.read_opts.print_report(&report);
+        return print_summary(cli.printer.output, summary);
+    }
+
+    let arena = Arena::with_capacity(entries.len());
+    let mut report = ParseReport::default();
+    let parsed_files = parser::report_error_iter(
+        parser::parse_files_with_progress(&arena, entries, cli.read_opts.progress_bar()),
+        &mut report,
+    );
+    let file_stats: BTreeMap<PathBuf, ContentStats> = stats::parse_stats(parsed_files)
+        .map(|(pf, stats)| (pf.path, stats))
+        .collect();
+
+    cli.read_opts.print_report(&report);
+
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+    match format {
+        Format::Plain => format.print_plain(
+            file_stats.iter().map(|(path, stats)| {
+                format!(
+                    "{}: {} words, {} characters, {} headings, {} links, ~{} min read",
+                    path.display(),
+                    stats.words,
+                    stats.characters,
+                    stats.headings,
+                    stats.links,
+                    stats.reading_time_minutes
+                )
+            }),
+            &mut writer,
+        ),
+        Format::Json | Format::Binary => format.print_structured(file_stats, &mut writer),
+    }
+}
+
+fn print_summary(format: Format, summary: VaultSummary) -> anyhow::Result<()> {
+    let mut writer = std::io::stdout();
+    match format {
+        Format::Plain => {
+            let lines = [
+                format!("notes: {}", summary.notes),
+                format!("attachments: {}", summary.attachments),
+                format!("tags: {} ({} usages)", summary.tags, summary.tag_usages),
+                format!("links: {} ({} broken)", summary.links, summary.broken_links),
+                format!("orphans: {}", summary.orphans),
+                format!(
+                    "words: {} ({} per note)",
+                    summary.total_words, summary.average_words
+                ),
+                format!("average note size: {} bytes", summary.average_note_bytes),
+            ];
+            format.print_plain(
+                lines.into_iter().chain(
+                    summary
+                        .notes_per_month
+                        .iter()
+                        .map(|(month, count)| format!("{month}: {count}")),
+                ),
+                &mut writer,
+            )
+        }
+        Format::Json | Format::Binary => format.print_structured(summary, &mut writer),
+    }
+}
+
+fn print_periodic(
+    format: Format,
+    date_format: &NoteDateFormat,
+    report: PeriodicReport,
+) -> anyhow::Result<()> {
+    let mut writer = std::io::stdout();
+    match format {
+        Format::Plain => {
+            let range = match (report.start, report.end) {
+                (Some(start), Some(end)) => format!(
+                    "range: {} to {}",
+                    date_format.format_date(start),
+                    date_format.format_date(end)
+                ),
+                _ => "range: no periodic notes found".to_string(),
+            };
+            let lines = [
+                range,
+                format!(
+                    "notes: {} of {} ({:.1}%)",
+                    report.notes,
+                    report.expected,
+                    report.coverage * 100.0
+                ),
+                format!("current streak: {}", report.current_streak),
+                format!("longest streak: {}", report.longest_streak),
+            ];
+            format.print_plain(
+                lines.into_iter().chain(
+                    report
+                        .missing
+                        .iter()
+                        .map(|date| format!("missing: {}", date_format.format_date(*date))),
+                ),
+                &mut writer,
+            )
+        }
+        Format::Json | Format::Binary => format.print_structured(report, &mut writer),
+    }
+}
@@ -0,0 +1,5 @@
+fn main() -> anyhow::Result<()> {
+    let cli: stats::Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+    stats::run(cli)
+}
@@ -0,0 +1,416 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::Metadata,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use clap::Parser;
+use comrak::Arena;
+use serde::{Deserialize, Serialize};
+
+use obsidian_core::{
+    frontmatter,
+    parser::{self, ParseReport},
+    periodic::NoteDateFormat,
+    printer::{self, Format},
+    reader,
+};
+use obsidian_tags::{TagIndex, cooccurrence::CoOccurrence, timeline};
+
+/// A command line tool for extracting and filtering Obsidian tags from markdown files.
+///
+/// By default, this tool will read all markdown files in the specified directory and parse the
+/// frontmatter content for tags. If using a structured printer format (like JSON), it will output a
+/// mapping of tags to the files that contain them. For plain text output, it will return a list of
+/// unique tags found across all files.
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-tags", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    #[command(flatten)]
+    pub read_opts: reader::ReaderOpts,
+
+    /// A filter expression for selecting files based on their tags. When this is passed, the output
+    /// will be in the form of a list of files, encoded according to the selected format (e.g. a
+    /// file path on each line for plain text, and an array of file paths for structured formats).
+    ///
+    /// Filter types:
+    /// - tag:<tag1,tag2,...> : Selects files that have all of the specified tags
+    /// - tag-any:<tag1,tag2,...> : Selects files that have any of the specified tags
+    #[arg(short = 'f', long)]
+    pub filter: Option<Filter>,
+
+    /// List the tags that appear on the same notes as this tag instead, with the most strongly
+    /// associated tags first. Tags are ranked by the fraction of notes with either tag that have
+    /// both (the Jaccard index). Plain output prints each tag with the number of notes they share
+    /// and that fraction (e.g. `cli: 12 (0.40)`), while structured output also includes the
+    /// correlation between the tags
+    #[arg(long = "related", conflicts_with = "filter")]
+    pub related: Option<String>,
+
+    /// Also read tags from this frontmatter property, for vaults that use something like
+    /// `keywords` or `topics` instead of (or as well as) `tags`. Can be passed multiple times
+    #[arg(long = "tag-key")]
+    pub tag_keys: Vec<String>,
+
+    /// Map each file to its tags instead. Plain output prints each file followed by its tags (e.g.
+    /// `/path/to/vault/Note.md: cli, rust`) and structured output is a mapping of file paths to
+    /// their tags. When combined with `--filter`, only the matching files are included
+    #[arg(long = "by-file", conflicts_with = "related")]
+    pub by_file: bool,
+
+    /// Output both the tag to files mapping and the file to tags mapping. Structured output is an
+    /// object with a `tags` key (the same as the default output) and a `files` key (the same as
+    /// `--by-file`). Plain output prints every file and tag pair on its own line (e.g.
+    /// `/path/to/vault/Note.md: rust`)
+    #[arg(long = "combined", conflicts_with_all = ["filter", "related", "by_file"])]
+    pub combined: bool,
+
+    /// Print when each tag was first and last used instead, with the most recently used tags
+    /// first, so active and dormant topics are easy to spot. Daily notes are dated by their name
+    /// and all other notes by when the file was created. Structured output also includes the
+    /// number of notes with each tag per month
+    #[arg(
+        long = "timeline",
+        conflicts_with_all = ["filter", "related", "by_file", "combined"]
+    )]
+    pub timeline: bool,
+
+    /// The Moment.js format of daily note names used to date notes with `--timeline`. Defaults to
+    /// the format from the Daily notes settings of the vault being read
+    #[arg(long = "date-format", requires = "timeline")]
+    pub date_format: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+/// A filter for selecting files based on their tags
+pub enum Filter {
+    TagAll(BTreeSet<String>),
+    TagAny(BTreeSet<String>),
+}
+
+impl FromStr for Filter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (operator, rest) = s.split_once(':').ok_or_else(|| {
+            anyhow::anyhow!("Invalid filter format. Expected 'tag:<tags>' or 'tag-any:<tags>'")
+        })?;
+        match operator {
+            "tag" => Ok(Filter::TagAll(
+                rest.split(',').map(|s| s.trim().to_string()).collect(),
+            )),
+            "tag-any" => Ok(Filter::TagAny(
+                rest.split(',').map(|s| s.trim().to_string()).collect(),
+            )),
+            _ => Err(anyhow::anyhow!(
+                "Unknown filter operator: {}. Expected 'tag' or 'tag-any'",
+                operator
+            )),
+        }
+    }
+}
+
+impl Filter {
+    fn get_matches<'a>(&self, tags: &'a BTreeMap<String, TagInfo>) -> BTreeSet<&'a PathBuf> {
+        match self {
+            Filter::TagAll(required_tags) => {
+                let mut sets: Vec<BTreeSet<&PathBuf>> = Vec::with_capacity(required_tags.len());
+                for tag in required_tags {
+                    if let Some(tag_info) = tags.get(tag) {
+                        sets.push(tag_info.files.iter().collect());
+                    } else {
+                        // If any required tag is missing, no files can match
+                        return BTreeSet::new();
+                    }
+                }
+                // Intersect all sets to find files that have all required tags
+                sets.into_iter()
+                    .reduce(|a, b| a.intersection(&b).copied().collect())
+                    .unwrap_or_default()
+            }
+            Filter::TagAny(possible_tags) => {
+                let mut result = BTreeSet::new();
+                for tag in possible_tags {
+                    if let Some(tag_info) = tags.get(tag) {
+                        result.extend(tag_info.files.iter());
+                    }
+                }
+                result
+            }
+        }
+    }
+}
+
+/// The output of `--combined`
+#[derive(Debug, Serialize)]
+struct Combined<'a> {
+    tags: &'a BTreeMap<String, TagInfo>,
+    files: &'a BTreeMap<PathBuf, BTreeSet<String>>,
+}
+
+/// A struct tying data to a tag. Right now this is really simple, but may be expanded in the future
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct TagInfo {
+    /// The files associated with this tag
+    pub files: BTreeSet<PathBuf>,
+}
+
+impl TagInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Run the command with the given arguments
+pub fn run(cli: Cli) -> anyhow::Result<()> {
+    let entries = cli.read_opts.read_files()?;
+
+    let arena = Arena::with_capacity(entries.len());
+    let mut report = ParseReport::default();
+    let parsed_files = parser::report_error_iter(
+        parser::parse_files_with_progress(&arena, entries, cli.read_opts.progress_bar()),
+        &mut report,
+    );
+    let parsed_with_fm = frontmatter::parse_frontmatter(parsed_files)
+        .map(|(pf, fm)| (pf, fm.map(|fm| fm.with_tag_keys(&cli.tag_keys))));
+
+    // Have to clone the path because pf has a lifetime
+    let notes: Vec<(PathBuf, Vec<String>, Metadata)> = parsed_with_fm
+        .map(|(pf, fm)| {
+            let tags = fm.and_then(|fm| fm.tags).unwrap_or_default();
+            (pf.path.clone(), tags, pf.metadata.clone())
+        })
+        .collect();
+    let index: TagIndex = notes
+        .iter()
+        .map(|(path, note_tags, _)| (path.clone(), note_tags.clone()))
+        .collect();
+    let tags: BTreeMap<String, TagInfo> = index
+        .by_tag
+        .iter()
+        .map(|(tag, files)| {
+            let info = TagInfo {
+                files: files.clone(),
+            };
+            (tag.clone(), info)
+        })
+        .collect();
+
+    cli.read_opts.print_report(&report);
+
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+    if cli.timeline {
+        let daily_notes = match &cli.date_format {
+            Some(format) => NoteDateFormat::new(format)?,
+            None => NoteDateFormat::daily_notes(
+                cli.read_opts
+                    .dir
+                    .clone()
+                    .unwrap_or_else(|| PathBuf::from(".")),
+            )?,
+        };
+        let timelines =
+            timeline::timelines(notes.iter().filter_map(|(path, note_tags, metadata)| {
+                Some((
+                    timeline::note_date(path, metadata, Some(&daily_notes))?,
+                    note_tags,
+                ))
+            }));
+        return match format {
+            Format::Plain => {
+                let mut timelines: Vec<_> = timelines.iter().collect();
+                timelines.sort_by(|a, b| b.1.last_used.cmp(&a.1.last_used).then(a.0.cmp(b.0)));
+                format.print_plain(
+                    timelines.into_iter().map(|(tag, t)| {
+                        format!(
+                            "{tag}: {} to {} ({} notes)",
+                            t.first_used, t.last_used, t.notes
+                        )
+                    }),
+                    &mut writer,
+                )
+            }
+            Format::Json | Format::Binary => format.print_structured(timelines, &mut writer),
+        };
+    }
+    if let Some(tag) = &cli.related {
+        let related =
+            CoOccurrence::from_notes(notes.iter().map(|(_, note_tags, _)| note_tags)).related(tag);
+        return match format {
+            Format::Plain => format.print_plain(
+                related
+                    .iter()
+                    .map(|r| format!("{}: {} ({:.2})", r.tag, r.count, r.jaccard)),
+                &mut writer,
+            ),
+            Format::Json | Format::Binary => format.print_structured(related, &mut writer),
+        };
+    }
+    if cli.combined {
+        return match format {
+            Format::Plain => format.print_plain(
+                index.by_file.iter().flat_map(|(path, file_tags)| {
+                    file_tags
+                        .iter()
+                        .map(move |tag| format!("{}: {tag}", path.display()))
+                }),
+                &mut writer,
+            ),
+            Format::Json | Format::Binary => format.print_structured(
+                Combined {
+                    tags: &tags,
+                    files: &index.by_file,
+                },
+                &mut writer,
+            ),
+        };
+    }
+    if cli.by_file {
+        let matches = cli.filter.as_ref().map(|filter| filter.get_matches(&tags));
+        let files: BTreeMap<&PathBuf, &BTreeSet<String>> = index
+            .by_file
+            .iter()
+            .filter(|(path, _)| matches.as_ref().is_none_or(|m| m.contains(path)))
+            .collect();
+        return match format {
+            Format::Plain => format.print_plain(
+                files.into_iter().map(|(path, file_tags)| {
+                    let file_tags: Vec<&str> = file_tags.iter().map(String::as_str).collect();
+                    format!("{}: {}", path.display(), file_tags.join(", "))
+                }),
+                &mut writer,
+            ),
+            Format::Json | Format::Binary => format.print_structured(files, &mut writer),
+        };
+    }
+    match format {
+        Format::Plain => {
+            if let Some(filter) = cli.filter {
+                let matches = filter.get_matches(&tags);
+                format.print_plain(matches.into_iter().map(|p| p.display()), &mut writer)
+            } else {
+                format.print_plain(tags.keys(), &mut writer)
+            }
+        }
+        Format::Json | Format::Binary => {
+            if let Some(filter) = cli.filter {
+                let matches = filter.get_matches(&tags);
+                format.print_structured(matches, &mut writer)
+            } else {
+                format.print_structured(tags, &mut writer)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Filter, TagInfo};
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::path::PathBuf;
+    use std::str::FromStr;
+
+    fn expected_tag_set(tags: &[&str]) -> BTreeSet<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    fn tag_info(paths: &[&str]) -> TagInfo {
+        TagInfo {
+            files: paths.iter().map(PathBuf::from).collect::<BTreeSet<_>>(),
+        }
+    }
+
+    #[test]
+    fn parses_tag_all_filter() {
+        let filter = Filter::from_str("tag:one,two").expect("expected filter to parse");
+        match filter {
+            Filter::TagAll(tags) => assert_eq!(tags, expected_tag_set(&["one", "two"])),
+            _ => panic!("expected Filter::TagAll variant"),
+        }
+    }
+
+    #[test]
+    fn parses_tag_any_filter_with_whitespace() {
+        let filter =
+            Filter::from_str("tag-any: one , two ").expect("expected filter to parse with trim");
+        match filter {
+            Filter::TagAny(tags) => assert_eq!(tags, expected_tag_set(&["one", "two"])),
+            _ => panic!("expected Filter::TagAny variant"),
+        }
+    }
+
+    #[test]
+    fn parsing_rejects_unknown_operator() {
+        let err = Filter::from_str("not-real:one").expect_err("expected parsing to fail");
+        assert!(
+            err.to_string().contains("Unknown filter operator"),
+            "unexpected error message: {err}"
+        );
+    }
+
+    #[test]
+    fn parsing_rejects_missing_separator() {
+        let err = Filter::from_str("tag").expect_err("expected parsing to fail");
+        assert!(
+            err.to_string()
+                .contains("Invalid filter format. Expected 'tag:<tags>' or 'tag-any:<tags>'"),
+            "unexpected error message: {err}"
+        );
+    }
+
+    #[test]
+    fn get_matches_requires_all_tags() {
+        let mut tags: BTreeMap<String, TagInfo> = BTreeMap::new();
+        tags.insert("one".into(), tag_info(&["note1.md", "note2.md"]));
+        tags.insert("two".into(), tag_info(&["note2.md", "note3.md"]));
+
+        let filter = Filter::from_str("tag:one,two").unwrap();
+        let matches = filter
+            .get_matches(&tags)
+            .into_iter()
+            .cloned()
+            .collect::<BTreeSet<PathBuf>>();
+        let expected = ["note2.md"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect::<BTreeSet<_>>();
+
+        assert_eq!(matches, expected);
+    }
+
+    #[test]
+    fn get_matches_handles_missing_required_tag() {
+        let mut tags: BTreeMap<String, TagInfo> = BTreeMap::new();
+        tags.insert("one".into(), tag_info(&["note1.md", "note2.md"]));
+
+        let filter = Filter::from_str("tag:one,two").unwrap();
+        let matches = filter.get_matches(&tags);
+
+        assert!(matches.is_empty());
+    }
+
+    #[test]
+    fn get_matches_collects_any_tags() {
+        let mut tags: BTreeMap<String, TagInfo> = BTreeMap::new();
+        tags.insert("one".into(), tag_info(&["note1.md", "note2.md"]));
+        tags.insert("two".into(), tag_info(&["note2.md", "note3.md"]));
+
+        let filter = Filter::from_str("tag-any:two,missing").unwrap();
+        let matches = filter
+            .get_matches(&tags)
+            .into_iter()
+            .cloned()
+            .collect::<BTreeSet<PathBuf>>();
+        let expected = ["note2.md", "note3.md"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect::<BTreeSet<_>>();
+
+        assert_eq!(matches, expected);
+    }
+}
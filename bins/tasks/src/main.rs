@@ -0,0 +1,119 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use chrono::NaiveDate;
+use clap::Parser;
+use comrak::Arena;
+
+use obsidian_core::{
+    parser::{self, ParseReport},
+    printer::{self, Format},
+    reader,
+};
+use obsidian_tasks::{Task, TaskStatus};
+
+/// Find, filter, and print tasks (checkbox list items) from markdown files in an Obsidian vault.
+///
+/// By default, this tool will read all markdown files in the specified directory and print every
+/// task found in them. Any status character is supported (e.g. `- [-]` for cancelled or `- [/]`
+/// for in progress), as are inline `#tags` and due dates written as `📅 2025-01-15` (Tasks plugin)
+/// or `[due:: 2025-01-15]` (Dataview).
+///
+/// For plain text output, each task is printed on its own line prefixed with its file and line
+/// number, similar to grep. For structured formats, the output is a mapping of file paths to the
+/// list of tasks in that file.
+///
+/// Example output (plain):
+/// /path/to/vault/Projects/Website.md:12: [ ] Update the about page #work 📅 2025-01-15
+/// /path/to/vault/Projects/Website.md:13: [x] Renew the domain
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-tasks", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    #[command(flatten)]
+    pub read_opts: reader::ReaderOpts,
+
+    /// Only include tasks with the given status. Valid options are "todo", "done", "cancelled",
+    /// "in-progress", or a single status character. Can be passed multiple times
+    #[arg(short = 's', long = "status")]
+    pub statuses: Vec<TaskStatus>,
+
+    /// Only include tasks that are still open (i.e. not done or cancelled)
+    #[arg(long, default_value_t = false, conflicts_with = "statuses")]
+    pub open: bool,
+
+    /// Only include tasks that have all of the given inline tags (without the leading `#`). Can be
+    /// passed multiple times
+    #[arg(short = 't', long = "tag")]
+    pub tags: Vec<String>,
+
+    /// Only include tasks due on or before the given date (YYYY-MM-DD). Tasks without a due date,
+    /// or whose due date isn't a valid date, are excluded
+    #[arg(long = "due-before")]
+    pub due_before: Option<NaiveDate>,
+}
+
+impl Cli {
+    fn matches(&self, task: &Task) -> bool {
+        if self.open && !task.status.is_open() {
+            return false;
+        }
+        if !self.statuses.is_empty() && !self.statuses.contains(&task.status) {
+            return false;
+        }
+        if !self.tags.iter().all(|tag| task.tags.contains(tag)) {
+            return false;
+        }
+        match self.due_before {
+            Some(before) => task
+                .due
+                .as_ref()
+                .and_then(|due| due.parse::<NaiveDate>().ok())
+                .is_some_and(|due| due <= before),
+            None => true,
+        }
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli: Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+
+    let entries = cli.read_opts.read_files()?;
+
+    let arena = Arena::with_capacity(entries.len());
+    let mut report = ParseReport::default();
+    let parsed_files = parser::report_error_iter(
+        parser::parse_files_with_progress(&arena, entries, cli.read_opts.progress_bar()),
+        &mut report,
+    );
+    let tasks: BTreeMap<PathBuf, Vec<Task>> = obsidian_tasks::parse_tasks(parsed_files)
+        .filter_map(|(pf, tasks)| {
+            let tasks: Vec<Task> = tasks.into_iter().filter(|t| cli.matches(t)).collect();
+            (!tasks.is_empty()).then_some((pf.path, tasks))
+        })
+        .collect();
+
+    cli.read_opts.print_report(&report);
+
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+    match format {
+        Format::Plain => format.print_plain(
+            tasks.iter().flat_map(|(path, tasks)| {
+                tasks.iter().map(move |task| {
+                    format!(
+                        "{}:{}: {} {}",
+                        path.display(),
+                        task.span.start_line,
+                        task.status,
+                        task.text
+                    )
+                })
+            }),
+            &mut writer,
+        ),
+        Format::Json | Format::Binary => format.print_structured(tasks, &mut writer),
+    }
+}
@@ -0,0 +1,106 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use obsidian_core::printer::{self, Format};
+use obsidian_testkit::{PropertySpec, VaultSpec};
+
+/// Generate a synthetic Obsidian vault for testing and benchmarking.
+///
+/// The generated vault is fully determined by the flags passed to this tool (including the seed),
+/// so performance problems can be reproduced by sharing the command used to generate the vault
+/// instead of a personal vault. Notes contain frontmatter tags and properties, headings, and a mix
+/// of markdown links and wikilinks to other notes (and optionally to notes that don't exist).
+///
+/// The paths of all generated files are printed to stdout, so the output can be piped straight
+/// into the other tools:
+///
+/// obsidian-testkit /tmp/bench-vault --notes 10000 | obsidian-links --vault-dir /tmp/bench-vault
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-testkit", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    /// The directory to write the generated vault to. It will be created if it doesn't exist
+    pub dir: PathBuf,
+
+    /// Write into the directory even if it already contains files. Existing generated notes will be
+    /// overwritten
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+
+    /// The number of notes to generate
+    #[arg(long, default_value_t = 100)]
+    pub notes: usize,
+
+    /// The number of folders to spread notes across
+    #[arg(long, default_value_t = 5)]
+    pub folders: usize,
+
+    /// The average number of outgoing links per note
+    #[arg(long = "links-per-note", default_value_t = 3.0)]
+    pub links_per_note: f64,
+
+    /// The fraction (0.0-1.0) of links that use wikilink syntax
+    #[arg(long = "wikilink-ratio", default_value_t = 0.5)]
+    pub wikilink_ratio: f64,
+
+    /// The fraction (0.0-1.0) of links that point to notes that don't exist
+    #[arg(long = "broken-link-ratio", default_value_t = 0.05)]
+    pub broken_link_ratio: f64,
+
+    /// The number of distinct tags in the vault
+    #[arg(long, default_value_t = 20)]
+    pub tags: usize,
+
+    /// The maximum number of tags per note
+    #[arg(long = "tags-per-note", default_value_t = 3)]
+    pub tags_per_note: usize,
+
+    /// A frontmatter property to add to every note in the form `<name>:<type>`. Valid types are
+    /// "text", "number", "checkbox", "date", "datetime", and "list". Can be passed multiple times
+    #[arg(short = 'p', long = "property")]
+    pub properties: Vec<PropertySpec>,
+
+    /// The approximate number of words in each note body
+    #[arg(long, default_value_t = 100)]
+    pub words: usize,
+
+    /// The seed for the random number generator
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli: Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+
+    if !cli.force && cli.dir.exists() && std::fs::read_dir(&cli.dir)?.next().is_some() {
+        anyhow::bail!(
+            "{} is not empty. Pass --force to generate into it anyway",
+            cli.dir.display()
+        );
+    }
+
+    let spec = VaultSpec {
+        notes: cli.notes,
+        folders: cli.folders,
+        links_per_note: cli.links_per_note,
+        wikilink_ratio: cli.wikilink_ratio,
+        broken_link_ratio: cli.broken_link_ratio,
+        tags: cli.tags,
+        tags_per_note: cli.tags_per_note,
+        properties: cli.properties,
+        words_per_note: cli.words,
+        seed: cli.seed,
+    };
+    let written = spec.write_to(&cli.dir)?;
+
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+    match format {
+        Format::Plain => format.print_plain(written.iter().map(|p| p.display()), &mut writer),
+        Format::Json | Format::Binary => format.print_structured(written, &mut writer),
+    }
+}
@@ -0,0 +1,135 @@
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+
+use obsidian_core::{
+    printer::{self, Format},
+    reader,
+};
+use obsidian_links::parser::LinkStyle;
+use obsidian_stats::diff::{self, VaultSnapshot};
+
+/// Compare two snapshots of an Obsidian vault.
+///
+/// Each side of the comparison can be a vault directory or a snapshot saved earlier with
+/// `--snapshot`, so a vault can be compared against a backup copy or against itself at an earlier
+/// point in time. Notes are matched by their path relative to the vault root. The output lists
+/// every note that was added, removed, or changed, along with any frontmatter tags and outgoing
+/// links that were added or removed in changed notes.
+///
+/// Pass `--snapshot` with a single vault to print a snapshot of it instead. Snapshots can only be
+/// written in the json or binary formats and either can be loaded again.
+///
+/// Example output (plain):
+/// added: Projects/Homelab.md
+/// removed: Inbox/Idea.md
+/// changed: Daily/2025-01-15.md
+///   tag added: homelab
+///   link added: Projects/Homelab.md
+///   link removed: Inbox/Idea.md
+///
+/// Example output (json):
+/// {
+///   "added": ["Projects/Homelab.md"],
+///   "removed": ["Inbox/Idea.md"],
+///   "changed": ["Daily/2025-01-15.md"],
+///   "tags": {"Daily/2025-01-15.md": {"added": ["homelab"], "removed": []}},
+///   "links": {"Daily/2025-01-15.md": {"added": ["Projects/Homelab.md"], "removed": ["Inbox/Idea.md"]}}
+/// }
+#[derive(Parser, Debug)]
+#[command(name = "obsidian-vault-diff", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub printer: printer::PrinterArgs,
+
+    /// The old vault directory or saved snapshot
+    pub old: PathBuf,
+
+    /// The new vault directory or saved snapshot
+    #[arg(required_unless_present = "snapshot")]
+    pub new: Option<PathBuf>,
+
+    /// Print a snapshot of the old vault that can be compared against later, instead of comparing
+    #[arg(long = "snapshot", default_value_t = false, conflicts_with = "new")]
+    pub snapshot: bool,
+
+    /// The style of links used in the vaults. Valid options are "infer", "from_vault_root", and
+    /// "relative_to_file". Default is "infer". See `obsidian-links --help` for more details
+    #[arg(long = "link-style")]
+    pub link_style: Option<LinkStyle>,
+
+    /// Also compare tags from this frontmatter property, for vaults that use something like
+    /// `keywords` instead of (or as well as) `tags`. Can be passed multiple times
+    #[arg(long = "tag-key")]
+    pub tag_keys: Vec<String>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli: Cli = obsidian_core::config::parse()?;
+    env_logger::init();
+
+    let link_style = cli.link_style.unwrap_or_default();
+    let format = cli.printer.output;
+    let mut writer = std::io::stdout();
+
+    let old = load(&cli.old, link_style, &cli.tag_keys)?;
+    let Some(new) = &cli.new else {
+        if format == Format::Plain {
+            anyhow::bail!("Snapshots can only be printed with the json or binary output formats");
+        }
+        return format.print_structured(old, &mut writer);
+    };
+    let diff = diff::diff(&old, &load(new, link_style, &cli.tag_keys)?);
+
+    match format {
+        Format::Plain => {
+            let lines = diff
+                .added
+                .iter()
+                .map(|path| format!("added: {}", path.display()))
+                .chain(
+                    diff.removed
+                        .iter()
+                        .map(|path| format!("removed: {}", path.display())),
+                )
+                .chain(diff.changed.iter().flat_map(|path| {
+                    let mut lines = vec![format!("changed: {}", path.display())];
+                    if let Some(tags) = diff.tags.get(path) {
+                        lines.extend(tags.added.iter().map(|tag| format!("  tag added: {tag}")));
+                        lines.extend(
+                            tags.removed
+                                .iter()
+                                .map(|tag| format!("  tag removed: {tag}")),
+                        );
+                    }
+                    if let Some(links) = diff.links.get(path) {
+                        lines.extend(
+                            links
+                                .added
+                                .iter()
+                                .map(|link| format!("  link added: {}", link.display())),
+                        );
+                        lines.extend(
+                            links
+                                .removed
+                                .iter()
+                                .map(|link| format!("  link removed: {}", link.display())),
+                        );
+                    }
+                    lines
+                }));
+            format.print_plain(lines, &mut writer)
+        }
+        Format::Json | Format::Binary => format.print_structured(diff, &mut writer),
+    }
+}
+
+/// Snapshot a vault directory or load a saved snapshot
+fn load(path: &Path, link_style: LinkStyle, tag_keys: &[String]) -> anyhow::Result<VaultSnapshot> {
+    if path.is_dir() {
+        let entries = reader::read_dir(path, true)?;
+        VaultSnapshot::from_entries(entries, path, link_style, tag_keys)
+    } else {
+        VaultSnapshot::load(path)
+    }
+}
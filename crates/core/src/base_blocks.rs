@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use comrak::nodes::{AstNode, NodeValue};
+use serde::{Deserialize, Serialize};
+
+use crate::parser::{ParsedFile, Span};
+
+/// The language of fenced code blocks that Obsidian renders as an embedded base
+pub const BASE_BLOCK_LANGUAGE: &str = "base";
+
+/// A base definition embedded in a markdown file inside a ```` ```base ```` fenced code block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaseBlock {
+    /// The raw YAML source of the base definition
+    pub yaml: String,
+    /// The location of the whole fenced block (including the fences) in the file
+    pub span: Span,
+}
+
+impl BaseBlock {
+    /// Parse the YAML of this block. This is mostly useful for validating the block or for
+    /// consumers that want to deserialize it into their own base definition types.
+    pub fn parse<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        serde_norway::from_str(&self.yaml).with_context(|| {
+            format!(
+                "Failed to parse base block starting on line {}",
+                self.span.start_line
+            )
+        })
+    }
+}
+
+/// Find the embedded base blocks from a list of ParsedFiles, returning an iterator of tuples of
+/// the [`ParsedFile`] returned as is and all base blocks found in the file, in document order
+pub fn parse_base_blocks<'a>(
+    entries: impl IntoIterator<Item = ParsedFile<'a>>,
+) -> impl Iterator<Item = (ParsedFile<'a>, Vec<BaseBlock>)> {
+    entries.into_iter().map(|pf| {
+        let blocks = base_blocks_from_ast(pf.ast);
+        (pf, blocks)
+    })
+}
+
+/// Find all embedded base blocks in the AST of a markdown file
+pub fn base_blocks_from_ast<'a>(ast: &'a AstNode<'a>) -> Vec<BaseBlock> {
+    ast.descendants()
+        .filter_map(|node| {
+            let data = node.data.borrow();
+            let NodeValue::CodeBlock(ref block) = data.value else {
+                return None;
+            };
+            if !block.fenced || block.info.split_whitespace().next() != Some(BASE_BLOCK_LANGUAGE) {
+                return None;
+            }
+            Some(BaseBlock {
+                yaml: block.literal.to_string(),
+                span: data.sourcepos.into(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_base_blocks_with_locations() -> Result<()> {
+        let input = r#"# Books
+
+```base
+filters:
+  and:
+    - file.hasTag("book")
+views:
+  - type: table
+    name: All books
+```
+
+```yaml
+not: a base
+```
+"#;
+        let arena = comrak::Arena::new();
+        let ast = crate::parser::parse_content(&arena, input);
+        let blocks = base_blocks_from_ast(ast);
+
+        assert_eq!(blocks.len(), 1);
+        let block = &blocks[0];
+        assert_eq!(block.span.start_line, 3);
+        assert_eq!(block.span.end_line, 10);
+        assert!(block.yaml.starts_with("filters:"));
+
+        let parsed: serde_norway::Value = block.parse()?;
+        assert_eq!(parsed["views"][0]["name"].as_str(), Some("All books"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_yaml_reports_block_location() {
+        let input = "text\n\n```base\nviews: [\n```\n";
+        let arena = comrak::Arena::new();
+        let ast = crate::parser::parse_content(&arena, input);
+        let blocks = base_blocks_from_ast(ast);
+
+        let err = blocks[0]
+            .parse::<serde_norway::Value>()
+            .expect_err("expected invalid YAML to fail");
+        assert!(
+            err.to_string().contains("line 3"),
+            "unexpected error: {err}"
+        );
+    }
+}
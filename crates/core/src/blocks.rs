@@ -0,0 +1,207 @@
+use comrak::nodes::{AlertType, AstNode, NodeValue};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    digest,
+    parser::{self, ParsedFile, Span},
+};
+
+/// An Obsidian callout (e.g. `> [!note] Title`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Callout {
+    /// The lowercased type of the callout (e.g. `note`, `warning`, or any custom type)
+    pub kind: String,
+    /// The custom title of the callout, if one was given
+    pub title: Option<String>,
+    /// Whether the callout is foldable and, if so, whether it is collapsed by default (`-`) or
+    /// expanded by default (`+`). `None` means the callout isn't foldable
+    pub folded: Option<bool>,
+    /// The location of the callout in the file
+    pub span: Span,
+}
+
+/// A code block in a markdown file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeBlock {
+    /// The language of the code block (the first word of the info string), if any
+    pub language: Option<String>,
+    /// Whether this is a fenced code block (as opposed to an indented one)
+    pub fenced: bool,
+    /// The number of lines of code in the block
+    pub lines: usize,
+    /// A stable hex digest of the block content, useful for finding the same snippet across notes
+    pub hash: String,
+    /// The location of the code block (including fences) in the file
+    pub span: Span,
+}
+
+/// All callouts and code blocks found in a single file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockInventory {
+    pub callouts: Vec<Callout>,
+    pub code_blocks: Vec<CodeBlock>,
+}
+
+impl BlockInventory {
+    /// Returns true if the file contains no callouts or code blocks
+    pub fn is_empty(&self) -> bool {
+        self.callouts.is_empty() && self.code_blocks.is_empty()
+    }
+}
+
+/// Inventory the callouts and code blocks from a list of ParsedFiles, returning an iterator of
+/// tuples of the [`ParsedFile`] returned as is and the blocks found in the file
+pub fn parse_blocks<'a>(
+    entries: impl IntoIterator<Item = ParsedFile<'a>>,
+) -> impl Iterator<Item = (ParsedFile<'a>, BlockInventory)> {
+    entries.into_iter().map(|pf| {
+        let inventory = blocks_from_ast(pf.ast);
+        (pf, inventory)
+    })
+}
+
+/// Inventory all callouts and code blocks in the AST of a markdown file, in document order
+pub fn blocks_from_ast<'a>(ast: &'a AstNode<'a>) -> BlockInventory {
+    let mut inventory = BlockInventory::default();
+    for node in ast.descendants() {
+        let data = node.data.borrow();
+        match &data.value {
+            NodeValue::CodeBlock(block) => {
+                let language = block
+                    .info
+                    .split_whitespace()
+                    .next()
+                    .map(|lang| lang.to_string());
+                inventory.code_blocks.push(CodeBlock {
+                    language,
+                    fenced: block.fenced,
+                    lines: block.literal.lines().count(),
+                    hash: digest::hex_digest(block.literal.as_bytes()),
+                    span: data.sourcepos.into(),
+                });
+            }
+            // Comrak natively parses the GitHub alert types, which are a subset of callouts
+            NodeValue::Alert(alert) => {
+                let (folded, title) = split_fold_marker(alert.title.as_deref().unwrap_or(""));
+                inventory.callouts.push(Callout {
+                    kind: alert_kind(alert.alert_type).to_string(),
+                    title,
+                    folded,
+                    span: data.sourcepos.into(),
+                });
+            }
+            NodeValue::BlockQuote => {
+                if let Some(callout) = callout_from_blockquote(node) {
+                    inventory.callouts.push(callout);
+                }
+            }
+            _ => {}
+        }
+    }
+    inventory
+}
+
+fn alert_kind(alert_type: AlertType) -> &'static str {
+    match alert_type {
+        AlertType::Note => "note",
+        AlertType::Tip => "tip",
+        AlertType::Important => "important",
+        AlertType::Warning => "warning",
+        AlertType::Caution => "caution",
+    }
+}
+
+/// Parse a callout from a block quote whose first line looks like `[!type]` with an optional fold
+/// marker and title
+fn callout_from_blockquote<'a>(node: &'a AstNode<'a>) -> Option<Callout> {
+    let paragraph = node.first_child()?;
+    if !matches!(paragraph.data.borrow().value, NodeValue::Paragraph) {
+        return None;
+    }
+    let first_line: String = paragraph
+        .children()
+        .take_while(|child| {
+            !matches!(
+                child.data.borrow().value,
+                NodeValue::SoftBreak | NodeValue::LineBreak
+            )
+        })
+        .map(parser::node_text)
+        .collect();
+
+    let rest = first_line.trim_start().strip_prefix("[!")?;
+    let (kind, rest) = rest.split_once(']')?;
+    if kind.is_empty() || kind.contains(char::is_whitespace) {
+        return None;
+    }
+    let (folded, title) = split_fold_marker(rest);
+    Some(Callout {
+        kind: kind.to_lowercase(),
+        title,
+        folded,
+        span: node.into(),
+    })
+}
+
+fn split_fold_marker(rest: &str) -> (Option<bool>, Option<String>) {
+    let (folded, title) = match rest.strip_prefix('-') {
+        Some(title) => (Some(true), title),
+        None => match rest.strip_prefix('+') {
+            Some(title) => (Some(false), title),
+            None => (None, rest),
+        },
+    };
+    let title = title.trim();
+    (folded, (!title.is_empty()).then(|| title.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inventory_for(input: &str) -> BlockInventory {
+        let arena = comrak::Arena::new();
+        let ast = crate::parser::parse_content(&arena, input);
+        blocks_from_ast(ast)
+    }
+
+    #[test]
+    fn finds_custom_and_builtin_callouts() {
+        let inventory = inventory_for(
+            "> [!note]\n> A note\n\n> [!warning] Careful\n> Hot\n\n> [!faq]- Why though?\n> Because\n\n> [!Example]+\n> Shown\n\n> Just a quote\n",
+        );
+        let callouts: Vec<_> = inventory
+            .callouts
+            .iter()
+            .map(|c| (c.kind.as_str(), c.title.as_deref(), c.folded))
+            .collect();
+
+        assert_eq!(
+            callouts,
+            vec![
+                ("note", None, None),
+                ("warning", Some("Careful"), None),
+                ("faq", Some("Why though?"), Some(true)),
+                ("example", None, Some(false)),
+            ]
+        );
+        assert_eq!(inventory.callouts[2].span.start_line, 7);
+    }
+
+    #[test]
+    fn finds_code_blocks_with_hashes() {
+        let inventory = inventory_for(
+            "```rust\nfn main() {}\n```\n\n```python title=\"x\"\nprint(1)\nprint(2)\n```\n\n    indented\n\n```rust\nfn main() {}\n```\n",
+        );
+        let blocks = &inventory.code_blocks;
+
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[0].language.as_deref(), Some("rust"));
+        assert_eq!(blocks[1].language.as_deref(), Some("python"));
+        assert_eq!(blocks[1].lines, 2);
+        assert!(!blocks[2].fenced);
+        assert_eq!(blocks[2].language, None);
+        assert_eq!(blocks[0].hash, blocks[3].hash);
+        assert_ne!(blocks[0].hash, blocks[1].hash);
+    }
+}
@@ -0,0 +1,450 @@
+use std::{cmp::Ordering, collections::HashMap, fmt, path::Path, str::FromStr};
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_norway::Value;
+
+/// Where Obsidian stores the types of properties set in the Properties view, relative to the vault
+pub const TYPES_CONFIG: &str = ".obsidian/types.json";
+
+/// Formats tried (in order) when reading a date and time from text
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+];
+
+/// The type of a frontmatter property, matching the types in Obsidian's Properties view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PropertyType {
+    Text,
+    List,
+    Number,
+    Checkbox,
+    Date,
+    DateTime,
+    Link,
+}
+
+impl FromStr for PropertyType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(PropertyType::Text),
+            // Obsidian uses separate types for the built in `aliases` and `tags` lists
+            "list" | "multitext" | "aliases" | "tags" => Ok(PropertyType::List),
+            "number" => Ok(PropertyType::Number),
+            "checkbox" | "boolean" => Ok(PropertyType::Checkbox),
+            "date" => Ok(PropertyType::Date),
+            "datetime" => Ok(PropertyType::DateTime),
+            "link" => Ok(PropertyType::Link),
+            _ => Err(anyhow::anyhow!("Unknown property type: {s}")),
+        }
+    }
+}
+
+/// A frontmatter value converted to a specific type by a [`CoercionPolicy`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TypedValue {
+    Null,
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    /// An internal link (e.g. `[[Note]]`), holding just the link target
+    Link(String),
+    List(Vec<TypedValue>),
+}
+
+impl fmt::Display for TypedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypedValue::Null => Ok(()),
+            TypedValue::Text(s) => write!(f, "{s}"),
+            TypedValue::Number(n) => write!(f, "{n}"),
+            TypedValue::Bool(b) => write!(f, "{b}"),
+            TypedValue::Date(d) => write!(f, "{}", d.format("%Y-%m-%d")),
+            TypedValue::DateTime(dt) => write!(f, "{}", dt.format("%Y-%m-%dT%H:%M:%S")),
+            TypedValue::Link(target) => write!(f, "[[{target}]]"),
+            TypedValue::List(items) => {
+                let items: Vec<String> = items.iter().map(ToString::to_string).collect();
+                write!(f, "{}", items.join(", "))
+            }
+        }
+    }
+}
+
+impl TypedValue {
+    /// Parse text as a value of the given type, returning `None` if it isn't valid for the type.
+    /// Lists are parsed as a single item list of text
+    pub fn parse_as(text: &str, ty: PropertyType) -> Option<TypedValue> {
+        let text = text.trim();
+        match ty {
+            PropertyType::Text => Some(TypedValue::Text(text.to_string())),
+            PropertyType::List => Some(TypedValue::List(vec![TypedValue::Text(text.to_string())])),
+            // Rust parses text like `nan` and `inf` as numbers, but nobody means those as numbers
+            PropertyType::Number => text
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(TypedValue::Number),
+            PropertyType::Checkbox => match text.to_lowercase().as_str() {
+                "true" => Some(TypedValue::Bool(true)),
+                "false" => Some(TypedValue::Bool(false)),
+                _ => None,
+            },
+            PropertyType::Date => parse_datetime(text)
+                .map(|dt| dt.date())
+                .or_else(|| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok())
+                .map(TypedValue::Date),
+            PropertyType::DateTime => parse_datetime(text)
+                .or_else(|| {
+                    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                        .ok()
+                        .map(|d| d.and_time(Default::default()))
+                })
+                .map(TypedValue::DateTime),
+            PropertyType::Link => text
+                .strip_prefix("[[")
+                .and_then(|rest| rest.strip_suffix("]]"))
+                .map(|target| {
+                    // Drop any display text after the pipe
+                    let target = target.split_once('|').map_or(target, |(t, _)| t);
+                    TypedValue::Link(target.trim().to_string())
+                }),
+        }
+    }
+
+    /// Compare this value to text from a filter. The text is parsed as the same type as this value
+    /// so numbers and dates compare by value, falling back to comparing the text otherwise
+    pub fn compare_text(&self, text: &str) -> Ordering {
+        let parsed = match self {
+            TypedValue::Number(_) => TypedValue::parse_as(text, PropertyType::Number),
+            TypedValue::Bool(_) => TypedValue::parse_as(text, PropertyType::Checkbox),
+            TypedValue::Date(_) => TypedValue::parse_as(text, PropertyType::Date),
+            TypedValue::DateTime(_) => TypedValue::parse_as(text, PropertyType::DateTime),
+            _ => None,
+        };
+        match (self, parsed) {
+            (TypedValue::Number(a), Some(TypedValue::Number(b))) => a.total_cmp(&b),
+            (TypedValue::Bool(a), Some(TypedValue::Bool(b))) => a.cmp(&b),
+            (TypedValue::Date(a), Some(TypedValue::Date(b))) => a.cmp(&b),
+            (TypedValue::DateTime(a), Some(TypedValue::DateTime(b))) => a.cmp(&b),
+            // Links match either the link text (`[[Note]]`) or just the target (`Note`)
+            (TypedValue::Link(target), _) => match TypedValue::parse_as(text, PropertyType::Link) {
+                Some(TypedValue::Link(other)) => target.cmp(&other),
+                _ => target.as_str().cmp(text.trim()),
+            },
+            _ => self.to_string().as_str().cmp(text),
+        }
+    }
+}
+
+/// How values without a declared type are converted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CoercionMode {
+    /// Only use the types YAML already gives values. Text (quoted or not) always stays text
+    Strict,
+    /// Also convert text that looks like a number, boolean, date, date and time, or link
+    #[default]
+    Lenient,
+}
+
+impl FromStr for CoercionMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "strict" => Ok(CoercionMode::Strict),
+            "lenient" => Ok(CoercionMode::Lenient),
+            _ => Err(anyhow::anyhow!("Unknown coercion mode: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TypesConfig {
+    #[serde(default)]
+    types: HashMap<String, String>,
+}
+
+/// Controls how frontmatter values are converted into [`TypedValue`]s. Properties can be given a
+/// declared type, which always takes priority. Values that can't be converted to their declared
+/// type (and properties without one) are converted according to the [`CoercionMode`]
+#[derive(Debug, Clone, Default)]
+pub struct CoercionPolicy {
+    mode: CoercionMode,
+    types: HashMap<String, PropertyType>,
+}
+
+impl CoercionPolicy {
+    /// Create a policy with the given mode and no declared types
+    pub fn new(mode: CoercionMode) -> Self {
+        Self {
+            mode,
+            types: HashMap::new(),
+        }
+    }
+
+    /// Create a policy with the given mode using the property types declared in the vault's
+    /// [`TYPES_CONFIG`]. If the vault doesn't have one, no types are declared and every property
+    /// is converted according to the mode. Types Obsidian adds in the future that aren't known
+    /// here are skipped with a warning
+    pub fn from_vault(vault_root: impl AsRef<Path>, mode: CoercionMode) -> Result<Self> {
+        let config_path = vault_root.as_ref().join(TYPES_CONFIG);
+        let config: TypesConfig = match std::fs::read(&config_path) {
+            Ok(raw) => serde_json::from_slice(&raw)
+                .with_context(|| format!("Invalid property types {}", config_path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => TypesConfig::default(),
+            Err(e) => return Err(e).context("Failed to read property types"),
+        };
+        let types = config
+            .types
+            .into_iter()
+            .filter_map(|(key, ty)| match ty.parse() {
+                Ok(ty) => Some((key, ty)),
+                Err(e) => {
+                    log::warn!("Ignoring type of property {key}: {e}");
+                    None
+                }
+            })
+            .collect();
+        Ok(Self { mode, types })
+    }
+
+    /// Declare the type of a property
+    pub fn with_type(mut self, key: impl Into<String>, ty: PropertyType) -> Self {
+        self.types.insert(key.into(), ty);
+        self
+    }
+
+    /// The mode used for values without a declared type
+    pub fn mode(&self) -> CoercionMode {
+        self.mode
+    }
+
+    /// The declared type of a property, if any
+    pub fn property_type(&self, key: &str) -> Option<PropertyType> {
+        self.types.get(key).copied()
+    }
+
+    /// Convert the value of a property into a typed value
+    pub fn coerce(&self, key: &str, value: &Value) -> TypedValue {
+        self.property_type(key)
+            .and_then(|ty| coerce_as(value, ty))
+            .unwrap_or_else(|| self.infer(value))
+    }
+
+    fn infer(&self, value: &Value) -> TypedValue {
+        match value {
+            Value::Null => TypedValue::Null,
+            Value::Bool(b) => TypedValue::Bool(*b),
+            Value::Number(n) => n
+                .as_f64()
+                .map(TypedValue::Number)
+                .unwrap_or_else(|| TypedValue::Text(n.to_string())),
+            Value::String(s) => match self.mode {
+                CoercionMode::Strict => TypedValue::Text(s.clone()),
+                CoercionMode::Lenient => [
+                    PropertyType::Link,
+                    PropertyType::Checkbox,
+                    PropertyType::Number,
+                    PropertyType::DateTime,
+                    PropertyType::Date,
+                ]
+                .into_iter()
+                // Only use date and time if there actually is a time, otherwise it is a date
+                .filter(|ty| *ty != PropertyType::DateTime || parse_datetime(s).is_some())
+                .find_map(|ty| TypedValue::parse_as(s, ty))
+                .unwrap_or_else(|| TypedValue::Text(s.clone())),
+            },
+            Value::Sequence(items) => {
+                TypedValue::List(items.iter().map(|item| self.infer(item)).collect())
+            }
+            Value::Tagged(tagged) => self.infer(&tagged.value),
+            Value::Mapping(_) => TypedValue::Text(
+                serde_json::to_string(value).unwrap_or_else(|_| format!("{value:?}")),
+            ),
+        }
+    }
+}
+
+/// Convert a value to the given type, returning `None` if it can't be
+fn coerce_as(value: &Value, ty: PropertyType) -> Option<TypedValue> {
+    match (value, ty) {
+        (Value::Null, _) => Some(TypedValue::Null),
+        // Lists of links are common (e.g. `related: ["[[A]]", "[[B]]"]`), so items that are links
+        // are kept as links
+        (Value::Sequence(items), PropertyType::List | PropertyType::Link) => {
+            Some(TypedValue::List(
+                items
+                    .iter()
+                    .map(|item| {
+                        crate::frontmatter::scalar_to_string(item)
+                            .map(|text| {
+                                TypedValue::parse_as(&text, PropertyType::Link)
+                                    .unwrap_or(TypedValue::Text(text))
+                            })
+                            .unwrap_or(TypedValue::Null)
+                    })
+                    .collect(),
+            ))
+        }
+        (Value::Sequence(_), _) => None,
+        (Value::Bool(b), PropertyType::Checkbox) => Some(TypedValue::Bool(*b)),
+        (Value::Number(n), PropertyType::Number) => n.as_f64().map(TypedValue::Number),
+        (value, ty) => {
+            crate::frontmatter::scalar_to_string(value).and_then(|s| TypedValue::parse_as(&s, ty))
+        }
+    }
+}
+
+fn parse_datetime(text: &str) -> Option<NaiveDateTime> {
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(yaml: &str) -> Value {
+        serde_norway::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn lenient_mode_infers_types_from_text() {
+        let policy = CoercionPolicy::default();
+        let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        assert_eq!(
+            policy.coerce("a", &value("2025-01-15")),
+            TypedValue::Date(date)
+        );
+        assert_eq!(
+            policy.coerce("a", &value("2025-01-15T09:30")),
+            TypedValue::DateTime(date.and_hms_opt(9, 30, 0).unwrap())
+        );
+        assert_eq!(policy.coerce("a", &value("'42'")), TypedValue::Number(42.0));
+        assert_eq!(policy.coerce("a", &value("'True'")), TypedValue::Bool(true));
+        assert_eq!(
+            policy.coerce("a", &value("nan")),
+            TypedValue::Text("nan".into())
+        );
+        assert_eq!(
+            policy.coerce("a", &value("'[[Project|the project]]'")),
+            TypedValue::Link("Project".into())
+        );
+        assert_eq!(
+            policy.coerce("a", &value("[1, hello]")),
+            TypedValue::List(vec![
+                TypedValue::Number(1.0),
+                TypedValue::Text("hello".into())
+            ])
+        );
+    }
+
+    #[test]
+    fn strict_mode_keeps_text() {
+        let policy = CoercionPolicy::new(CoercionMode::Strict);
+        assert_eq!(
+            policy.coerce("a", &value("2025-01-15")),
+            TypedValue::Text("2025-01-15".into())
+        );
+        assert_eq!(
+            policy.coerce("a", &value("'42'")),
+            TypedValue::Text("42".into())
+        );
+        assert_eq!(policy.coerce("a", &value("42")), TypedValue::Number(42.0));
+        assert_eq!(policy.coerce("a", &value("true")), TypedValue::Bool(true));
+    }
+
+    #[test]
+    fn declared_types_take_priority() {
+        let policy = CoercionPolicy::new(CoercionMode::Strict)
+            .with_type("due", PropertyType::Date)
+            .with_type("id", PropertyType::Text)
+            .with_type("aliases", PropertyType::List);
+        assert_eq!(
+            policy.coerce("due", &value("2025-01-15")),
+            TypedValue::Date(NaiveDate::from_ymd_opt(2025, 1, 15).unwrap())
+        );
+        assert_eq!(
+            policy.coerce("id", &value("0042")),
+            TypedValue::Text("0042".into())
+        );
+        assert_eq!(
+            policy.coerce("aliases", &value("[1, two]")),
+            TypedValue::List(vec![
+                TypedValue::Text("1".into()),
+                TypedValue::Text("two".into())
+            ])
+        );
+        assert_eq!(
+            policy.coerce("aliases", &value("['[[A|a]]', B]")),
+            TypedValue::List(vec![
+                TypedValue::Link("A".into()),
+                TypedValue::Text("B".into())
+            ])
+        );
+        // Values that don't fit the declared type fall back to the mode
+        assert_eq!(
+            policy.coerce("due", &value("soon")),
+            TypedValue::Text("soon".into())
+        );
+    }
+
+    #[test]
+    fn loads_types_from_vault() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let policy = CoercionPolicy::from_vault(dir.path(), CoercionMode::Lenient)?;
+        assert_eq!(policy.property_type("due"), None);
+
+        std::fs::create_dir(dir.path().join(".obsidian"))?;
+        std::fs::write(
+            dir.path().join(TYPES_CONFIG),
+            r#"{"types": {"due": "date", "done": "checkbox", "aliases": "aliases", "rating": "stars"}}"#,
+        )?;
+        let policy = CoercionPolicy::from_vault(dir.path(), CoercionMode::Strict)?;
+        assert_eq!(policy.property_type("due"), Some(PropertyType::Date));
+        assert_eq!(policy.property_type("done"), Some(PropertyType::Checkbox));
+        assert_eq!(policy.property_type("aliases"), Some(PropertyType::List));
+        assert_eq!(
+            policy.property_type("rating"),
+            None,
+            "unknown types are skipped"
+        );
+        assert_eq!(
+            policy.coerce("done", &value("'true'")),
+            TypedValue::Bool(true)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn compares_by_type() {
+        assert_eq!(
+            TypedValue::Number(10.0).compare_text("9"),
+            Ordering::Greater
+        );
+        assert_eq!(
+            TypedValue::Text("10".into()).compare_text("9"),
+            Ordering::Less
+        );
+        let date = TypedValue::Date(NaiveDate::from_ymd_opt(2025, 1, 15).unwrap());
+        assert_eq!(date.compare_text("2025-2-1"), Ordering::Less);
+        assert_eq!(TypedValue::Bool(true).compare_text("true"), Ordering::Equal);
+        let link = TypedValue::Link("Projects/Homelab".into());
+        assert_eq!(link.compare_text("Projects/Homelab"), Ordering::Equal);
+        assert_eq!(
+            link.compare_text("[[Projects/Homelab|lab]]"),
+            Ordering::Equal
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Defaults for command line flags loaded from config files, so common flags don't have to be
+//! passed every time.
+//!
+//! Two config files are read, both optional:
+//!
+//! - A user config at `~/.config/obsidian-utils/config.toml` (or under `$XDG_CONFIG_HOME` if set)
+//! - A vault config named `.obsidian-utils.toml` in the root of the vault
+//!
+//! Settings in the vault config take priority over the user config, and flags passed on the
+//! command line take priority over both. Relative paths are relative to the directory containing
+//! the config file. For example:
+//!
+//! ```toml
+//! vault-dir = "~/Documents/Notes"
+//! link-style = "from_vault_root"
+//! exclude = [".obsidian", "Templates/**"]
+//! output = "json"
+//! tag-keys = ["keywords"]
+//! ```
+
+use std::{
+    io::IsTerminal,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::{ArgMatches, Command, CommandFactory, FromArgMatches};
+use serde::Deserialize;
+
+/// The name of the per vault config file, in the root of the vault
+pub const VAULT_CONFIG: &str = ".obsidian-utils.toml";
+
+/// Default values for command line flags. Every setting is optional and only applies to tools
+/// that have the matching flag
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// The vault to use when no directory is passed (and nothing is piped to stdin)
+    pub vault_dir: Option<PathBuf>,
+    /// The default for `--link-style`
+    pub link_style: Option<String>,
+    /// Glob patterns for files to skip when reading the vault, the default for `--exclude`
+    pub exclude: Vec<String>,
+    /// The default for `--output`
+    pub output: Option<String>,
+    /// Properties to also read tags from (like `keywords`), the default for `--tag-key`
+    pub tag_keys: Vec<String>,
+}
+
+impl Config {
+    /// The path of the user config file, if the home directory can be found
+    pub fn user_config_path() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("obsidian-utils").join("config.toml"))
+    }
+
+    /// Load a config file, returning the default (empty) config if it doesn't exist
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let raw = match std::fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read config {}", path.display()));
+            }
+        };
+        let mut config: Config =
+            toml::from_str(&raw).with_context(|| format!("Invalid config {}", path.display()))?;
+        if let Some(dir) = config.vault_dir.take() {
+            let base = path.parent().unwrap_or(Path::new(""));
+            config.vault_dir = Some(base.join(expand_home(dir)));
+        }
+        Ok(config)
+    }
+
+    /// Load the user config
+    pub fn load_user() -> Result<Self> {
+        Self::user_config_path()
+            .map(Self::load)
+            .unwrap_or_else(|| Ok(Self::default()))
+    }
+
+    /// Load the config in the root of the given vault
+    pub fn load_vault(vault_root: impl AsRef<Path>) -> Result<Self> {
+        Self::load(vault_root.as_ref().join(VAULT_CONFIG))
+    }
+
+    /// Merge another config on top of this one. Settings in `other` take priority, except for
+    /// excludes and tag keys, which are combined
+    pub fn merge(mut self, other: Config) -> Self {
+        self.vault_dir = other.vault_dir.or(self.vault_dir);
+        self.link_style = other.link_style.or(self.link_style);
+        self.output = other.output.or(self.output);
+        self.exclude.extend(other.exclude);
+        self.tag_keys.extend(other.tag_keys);
+        self
+    }
+
+    /// Set the defaults of the matching arguments in the command and all of its subcommands.
+    /// Arguments that don't exist in the command are ignored
+    pub fn apply(&self, mut cmd: Command) -> Command {
+        if let Some(dir) = &self.vault_dir {
+            let dir = dir.to_string_lossy().into_owned();
+            cmd = set_default(cmd, "vault_dir", dir.clone());
+            // Only default the directory to read if nothing is being piped in, since a directory
+            // always takes priority over stdin. Required directories (like the output directory of
+            // a generated vault) mean something else entirely
+            if std::io::stdin().is_terminal() && !is_required(&cmd, "dir") {
+                cmd = set_default(cmd, "dir", dir);
+            }
+        }
+        if let Some(style) = &self.link_style {
+            cmd = set_default(cmd, "link_style", style.clone());
+        }
+        if let Some(output) = &self.output {
+            cmd = set_default(cmd, "output", output.clone());
+        }
+        for (id, values) in [("exclude", &self.exclude), ("tag_keys", &self.tag_keys)] {
+            if !values.is_empty() && has_arg(&cmd, id) {
+                let values = values.clone();
+                cmd = cmd.mut_arg(id, |arg| arg.default_values(values));
+            }
+        }
+        let subcommands: Vec<String> = cmd
+            .get_subcommands()
+            .map(|sub| sub.get_name().to_string())
+            .collect();
+        for name in subcommands {
+            cmd = cmd.mut_subcommand(name, |sub| self.apply(sub));
+        }
+        cmd
+    }
+}
+
+/// Parse the command line arguments using the defaults from the user and vault configs. This
+/// should be used in place of `Cli::parse()`. Like `Cli::parse()`, this exits with a usage message
+/// if the arguments are invalid
+pub fn parse<T: CommandFactory + FromArgMatches>() -> Result<T> {
+    let user = Config::load_user()?;
+    // The vault config can only be found once we know which vault is being used, so the arguments
+    // are parsed once with just the user config to find it
+    let matches = user.apply(T::command()).get_matches();
+    let mut matches = &matches;
+    while let Some((_, sub)) = matches.subcommand() {
+        matches = sub;
+    }
+    let vault_root = path_arg(matches, "vault_dir")
+        .or_else(|| path_arg(matches, "dir"))
+        .unwrap_or_else(|| PathBuf::from("."));
+    let config = user.merge(Config::load_vault(&vault_root)?);
+    let matches = config.apply(T::command()).get_matches();
+    Ok(T::from_arg_matches(&matches).unwrap_or_else(|e| e.exit()))
+}
+
+fn has_arg(cmd: &Command, id: &str) -> bool {
+    cmd.get_arguments().any(|arg| arg.get_id() == id)
+}
+
+fn is_required(cmd: &Command, id: &str) -> bool {
+    cmd.get_arguments()
+        .any(|arg| arg.get_id() == id && arg.is_required_set())
+}
+
+fn set_default(cmd: Command, id: &str, value: String) -> Command {
+    if has_arg(&cmd, id) && !is_required(&cmd, id) {
+        cmd.mut_arg(id, |arg| arg.default_value(value))
+    } else {
+        cmd
+    }
+}
+
+fn path_arg(matches: &ArgMatches, id: &str) -> Option<PathBuf> {
+    matches.try_get_one::<PathBuf>(id).ok().flatten().cloned()
+}
+
+fn expand_home(path: PathBuf) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[derive(Parser, Debug)]
+    struct Cli {
+        #[command(flatten)]
+        printer: crate::printer::PrinterArgs,
+        #[arg(long = "link-style")]
+        link_style: Option<String>,
+        #[arg(long = "vault-dir")]
+        vault_dir: Option<PathBuf>,
+        #[arg(long = "exclude")]
+        exclude: Vec<String>,
+    }
+
+    #[test]
+    fn loads_and_merges_configs() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let user_path = dir.path().join("config.toml");
+        std::fs::write(
+            &user_path,
+            "vault-dir = \"Notes\"\nlink-style = \"relative_to_file\"\noutput = \"json\"\nexclude = [\".obsidian\"]\n",
+        )?;
+        std::fs::write(
+            dir.path().join(VAULT_CONFIG),
+            "link-style = \"from_vault_root\"\nexclude = [\"Templates\"]\n",
+        )?;
+
+        let user = Config::load(&user_path)?;
+        assert_eq!(user.vault_dir, Some(dir.path().join("Notes")));
+        let config = user.merge(Config::load_vault(dir.path())?);
+        assert_eq!(config.link_style.as_deref(), Some("from_vault_root"));
+        assert_eq!(config.output.as_deref(), Some("json"));
+        assert_eq!(config.exclude, vec![".obsidian", "Templates"]);
+
+        assert_eq!(
+            Config::load(dir.path().join("missing.toml"))?,
+            Config::default()
+        );
+        std::fs::write(&user_path, "colour = \"blue\"\n")?;
+        assert!(Config::load(&user_path).is_err(), "unknown keys are errors");
+        Ok(())
+    }
+
+    #[test]
+    fn flags_take_priority_over_config() {
+        let config = Config {
+            vault_dir: Some(PathBuf::from("/vault")),
+            link_style: Some("from_vault_root".into()),
+            exclude: vec!["Templates".into()],
+            output: Some("json".into()),
+            tag_keys: Vec::new(),
+        };
+        let matches =
+            config
+                .apply(Cli::command())
+                .get_matches_from(["test", "--link-style", "infer"]);
+        let cli = Cli::from_arg_matches(&matches).unwrap();
+        assert_eq!(cli.link_style.as_deref(), Some("infer"));
+        assert_eq!(cli.vault_dir, Some(PathBuf::from("/vault")));
+        assert_eq!(cli.exclude, vec!["Templates"]);
+        assert_eq!(cli.printer.output, crate::printer::Format::Json);
+
+        let matches = config.apply(Cli::command()).get_matches_from([
+            "test",
+            "--exclude",
+            "Daily",
+            "-o",
+            "plain",
+        ]);
+        let cli = Cli::from_arg_matches(&matches).unwrap();
+        assert_eq!(cli.exclude, vec!["Daily"]);
+        assert_eq!(cli.printer.output, crate::printer::Format::Plain);
+    }
+}
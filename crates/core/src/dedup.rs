@@ -0,0 +1,224 @@
+use std::path::PathBuf;
+
+use comrak::nodes::{AstNode, NodeValue};
+use serde::{Deserialize, Serialize};
+
+use crate::{digest, parser::ParsedFile};
+
+/// The number of consecutive words in each shingle used for fuzzy matching
+pub const SHINGLE_SIZE: usize = 3;
+/// The number of hash functions in a MinHash signature. More hashes give a more accurate
+/// similarity estimate at the cost of memory and comparison time
+pub const SIGNATURE_SIZE: usize = 128;
+/// The default similarity (from 0 to 1) at which two notes are considered near-duplicates
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+
+/// A compact fingerprint of the content of a note, used to find duplicates without keeping the
+/// content of every note in memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fingerprint {
+    /// A digest of the normalized note body. Notes with the same digest have identical content,
+    /// ignoring frontmatter, formatting, case, and whitespace
+    pub digest: u64,
+    /// The MinHash signature of the word shingles in the note body
+    pub signature: Vec<u64>,
+}
+
+impl Fingerprint {
+    /// Estimate the similarity (Jaccard index of the word shingles) between two notes, from 0 (no
+    /// overlap) to 1 (identical)
+    pub fn similarity(&self, other: &Fingerprint) -> f64 {
+        if self.digest == other.digest {
+            return 1.0;
+        }
+        let matching = self
+            .signature
+            .iter()
+            .zip(&other.signature)
+            .filter(|(a, b)| a == b)
+            .count();
+        matching as f64 / SIGNATURE_SIZE as f64
+    }
+}
+
+/// A pair of notes that are likely duplicates of each other
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Duplicate {
+    pub first: PathBuf,
+    pub second: PathBuf,
+    /// The estimated similarity between the notes, from 0 to 1
+    pub similarity: f64,
+    /// Whether the notes have exactly the same (normalized) content
+    pub exact: bool,
+}
+
+/// Compute fingerprints from a list of ParsedFiles, returning an iterator of tuples of the
+/// [`ParsedFile`] returned as is and its fingerprint. Files without any content are skipped since
+/// every empty note would otherwise be reported as a duplicate of every other one
+pub fn parse_fingerprints<'a>(
+    entries: impl IntoIterator<Item = ParsedFile<'a>>,
+) -> impl Iterator<Item = (ParsedFile<'a>, Fingerprint)> {
+    entries
+        .into_iter()
+        .filter_map(|pf| fingerprint_from_ast(pf.ast).map(|fp| (pf, fp)))
+}
+
+/// Compute the fingerprint of a markdown file from its AST. Returns `None` if the file has no
+/// content other than frontmatter
+pub fn fingerprint_from_ast<'a>(ast: &'a AstNode<'a>) -> Option<Fingerprint> {
+    let words = normalized_words(ast);
+    if words.is_empty() {
+        return None;
+    }
+    let digest = digest::digest(words.join(" "));
+
+    // Notes shorter than a single shingle are treated as one shingle
+    let mut signature = vec![u64::MAX; SIGNATURE_SIZE];
+    for shingle in words.windows(SHINGLE_SIZE.min(words.len())) {
+        let shingle_hash = digest::digest(shingle.join(" "));
+        for (seed, min) in signature.iter_mut().enumerate() {
+            *min = (*min).min(mix(shingle_hash ^ seed as u64));
+        }
+    }
+    Some(Fingerprint { digest, signature })
+}
+
+/// Find all pairs of notes whose similarity is at least `threshold`. Exact duplicates are always
+/// reported, and a threshold of 1 only reports exact duplicates (notes with the same digest), since
+/// notes with different content can still share every shingle. Pairs are returned with the most
+/// similar first.
+///
+/// This compares every pair of fingerprints, which is fast enough for vaults with many thousands
+/// of notes since signatures are small
+pub fn find_duplicates(fingerprints: &[(PathBuf, Fingerprint)], threshold: f64) -> Vec<Duplicate> {
+    let mut duplicates = Vec::new();
+    for (i, (first, a)) in fingerprints.iter().enumerate() {
+        for (second, b) in &fingerprints[i + 1..] {
+            let exact = a.digest == b.digest;
+            let similarity = a.similarity(b);
+            if exact || (threshold < 1.0 && similarity >= threshold) {
+                duplicates.push(Duplicate {
+                    first: first.clone(),
+                    second: second.clone(),
+                    similarity,
+                    exact,
+                });
+            }
+        }
+    }
+    duplicates.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then_with(|| a.first.cmp(&b.first))
+            .then_with(|| a.second.cmp(&b.second))
+    });
+    duplicates
+}
+
+/// Collect the lowercased words of the note body (including code), with punctuation stripped
+fn normalized_words<'a>(ast: &'a AstNode<'a>) -> Vec<String> {
+    let mut text = String::new();
+    for node in ast.descendants() {
+        let data = node.data.borrow();
+        match &data.value {
+            NodeValue::FrontMatter(_) => continue,
+            NodeValue::Code(code) => text.push_str(&code.literal),
+            NodeValue::CodeBlock(block) => text.push_str(&block.literal),
+            value => {
+                if let Some(t) = value.text() {
+                    text.push_str(t);
+                }
+            }
+        }
+        // Make sure words in separate blocks or lines aren't joined together
+        if data.value.block() || matches!(data.value, NodeValue::SoftBreak | NodeValue::LineBreak) {
+            text.push(' ');
+        }
+    }
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Derive an independent hash from a shingle hash and a seed (the SplitMix64 finalizer)
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint_for(input: &str) -> Option<Fingerprint> {
+        let arena = comrak::Arena::new();
+        let ast = crate::parser::parse_content(&arena, input);
+        fingerprint_from_ast(ast)
+    }
+
+    #[test]
+    fn exact_duplicates_ignore_frontmatter_and_formatting() {
+        let a = fingerprint_for("---\ntags: [a]\n---\n# Title\n\nSome **bold** text.\n").unwrap();
+        let b = fingerprint_for("# title\nSome bold   text\n").unwrap();
+
+        assert_eq!(a.digest, b.digest);
+        assert_eq!(a.similarity(&b), 1.0);
+        assert!(fingerprint_for("---\ntags: [a]\n---\n").is_none());
+    }
+
+    #[test]
+    fn near_duplicates_are_similar() {
+        let base = "The quick brown fox jumps over the lazy dog while the farmer watches from the porch and drinks his coffee slowly in the morning sun";
+        let original = fingerprint_for(base).unwrap();
+        let edited = fingerprint_for(&base.replace("coffee", "tea")).unwrap();
+        let unrelated = fingerprint_for(
+            "Rust ownership rules ensure memory safety without a garbage collector",
+        )
+        .unwrap();
+
+        let similarity = original.similarity(&edited);
+        assert!(similarity > 0.6 && similarity < 1.0, "got {similarity}");
+        assert!(original.similarity(&unrelated) < 0.1);
+
+        let fingerprints = vec![
+            (PathBuf::from("a.md"), original),
+            (PathBuf::from("b.md"), edited),
+            (PathBuf::from("c.md"), unrelated),
+        ];
+        let duplicates = find_duplicates(&fingerprints, 0.6);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].first, PathBuf::from("a.md"));
+        assert!(!duplicates[0].exact);
+        assert!(find_duplicates(&fingerprints, 1.0).is_empty());
+    }
+
+    #[test]
+    fn exact_threshold_requires_same_content() {
+        let paragraph = "the quick brown fox jumps over the lazy dog";
+        let twice = fingerprint_for(&[paragraph; 2].join("\n\n")).unwrap();
+        let thrice = fingerprint_for(&[paragraph; 3].join("\n\n")).unwrap();
+        // Both notes have exactly the same shingles, so only the digest tells them apart
+        assert_ne!(twice.digest, thrice.digest);
+        assert_eq!(twice.signature, thrice.signature);
+
+        let fingerprints = vec![
+            (PathBuf::from("a.md"), twice.clone()),
+            (PathBuf::from("b.md"), thrice),
+            (PathBuf::from("c.md"), twice),
+        ];
+        let duplicates = find_duplicates(&fingerprints, 1.0);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].first, PathBuf::from("a.md"));
+        assert_eq!(duplicates[0].second, PathBuf::from("c.md"));
+        assert!(duplicates[0].exact);
+        assert_eq!(find_duplicates(&fingerprints, 0.9).len(), 3);
+    }
+}
@@ -0,0 +1,32 @@
+/// Compute a stable 64-bit digest (FNV-1a) of some data.
+///
+/// This is not a cryptographic hash. It is meant for cheaply detecting identical content (like
+/// duplicate code blocks or unchanged files), and unlike [`std::hash::DefaultHasher`] the output is
+/// guaranteed to be the same across Rust versions and platforms, so digests can safely be
+/// persisted or compared between runs.
+pub fn digest(data: impl AsRef<[u8]>) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    data.as_ref().iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Compute the [`digest`] of some data as a fixed width hex string
+pub fn hex_digest(data: impl AsRef<[u8]>) -> String {
+    format!("{:016x}", digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_is_stable() {
+        // Known FNV-1a test vectors
+        assert_eq!(digest(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(digest("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hex_digest("foobar"), "85944171f73967e8");
+    }
+}
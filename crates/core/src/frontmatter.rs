@@ -2,6 +2,7 @@ use std::{collections::HashMap, sync::LazyLock};
 
 use comrak::nodes::{AstNode, NodeValue};
 use serde::{Deserialize, Serialize};
+use serde_norway::{Mapping, Value};
 
 use crate::parser::ParsedFile;
 
@@ -12,7 +13,7 @@ static FRONTMATTER_DELIMITER_CHARS: LazyLock<Vec<char>> =
     LazyLock::new(|| crate::parser::FRONTMATTER_DELIMITER.chars().collect());
 
 /// A struct representing the known frontmatter of a markdown file plus additional values
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Frontmatter {
     /// The tags associated with this file
     pub tags: Option<Vec<String>>,
@@ -22,7 +23,62 @@ pub struct Frontmatter {
     pub cssclasses: Option<Vec<String>>,
     /// Any additional frontmatter values not explicitly modeled above
     #[serde(flatten)]
-    pub values: HashMap<String, serde_norway::Value>,
+    pub values: HashMap<String, Value>,
+}
+
+impl Frontmatter {
+    /// Get the value of a property by name. This works for both the modeled properties (like `tags`)
+    /// and any other property
+    pub fn get(&self, key: &str) -> Option<Value> {
+        let list = match key {
+            "tags" => &self.tags,
+            "aliases" => &self.aliases,
+            "cssclasses" => &self.cssclasses,
+            _ => return self.values.get(key).cloned(),
+        };
+        list.as_ref()
+            .map(|items| Value::Sequence(items.iter().cloned().map(Value::String).collect()))
+    }
+
+    /// Also read tags from the given properties, for vaults that use something like `keywords` or
+    /// `topics` instead of `tags`. Tags from each property are added to [`Frontmatter::tags`] (in
+    /// order, skipping duplicates) and the properties themselves are left as is. Single values are
+    /// split on commas, the same as `tags`
+    pub fn with_tag_keys(mut self, keys: &[impl AsRef<str>]) -> Self {
+        for key in keys {
+            let key = key.as_ref();
+            let Some(value) = self.values.get(key).cloned() else {
+                continue;
+            };
+            let mut warnings = Vec::new();
+            let aliased = value_to_list(key, value, &mut warnings);
+            for warning in warnings {
+                log::debug!("Tag property: {warning}");
+            }
+            let Some(aliased) = aliased else {
+                continue;
+            };
+            let tags = self.tags.get_or_insert_with(Vec::new);
+            for tag in aliased {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+        }
+        self
+    }
+
+    /// Iterate over the names of all properties set in this frontmatter
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        [
+            ("tags", &self.tags),
+            ("aliases", &self.aliases),
+            ("cssclasses", &self.cssclasses),
+        ]
+        .into_iter()
+        .filter_map(|(key, list)| list.is_some().then_some(key))
+        .chain(self.values.keys().map(String::as_str))
+    }
 }
 
 /// Parse the frontmatter from a list of ParsedFiles, returning an iterator of tuples of the
@@ -37,30 +93,215 @@ pub fn parse_frontmatter<'a>(
     })
 }
 
-/// Parse the frontmatter from the AST of a markdown file
-fn parse_frontmatter_from_ast<'a>(ast: &'a AstNode<'a>) -> Option<Frontmatter> {
+/// How to handle malformed frontmatter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Salvage as much as possible from malformed frontmatter, reporting what was fixed or dropped as
+    /// warnings. Tabs in indentation are replaced with spaces, the last value wins for duplicate
+    /// keys, entries that can't be parsed are dropped, and single values for list properties (like
+    /// `tags: foo`) are converted to lists
+    #[default]
+    Lenient,
+    /// Return an error for anything that would produce a warning in lenient mode. This is useful for
+    /// validating a vault
+    Strict,
+}
+
+/// Frontmatter parsed with a [`ParseMode`], along with any problems that were worked around
+#[derive(Debug)]
+pub struct ParsedFrontmatter {
+    pub frontmatter: Frontmatter,
+    /// Descriptions of anything that had to be fixed or dropped. Always empty in strict mode
+    pub warnings: Vec<String>,
+}
+
+/// Parse the frontmatter from the AST of a markdown file. Malformed frontmatter is parsed in
+/// [`ParseMode::Lenient`] mode, with any warnings logged
+pub fn parse_frontmatter_from_ast<'a>(ast: &'a AstNode<'a>) -> Option<Frontmatter> {
+    match parse_frontmatter_from_ast_with_mode(ast, ParseMode::Lenient) {
+        Ok(parsed) => parsed.map(|parsed| {
+            for warning in parsed.warnings {
+                log::warn!("Malformed frontmatter: {warning}");
+            }
+            parsed.frontmatter
+        }),
+        Err(e) => {
+            log::error!("Failed to parse frontmatter: {e}");
+            None
+        }
+    }
+}
+
+/// Parse the frontmatter from the AST of a markdown file using the given mode. Returns `Ok(None)`
+/// if the file has no frontmatter
+pub fn parse_frontmatter_from_ast_with_mode<'a>(
+    ast: &'a AstNode<'a>,
+    mode: ParseMode,
+) -> anyhow::Result<Option<ParsedFrontmatter>> {
     for node in ast.descendants() {
         if let NodeValue::FrontMatter(ref text) = node.data.borrow().value {
             let trimmed = text
                 .trim()
                 .trim_matches(FRONTMATTER_DELIMITER_CHARS.as_slice());
-            let fm: Frontmatter = serde_norway::from_str(trimmed)
-                .map_err(|e| {
-                    log::error!("Failed to parse frontmatter: {}", e);
-                })
-                .ok()?;
-            return Some(fm);
+            return parse_frontmatter_text(trimmed, mode).map(Some);
         }
     }
-    None
+    Ok(None)
+}
+
+/// Parse raw frontmatter YAML (without the `---` delimiters) using the given mode
+pub fn parse_frontmatter_text(text: &str, mode: ParseMode) -> anyhow::Result<ParsedFrontmatter> {
+    let mut warnings = Vec::new();
+
+    let text = if text
+        .lines()
+        .any(|line| line.trim_start_matches(' ').starts_with('\t'))
+    {
+        warnings.push("tabs in indentation were replaced with spaces".to_string());
+        text.lines()
+            .map(|line| {
+                let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+                format!("{}{}", line[..indent].replace('\t', "  "), &line[indent..])
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        text.to_string()
+    };
+
+    let mapping = match serde_norway::from_str::<Value>(&text) {
+        Ok(Value::Mapping(mapping)) => mapping,
+        Ok(Value::Null) => Mapping::new(),
+        Ok(_) => {
+            warnings
+                .push("the root of the frontmatter is not a map, so it was ignored".to_string());
+            Mapping::new()
+        }
+        Err(_) => salvage_entries(&text, &mut warnings),
+    };
+    let frontmatter = frontmatter_from_mapping(mapping, &mut warnings);
+
+    if mode == ParseMode::Strict && !warnings.is_empty() {
+        anyhow::bail!("Invalid frontmatter: {}", warnings.join("; "));
+    }
+    Ok(ParsedFrontmatter {
+        frontmatter,
+        warnings,
+    })
+}
+
+/// Parse each top level entry on its own so one bad entry (or a duplicated key) doesn't lose the
+/// whole frontmatter
+fn salvage_entries(text: &str, warnings: &mut Vec<String>) -> Mapping {
+    let mut entries: Vec<String> = Vec::new();
+    for line in text.lines() {
+        // Top level lists are allowed to start at the same indentation as their key
+        let continues_entry = line.is_empty()
+            || line.starts_with([' ', '#'])
+            || line.starts_with("- ")
+            || line == "-";
+        match entries.last_mut() {
+            Some(entry) if continues_entry => {
+                entry.push('\n');
+                entry.push_str(line);
+            }
+            _ => entries.push(line.to_string()),
+        }
+    }
+
+    let mut mapping = Mapping::new();
+    for entry in entries {
+        match serde_norway::from_str::<Value>(&entry) {
+            Ok(Value::Mapping(parsed)) => {
+                for (key, value) in parsed {
+                    if mapping.insert(key.clone(), value).is_some() {
+                        warnings.push(format!(
+                            "duplicate key {}, the last value was kept",
+                            key_to_string(&key)
+                        ));
+                    }
+                }
+            }
+            Ok(Value::Null) => {}
+            _ => {
+                let first_line = entry.lines().next().unwrap_or_default();
+                warnings.push(format!("dropped invalid entry `{first_line}`"));
+            }
+        }
+    }
+    mapping
+}
+
+fn frontmatter_from_mapping(mapping: Mapping, warnings: &mut Vec<String>) -> Frontmatter {
+    let mut frontmatter = Frontmatter::default();
+    for (key, value) in mapping {
+        let key = key_to_string(&key);
+        let list = match key.as_str() {
+            "tags" => &mut frontmatter.tags,
+            "aliases" => &mut frontmatter.aliases,
+            "cssclasses" => &mut frontmatter.cssclasses,
+            _ => {
+                frontmatter.values.insert(key, value);
+                continue;
+            }
+        };
+        *list = value_to_list(&key, value, warnings);
+    }
+    frontmatter
+}
+
+/// Convert the value of a list property to a list of strings. Single values are split on commas
+fn value_to_list(key: &str, value: Value, warnings: &mut Vec<String>) -> Option<Vec<String>> {
+    match value {
+        Value::Null => None,
+        Value::Sequence(items) => Some(
+            items
+                .into_iter()
+                .filter_map(|item| match scalar_to_string(&item) {
+                    Some(item) => Some(item),
+                    None => {
+                        warnings.push(format!("dropped a non text value from {key}"));
+                        None
+                    }
+                })
+                .collect(),
+        ),
+        value => match scalar_to_string(&value) {
+            Some(item) => {
+                warnings.push(format!("{key} should be a list, converted {item:?} to one"));
+                Some(
+                    item.split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect(),
+                )
+            }
+            None => {
+                warnings.push(format!("{key} should be a list, so it was ignored"));
+                None
+            }
+        },
+    }
+}
+
+/// Convert a scalar YAML value (string, number, or bool) to a string
+pub(crate) fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn key_to_string(key: &Value) -> String {
+    scalar_to_string(key).unwrap_or_else(|| format!("{key:?}"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use serde_norway::Value;
-
     #[test]
     fn test_parse_frontmatter() {
         let input = r#"---
@@ -86,4 +327,81 @@ extra: "value"
             Some(&Value::String("value".into()))
         );
     }
+
+    fn parse(text: &str, mode: ParseMode) -> anyhow::Result<ParsedFrontmatter> {
+        parse_frontmatter_text(text, mode)
+    }
+
+    #[test]
+    fn lenient_mode_salvages_malformed_frontmatter() -> anyhow::Result<()> {
+        let parsed = parse(
+            "tags: project\nstatus: draft\nbroken: [unclosed\naliases:\n- one\n- 2\nstatus: done\n",
+            ParseMode::Lenient,
+        )?;
+        let fm = &parsed.frontmatter;
+
+        assert_eq!(fm.tags, Some(vec!["project".to_string()]));
+        assert_eq!(fm.aliases, Some(vec!["one".to_string(), "2".to_string()]));
+        assert_eq!(fm.values.get("status"), Some(&Value::String("done".into())));
+        assert!(!fm.values.contains_key("broken"));
+        assert_eq!(parsed.warnings.len(), 3, "{:?}", parsed.warnings);
+
+        let parsed = parse("nested:\n\tkey: value", ParseMode::Lenient)?;
+        assert!(parsed.frontmatter.values.contains_key("nested"));
+        assert_eq!(parsed.warnings.len(), 1);
+
+        let parsed = parse("- just\n- a list", ParseMode::Lenient)?;
+        assert!(parsed.frontmatter.values.is_empty());
+        assert_eq!(parsed.warnings.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn reads_tags_from_other_keys() -> anyhow::Result<()> {
+        let parsed = parse(
+            "tags: [rust]\nkeywords: [cli, rust]\ntopics: gardening, cooking\n",
+            ParseMode::Strict,
+        )?;
+        let fm = parsed
+            .frontmatter
+            .with_tag_keys(&["keywords", "topics", "missing"]);
+        assert_eq!(
+            fm.tags,
+            Some(vec![
+                "rust".to_string(),
+                "cli".to_string(),
+                "gardening".to_string(),
+                "cooking".to_string()
+            ])
+        );
+        assert!(fm.values.contains_key("keywords"));
+
+        let fm = parse("topics: [a]", ParseMode::Strict)?
+            .frontmatter
+            .with_tag_keys(&["topics"]);
+        assert_eq!(fm.tags, Some(vec!["a".to_string()]));
+
+        let fm = parse("topics:", ParseMode::Strict)?
+            .frontmatter
+            .with_tag_keys(&["topics"]);
+        assert_eq!(fm.tags, None, "empty tag keys don't add tags");
+        Ok(())
+    }
+
+    #[test]
+    fn strict_mode_rejects_malformed_frontmatter() {
+        assert!(parse("tags: [a, b]\nextra: 1", ParseMode::Strict).is_ok());
+        assert!(parse("", ParseMode::Strict).is_ok());
+
+        for invalid in [
+            "tags: project",
+            "a: 1\na: 2",
+            "nested:\n\tkey: value",
+            "- a list",
+            "good: 1\nbad: [unclosed",
+        ] {
+            let err = parse(invalid, ParseMode::Strict).expect_err(invalid);
+            assert!(err.to_string().starts_with("Invalid frontmatter"), "{err}");
+        }
+    }
 }
@@ -0,0 +1,423 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_norway::{Mapping, Value};
+
+use crate::{reader::FileEntry, writer::Transaction};
+
+/// The column (or field) name used to match a record to a note by its path
+pub const PATH_KEY: &str = "path";
+/// The column (or field) name used to match a record to a note by its title
+pub const TITLE_KEY: &str = "title";
+
+/// Supported import formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ImportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ImportFormat::Csv),
+            "json" => Ok(ImportFormat::Json),
+            _ => Err(anyhow::anyhow!("Unknown import format: {}", s)),
+        }
+    }
+}
+
+impl ImportFormat {
+    /// Infer the import format from the extension of the given path
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        path.as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ext.parse().ok())
+    }
+}
+
+/// How a record identifies the note it should be applied to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordKey {
+    /// A path to the note, either absolute or relative to the vault root. The `.md` extension is
+    /// optional
+    Path(PathBuf),
+    /// The title of the note (i.e. the file name without the extension)
+    Title(String),
+}
+
+impl std::fmt::Display for RecordKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordKey::Path(p) => write!(f, "{}", p.display()),
+            RecordKey::Title(t) => write!(f, "{}", t),
+        }
+    }
+}
+
+/// A single set of property values to apply to a note
+#[derive(Debug, Clone)]
+pub struct ImportRecord {
+    /// The key used to find the note
+    pub key: RecordKey,
+    /// The properties to set in the note's frontmatter
+    pub properties: Mapping,
+}
+
+/// Read all records from the given reader in the specified format.
+///
+/// CSV input must have a header row containing either a `path` or `title` column. Every other
+/// column is treated as a property. Empty cells are skipped and cells that look like booleans or
+/// numbers are imported as such, with everything else imported as a string.
+///
+/// JSON input can either be an array of objects that each contain a `path` or `title` field, or an
+/// object mapping keys to objects of properties. In the latter form, keys ending in `.md` are
+/// treated as paths and all other keys as titles.
+pub fn read_records(reader: impl Read, format: ImportFormat) -> Result<Vec<ImportRecord>> {
+    match format {
+        ImportFormat::Csv => read_csv_records(reader),
+        ImportFormat::Json => read_json_records(reader),
+    }
+}
+
+fn read_csv_records(reader: impl Read) -> Result<Vec<ImportRecord>> {
+    let mut csv = csv::Reader::from_reader(reader);
+    let headers = csv.headers().context("Failed to read CSV headers")?.clone();
+    let (key_idx, is_path) = if let Some(idx) = headers.iter().position(|h| h == PATH_KEY) {
+        (idx, true)
+    } else if let Some(idx) = headers.iter().position(|h| h == TITLE_KEY) {
+        (idx, false)
+    } else {
+        anyhow::bail!("CSV input must have a '{PATH_KEY}' or '{TITLE_KEY}' column");
+    };
+
+    csv.records()
+        .map(|row| {
+            let row = row.context("Failed to read CSV row")?;
+            let key = row.get(key_idx).unwrap_or_default().trim();
+            if key.is_empty() {
+                anyhow::bail!("CSV row is missing a value for its key column");
+            }
+            let key = if is_path {
+                RecordKey::Path(PathBuf::from(key))
+            } else {
+                RecordKey::Title(key.to_string())
+            };
+            let properties = headers
+                .iter()
+                .zip(row.iter())
+                .enumerate()
+                .filter(|(idx, (_, cell))| *idx != key_idx && !cell.trim().is_empty())
+                .map(|(_, (header, cell))| (Value::from(header), parse_cell(cell.trim())))
+                .collect();
+            Ok(ImportRecord { key, properties })
+        })
+        .collect()
+}
+
+/// Parse a single CSV cell into a YAML value. Only booleans and numbers are inferred since
+/// anything else (like `[[Link]]`) would be mangled by treating it as YAML. Numbers are only kept
+/// if they are written exactly as given, so things like part numbers (`1e3`, `007`) stay strings
+fn parse_cell(cell: &str) -> Value {
+    match serde_norway::from_str::<Value>(cell) {
+        Ok(value @ Value::Bool(_)) => value,
+        Ok(Value::Number(n)) if n.to_string() == cell => Value::Number(n),
+        _ => Value::String(cell.to_string()),
+    }
+}
+
+fn read_json_records(reader: impl Read) -> Result<Vec<ImportRecord>> {
+    let data: serde_json::Value = serde_json::from_reader(reader).context("Invalid JSON input")?;
+    match data {
+        serde_json::Value::Array(items) => items
+            .into_iter()
+            .map(|item| {
+                let serde_json::Value::Object(mut obj) = item else {
+                    anyhow::bail!("Expected each JSON record to be an object");
+                };
+                let key = match (obj.remove(PATH_KEY), obj.remove(TITLE_KEY)) {
+                    (Some(serde_json::Value::String(p)), title) => {
+                        // The title is a normal property if we're matching on path
+                        if let Some(title) = title {
+                            obj.insert(TITLE_KEY.to_string(), title);
+                        }
+                        RecordKey::Path(PathBuf::from(p))
+                    }
+                    (None, Some(serde_json::Value::String(t))) => RecordKey::Title(t),
+                    _ => anyhow::bail!(
+                        "Expected each JSON record to have a string '{PATH_KEY}' or '{TITLE_KEY}' field"
+                    ),
+                };
+                Ok(ImportRecord {
+                    key,
+                    properties: json_properties(obj)?,
+                })
+            })
+            .collect(),
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .map(|(key, value)| {
+                let serde_json::Value::Object(obj) = value else {
+                    anyhow::bail!("Expected properties for {key} to be an object");
+                };
+                let key = if key.ends_with(".md") {
+                    RecordKey::Path(PathBuf::from(key))
+                } else {
+                    RecordKey::Title(key)
+                };
+                Ok(ImportRecord {
+                    key,
+                    properties: json_properties(obj)?,
+                })
+            })
+            .collect(),
+        _ => anyhow::bail!("Expected JSON input to be an array or an object"),
+    }
+}
+
+fn json_properties(obj: serde_json::Map<String, serde_json::Value>) -> Result<Mapping> {
+    obj.into_iter()
+        .map(|(k, v)| {
+            let v = serde_norway::to_value(v).context("Failed to convert JSON value")?;
+            Ok((Value::String(k), v))
+        })
+        .collect()
+}
+
+/// A summary of an import
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    /// Files whose frontmatter was changed by the import
+    pub updated: BTreeSet<PathBuf>,
+    /// Files that matched a record but were already up to date
+    pub unchanged: BTreeSet<PathBuf>,
+    /// Record keys that didn't match any note in the vault
+    pub unmatched: Vec<String>,
+    /// Record keys (titles) that matched more than one note in the vault
+    pub ambiguous: Vec<String>,
+}
+
+/// Applies import records to the notes in a vault
+pub struct Importer<'a> {
+    vault_root: &'a Path,
+    files: BTreeSet<PathBuf>,
+    titles: HashMap<String, Vec<PathBuf>>,
+    overwrite: bool,
+}
+
+impl<'a> Importer<'a> {
+    /// Create a new importer for the vault at the given root with the given file entries (usually
+    /// from [`crate::reader::read_dir`]). Only markdown files are considered when matching records
+    pub fn new(vault_root: &'a Path, entries: impl IntoIterator<Item = FileEntry>) -> Self {
+        let mut files = BTreeSet::new();
+        let mut titles: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        for entry in entries {
+            if !entry
+                .path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+            {
+                continue;
+            }
+            if let Some(stem) = entry.path.file_stem().and_then(|s| s.to_str()) {
+                titles
+                    .entry(stem.to_string())
+                    .or_default()
+                    .push(entry.path.clone());
+            }
+            files.insert(entry.path);
+        }
+        Self {
+            vault_root,
+            files,
+            titles,
+            overwrite: false,
+        }
+    }
+
+    /// Whether to overwrite properties that already exist in a note. Defaults to false, in which
+    /// case only missing properties are added
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Stage all records in the given transaction, returning a report of what was matched. Nothing
+    /// is written to disk until the transaction is committed
+    pub fn apply(
+        &self,
+        tx: &mut Transaction,
+        records: impl IntoIterator<Item = ImportRecord>,
+    ) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        for record in records {
+            let path = match self.resolve(&record.key) {
+                Resolved::Found(path) => path,
+                Resolved::Missing => {
+                    report.unmatched.push(record.key.to_string());
+                    continue;
+                }
+                Resolved::Ambiguous => {
+                    report.ambiguous.push(record.key.to_string());
+                    continue;
+                }
+            };
+            let overwrite = self.overwrite;
+            let changed = tx.edit_frontmatter(path, |fm| {
+                for (key, value) in record.properties {
+                    if overwrite || !fm.contains_key(&key) {
+                        fm.insert(key, value);
+                    }
+                }
+                Ok(())
+            })?;
+            if changed {
+                report.unchanged.remove(path);
+                report.updated.insert(path.to_path_buf());
+            } else if !report.updated.contains(path) {
+                report.unchanged.insert(path.to_path_buf());
+            }
+        }
+        Ok(report)
+    }
+
+    fn resolve(&self, key: &RecordKey) -> Resolved<'_> {
+        match key {
+            RecordKey::Path(path) => {
+                // Try the path as given first, then as a note name without the extension (which
+                // may itself contain a dot, like `my.book`)
+                let full = self.vault_root.join(path);
+                let mut with_ext = full.clone().into_os_string();
+                with_ext.push(".md");
+                match self
+                    .files
+                    .get(&full)
+                    .or_else(|| self.files.get(Path::new(&with_ext)))
+                {
+                    Some(found) => Resolved::Found(found),
+                    None => Resolved::Missing,
+                }
+            }
+            RecordKey::Title(title) => match self.titles.get(title).map(Vec::as_slice) {
+                Some([found]) => Resolved::Found(found),
+                Some([]) | None => Resolved::Missing,
+                Some(_) => Resolved::Ambiguous,
+            },
+        }
+    }
+}
+
+enum Resolved<'a> {
+    Found(&'a Path),
+    Missing,
+    Ambiguous,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_records_infer_scalar_types() -> Result<()> {
+        let input = "title,rating,url,read,empty\nBook,5,https://example.com,true,\n";
+        let records = read_records(input.as_bytes(), ImportFormat::Csv)?;
+
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.key, RecordKey::Title("Book".into()));
+        assert_eq!(record.properties.get("rating"), Some(&Value::from(5)));
+        assert_eq!(
+            record.properties.get("url"),
+            Some(&Value::from("https://example.com"))
+        );
+        assert_eq!(record.properties.get("read"), Some(&Value::Bool(true)));
+        assert!(!record.properties.contains_key("empty"));
+        assert!(!record.properties.contains_key(TITLE_KEY));
+
+        // Number-like text that wouldn't be written back the same way stays a string
+        let input = "title,code,score,serial\nBook,1e3,-2.5,007\n";
+        let records = read_records(input.as_bytes(), ImportFormat::Csv)?;
+        let props = &records[0].properties;
+        assert_eq!(props.get("code"), Some(&Value::from("1e3")));
+        assert_eq!(props.get("score"), Some(&Value::from(-2.5)));
+        assert_eq!(props.get("serial"), Some(&Value::from("007")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn csv_records_require_key_column() {
+        let result = read_records("name,rating\nBook,5\n".as_bytes(), ImportFormat::Csv);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn json_records_support_arrays_and_objects() -> Result<()> {
+        let input = r#"[{"path": "notes/Book.md", "title": "A Book", "tags": ["a"]}]"#;
+        let records = read_records(input.as_bytes(), ImportFormat::Json)?;
+        assert_eq!(records[0].key, RecordKey::Path("notes/Book.md".into()));
+        assert_eq!(
+            records[0].properties.get(TITLE_KEY),
+            Some(&Value::from("A Book"))
+        );
+
+        let input = r#"{"Book": {"rating": 4}, "notes/Other.md": {"rating": 2}}"#;
+        let mut records = read_records(input.as_bytes(), ImportFormat::Json)?;
+        records.sort_by_key(|r| r.key.to_string());
+        assert_eq!(records[0].key, RecordKey::Title("Book".into()));
+        assert_eq!(records[1].key, RecordKey::Path("notes/Other.md".into()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn importer_applies_records_and_reports_misses() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("a"))?;
+        std::fs::create_dir(dir.path().join("b"))?;
+        std::fs::write(dir.path().join("Book.md"), "---\nrating: 1\n---\nbody\n")?;
+        std::fs::write(dir.path().join("a/Dup.md"), "")?;
+        std::fs::write(dir.path().join("b/Dup.md"), "")?;
+        std::fs::write(dir.path().join("a/my.book.md"), "")?;
+
+        let entries = crate::reader::read_dir(dir.path(), true)?;
+        let importer = Importer::new(dir.path(), entries);
+        let mut records = read_records(
+            "title,rating,url\nBook,5,https://example.com\nDup,1,\nMissing,1,\n".as_bytes(),
+            ImportFormat::Csv,
+        )?;
+        // Paths match with or without the extension, even when the name has a dot in it
+        records.extend(read_records(
+            "path,rating\nBook.md,5\na/my.book,3\n".as_bytes(),
+            ImportFormat::Csv,
+        )?);
+
+        let mut tx = Transaction::new();
+        let report = importer.apply(&mut tx, records)?;
+        assert_eq!(
+            report.updated,
+            BTreeSet::from([dir.path().join("Book.md"), dir.path().join("a/my.book.md")])
+        );
+        assert_eq!(report.ambiguous, vec!["Dup".to_string()]);
+        assert_eq!(report.unmatched, vec!["Missing".to_string()]);
+
+        // Without overwrite, the existing rating is kept
+        let staged = tx.get(dir.path().join("Book.md")).unwrap();
+        assert!(staged.contains("rating: 1"));
+        assert!(staged.contains("url: https://example.com"));
+        let staged = tx.get(dir.path().join("a/my.book.md")).unwrap();
+        assert!(staged.contains("rating: 3"));
+
+        Ok(())
+    }
+}
@@ -1,7 +1,24 @@
+pub mod base_blocks;
+pub mod blocks;
+pub mod coerce;
+pub mod config;
+pub mod dedup;
+pub mod digest;
 pub mod frontmatter;
+pub mod import;
+pub mod migrate;
+pub mod outline;
 pub mod parser;
+pub mod periodic;
 pub mod printer;
+pub mod progress;
+pub mod properties;
 pub mod reader;
+pub mod scaffold;
+#[cfg(feature = "search")]
+pub mod search;
+pub mod stats;
+pub mod writer;
 
 /// The key used in serialized representations of tag data. This key is used when combining data
 /// together from different commands before (i.e. having both tags and links data in one output).
@@ -0,0 +1,235 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde_norway::{Mapping, Value};
+
+use crate::{periodic::NoteDateFormat, writer::Transaction};
+
+/// Common ways dates get written in frontmatter. These are tried (after the target format) when
+/// normalizing dates. Day first formats like `15/01/2025` are deliberately not included as they
+/// are ambiguous with the US style `01/15/2025`
+const KNOWN_DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d",
+    "%Y/%m/%d",
+    "%Y.%m.%d",
+    "%Y%m%d",
+    "%d.%m.%Y",
+    "%m/%d/%Y",
+    "%B %d, %Y",
+    "%b %d, %Y",
+    "%d %B %Y",
+    "%d %b %Y",
+];
+
+/// A single change to apply to the frontmatter of every file being migrated
+#[derive(Debug, Clone, PartialEq)]
+pub enum Migration {
+    /// Rename a property, keeping its position in the frontmatter. Fails if a file already has
+    /// both properties, as there is no way to know which value to keep
+    Rename { from: String, to: String },
+    /// Wrap a scalar property in a list (e.g. `alias: foo` becomes `alias: [foo]`). Properties that
+    /// are already lists or empty are left alone
+    ToList { key: String },
+    /// Rewrite a date property (or every date in a list property) in the given format. Values that
+    /// can't be parsed as a date are left alone
+    NormalizeDate { key: String, format: NoteDateFormat },
+}
+
+impl Migration {
+    /// Apply this migration to a frontmatter mapping
+    pub fn apply(&self, frontmatter: &mut Mapping) -> Result<()> {
+        match self {
+            Migration::Rename { from, to } => {
+                if from == to || !frontmatter.contains_key(from.as_str()) {
+                    return Ok(());
+                }
+                anyhow::ensure!(
+                    !frontmatter.contains_key(to.as_str()),
+                    "Unable to rename {from} to {to} as both properties are set"
+                );
+                // Rebuild the mapping so the renamed property keeps its place
+                *frontmatter = std::mem::take(frontmatter)
+                    .into_iter()
+                    .map(|(k, v)| match k {
+                        Value::String(k) if k == *from => (Value::String(to.clone()), v),
+                        k => (k, v),
+                    })
+                    .collect();
+            }
+            Migration::ToList { key } => {
+                if let Some(value) = frontmatter.get_mut(key.as_str())
+                    && !matches!(value, Value::Sequence(_) | Value::Null)
+                {
+                    *value = Value::Sequence(vec![std::mem::take(value)]);
+                }
+            }
+            Migration::NormalizeDate { key, format } => match frontmatter.get_mut(key.as_str()) {
+                Some(Value::Sequence(items)) => items
+                    .iter_mut()
+                    .for_each(|item| normalize_date(item, format)),
+                Some(value) => normalize_date(value, format),
+                None => {}
+            },
+        }
+        Ok(())
+    }
+}
+
+fn normalize_date(value: &mut Value, format: &NoteDateFormat) {
+    let Value::String(raw) = value else {
+        return;
+    };
+    match parse_date(raw.trim(), format) {
+        Some(date) => *raw = format.format_date(date),
+        None => log::debug!("Skipping value {raw} as it isn't a recognized date"),
+    }
+}
+
+fn parse_date(raw: &str, format: &NoteDateFormat) -> Option<NaiveDate> {
+    format.parse_date(raw).or_else(|| {
+        KNOWN_DATE_FORMATS
+            .iter()
+            .find_map(|f| NaiveDate::parse_from_str(raw, f).ok())
+    })
+}
+
+/// Stage the given migrations (applied in order) for each of the files in the transaction.
+/// Returns the paths of the files that were changed. Nothing is written until the transaction is
+/// committed, so a failure in any file leaves the vault untouched.
+pub fn migrate_files<P: AsRef<Path>>(
+    tx: &mut Transaction,
+    paths: impl IntoIterator<Item = P>,
+    migrations: &[Migration],
+) -> Result<Vec<PathBuf>> {
+    let mut changed = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let updated = tx.edit_frontmatter(path, |fm| {
+            migrations
+                .iter()
+                .try_for_each(|migration| migration.apply(fm))
+        })?;
+        if updated {
+            changed.push(path.to_path_buf());
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(yaml: &str) -> Mapping {
+        serde_norway::from_str(yaml).unwrap()
+    }
+
+    fn apply(yaml: &str, migration: Migration) -> Result<Mapping> {
+        let mut fm = mapping(yaml);
+        migration.apply(&mut fm)?;
+        Ok(fm)
+    }
+
+    #[test]
+    fn renames_in_place() -> Result<()> {
+        let rename = || Migration::Rename {
+            from: "alias".into(),
+            to: "aliases".into(),
+        };
+        let fm = apply("title: a\nalias: b\ntags: [c]", rename())?;
+        let keys: Vec<_> = fm.keys().filter_map(Value::as_str).collect();
+        assert_eq!(keys, vec!["title", "aliases", "tags"]);
+
+        assert!(apply("alias: a\naliases: [b]", rename()).is_err());
+        assert_eq!(apply("title: a", rename())?, mapping("title: a"));
+        Ok(())
+    }
+
+    #[test]
+    fn converts_scalars_to_lists() -> Result<()> {
+        let to_list = || Migration::ToList { key: "tags".into() };
+        assert_eq!(apply("tags: work", to_list())?, mapping("tags: [work]"));
+        assert_eq!(apply("tags: [a, b]", to_list())?, mapping("tags: [a, b]"));
+        assert_eq!(apply("tags:", to_list())?, mapping("tags:"));
+        Ok(())
+    }
+
+    #[test]
+    fn normalizes_dates() -> Result<()> {
+        let normalize = |format: &str| Migration::NormalizeDate {
+            key: "due".into(),
+            format: NoteDateFormat::new(format).unwrap(),
+        };
+        assert_eq!(
+            apply("due: 01/15/2025", normalize("YYYY-MM-DD"))?,
+            mapping("due: '2025-01-15'")
+        );
+        assert_eq!(
+            apply(
+                "due: [2025-01-15, 'January 5, 2025', soon]",
+                normalize("DD.MM.YYYY")
+            )?,
+            mapping("due: ['15.01.2025', '05.01.2025', soon]")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn migrates_files_with_diffs() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let note = dir.path().join("note.md");
+        let untouched = dir.path().join("untouched.md");
+        std::fs::write(&note, "---\ntag: work\n---\nBody\n")?;
+        std::fs::write(&untouched, "No frontmatter\n")?;
+
+        let mut tx = Transaction::new();
+        let migrations = [
+            Migration::Rename {
+                from: "tag".into(),
+                to: "tags".into(),
+            },
+            Migration::ToList { key: "tags".into() },
+        ];
+        let changed = migrate_files(&mut tx, [&note, &untouched], &migrations)?;
+        assert_eq!(changed, vec![note.clone()]);
+
+        let diffs = tx.diffs()?;
+        assert_eq!(diffs.len(), 1);
+        assert!(
+            diffs[0].1.contains("-tag: work\n+tags:\n+- work\n"),
+            "{}",
+            diffs[0].1
+        );
+
+        tx.commit()?;
+        assert_eq!(
+            std::fs::read_to_string(&note)?,
+            "---\ntags:\n- work\n---\nBody\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn migrations_keep_comments_and_untouched_properties() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let note = dir.path().join("note.md");
+        std::fs::write(
+            &note,
+            "---\n# project metadata\ntitle: \"My: note\"\ntags: work\nrating: 005\n---\nBody\n",
+        )?;
+
+        let mut tx = Transaction::new();
+        migrate_files(
+            &mut tx,
+            [&note],
+            &[Migration::ToList { key: "tags".into() }],
+        )?;
+        tx.commit()?;
+        assert_eq!(
+            std::fs::read_to_string(&note)?,
+            "---\n# project metadata\ntitle: \"My: note\"\ntags:\n- work\nrating: 005\n---\nBody\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use comrak::nodes::{AstNode, NodeValue};
+use serde::{Deserialize, Serialize};
+
+use crate::parser::{self, ParsedFile, Span};
+
+/// A heading in a markdown file along with all of the headings nested under it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Heading {
+    /// The level of the heading (1-6)
+    pub level: u8,
+    /// The plain text of the heading. This is also what Obsidian uses to link to a heading (e.g.
+    /// `[[Note#Heading text]]`)
+    pub text: String,
+    /// A GitHub style slug for the heading (e.g. `heading-text`), suitable for use as an anchor in
+    /// generated HTML or tables of contents. Slugs are unique within a file, with duplicates
+    /// getting a numeric suffix (`heading-text-1`)
+    pub slug: String,
+    /// The location of the heading in the file
+    pub span: Span,
+    /// All headings nested under this one. A heading is nested under the closest preceding heading
+    /// with a lower level, even if levels are skipped (e.g. an H3 directly under an H1)
+    pub children: Vec<Heading>,
+}
+
+impl Heading {
+    /// Iterate depth-first over this heading and all of its nested headings, in document order
+    pub fn iter(&self) -> impl Iterator<Item = &Heading> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let current = stack.pop()?;
+            stack.extend(current.children.iter().rev());
+            Some(current)
+        })
+    }
+}
+
+/// Iterate depth-first over all headings in an outline, in document order
+pub fn flatten(outline: &[Heading]) -> impl Iterator<Item = &Heading> {
+    outline.iter().flat_map(Heading::iter)
+}
+
+/// Find the first heading in an outline whose text matches the given heading reference (the part
+/// after the `#` in a link). Matching is case-insensitive, like it is in Obsidian
+pub fn find<'a>(outline: &'a [Heading], reference: &str) -> Option<&'a Heading> {
+    let reference = reference.trim();
+    flatten(outline).find(|h| h.text.eq_ignore_ascii_case(reference))
+}
+
+/// Parse the outline from a list of ParsedFiles, returning an iterator of tuples of the
+/// [`ParsedFile`] returned as is and the top level headings of the file
+pub fn parse_outlines<'a>(
+    entries: impl IntoIterator<Item = ParsedFile<'a>>,
+) -> impl Iterator<Item = (ParsedFile<'a>, Vec<Heading>)> {
+    entries.into_iter().map(|pf| {
+        let outline = outline_from_ast(pf.ast);
+        (pf, outline)
+    })
+}
+
+/// Build the heading tree from the AST of a markdown file
+pub fn outline_from_ast<'a>(ast: &'a AstNode<'a>) -> Vec<Heading> {
+    let mut slugs = HashMap::new();
+    let flat = ast.descendants().filter_map(|node| {
+        let NodeValue::Heading(ref heading) = node.data.borrow().value else {
+            return None;
+        };
+        let text = parser::node_text(node).trim().to_string();
+        Some(Heading {
+            level: heading.level,
+            slug: unique_slug(&mut slugs, &text),
+            text,
+            span: node.into(),
+            children: Vec::new(),
+        })
+    });
+
+    let mut roots = Vec::new();
+    let mut stack: Vec<Heading> = Vec::new();
+    for heading in flat {
+        while stack.last().is_some_and(|top| top.level >= heading.level) {
+            let done = stack.pop().expect("stack has a last element");
+            attach(&mut stack, &mut roots, done);
+        }
+        stack.push(heading);
+    }
+    while let Some(done) = stack.pop() {
+        attach(&mut stack, &mut roots, done);
+    }
+    roots
+}
+
+fn attach(stack: &mut [Heading], roots: &mut Vec<Heading>, heading: Heading) {
+    match stack.last_mut() {
+        Some(parent) => parent.children.push(heading),
+        None => roots.push(heading),
+    }
+}
+
+/// Generate a GitHub style slug for some heading text
+pub fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+fn unique_slug(seen: &mut HashMap<String, usize>, text: &str) -> String {
+    let slug = slugify(text);
+    let count = seen.entry(slug.clone()).or_insert(0);
+    let unique = if *count == 0 {
+        slug
+    } else {
+        format!("{slug}-{count}")
+    };
+    *count += 1;
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline_for(input: &str) -> Vec<Heading> {
+        let arena = comrak::Arena::new();
+        let ast = crate::parser::parse_content(&arena, input);
+        outline_from_ast(ast)
+    }
+
+    #[test]
+    fn builds_nested_heading_tree() {
+        let outline = outline_for(
+            "# Title\n\n## First\n\n### Deep\n\n## Second\n\n#### Skipped level\n\n# Another\n",
+        );
+
+        assert_eq!(outline.len(), 2);
+        let title = &outline[0];
+        assert_eq!(title.text, "Title");
+        assert_eq!(title.children.len(), 2);
+        assert_eq!(title.children[0].children[0].text, "Deep");
+        assert_eq!(title.children[1].children[0].text, "Skipped level");
+        assert_eq!(outline[1].text, "Another");
+        assert_eq!(outline[1].span.start_line, 11);
+
+        let order: Vec<_> = flatten(&outline).map(|h| h.text.as_str()).collect();
+        assert_eq!(
+            order,
+            vec![
+                "Title",
+                "First",
+                "Deep",
+                "Second",
+                "Skipped level",
+                "Another"
+            ]
+        );
+    }
+
+    #[test]
+    fn slugs_are_unique_and_github_style() {
+        let outline = outline_for("## Hello, **World**!\n\n## Hello World\n\n## `code` & more\n");
+        let slugs: Vec<_> = flatten(&outline).map(|h| h.slug.as_str()).collect();
+
+        assert_eq!(slugs, vec!["hello-world", "hello-world-1", "code--more"]);
+        assert_eq!(outline[0].text, "Hello, World!");
+    }
+
+    #[test]
+    fn find_matches_heading_references() {
+        let outline = outline_for("# Title\n\n## Some Section\n");
+
+        assert_eq!(
+            find(&outline, "some section").map(|h| h.span.start_line),
+            Some(3)
+        );
+        assert!(find(&outline, "missing").is_none());
+    }
+}
@@ -3,10 +3,20 @@ use std::{
     sync::LazyLock,
 };
 
-use anyhow::{Context, Result};
-use comrak::{Arena, Options, nodes::AstNode, options::Extension};
+use anyhow::Result;
+use chrono::NaiveDate;
+use comrak::{
+    Arena, Options,
+    nodes::{AstNode, NodeValue, Sourcepos},
+    options::{Extension, Parse},
+};
+use serde::{Deserialize, Serialize};
 
-use crate::reader::FileEntry;
+use crate::{
+    periodic::NoteDateFormat,
+    progress::{ProgressEvent, ProgressReporter},
+    reader::FileEntry,
+};
 
 pub const FRONTMATTER_DELIMITER: &str = "---";
 static PARSE_OPTIONS: LazyLock<Options<'static>> = LazyLock::new(|| Options {
@@ -18,6 +28,12 @@ static PARSE_OPTIONS: LazyLock<Options<'static>> = LazyLock::new(|| Options {
         front_matter_delimiter: Some(FRONTMATTER_DELIMITER.into()),
         alerts: true,
         wikilinks_title_after_pipe: true,
+        tasklist: true,
+        ..Default::default()
+    },
+    parse: Parse {
+        // Obsidian allows any character as a task status (e.g. `[-]` for cancelled tasks)
+        relaxed_tasklist_matching: true,
         ..Default::default()
     },
     ..Default::default()
@@ -33,6 +49,157 @@ pub struct ParsedFile<'a> {
     pub ast: &'a AstNode<'a>,
 }
 
+impl ParsedFile<'_> {
+    /// Get the date of this note if its path matches the given periodic note format (e.g. a daily
+    /// note named `2025-01-15.md`)
+    pub fn note_date(&self, format: &NoteDateFormat) -> Option<NaiveDate> {
+        format.note_date(&self.path)
+    }
+}
+
+/// The location of a node in the source markdown. Lines and columns are 1-based and the end is
+/// inclusive, matching how most editors report positions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Span {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl From<Sourcepos> for Span {
+    fn from(pos: Sourcepos) -> Self {
+        Span {
+            start_line: pos.start.line,
+            start_column: pos.start.column,
+            end_line: pos.end.line,
+            end_column: pos.end.column,
+        }
+    }
+}
+
+impl<'a> From<&'a AstNode<'a>> for Span {
+    fn from(node: &'a AstNode<'a>) -> Self {
+        node.data.borrow().sourcepos.into()
+    }
+}
+
+/// Collect the plain text content of a node and all of its descendants. Formatting is dropped,
+/// inline code is kept as its literal text, and line breaks are collapsed into single spaces.
+pub fn node_text<'a>(node: &'a AstNode<'a>) -> String {
+    let mut text = String::new();
+    for child in node.descendants() {
+        match &child.data.borrow().value {
+            NodeValue::Code(code) => text.push_str(&code.literal),
+            NodeValue::SoftBreak | NodeValue::LineBreak => text.push(' '),
+            value => {
+                if let Some(t) = value.text() {
+                    text.push_str(t);
+                }
+            }
+        }
+    }
+    text
+}
+
+/// The kind of problem that caused a file to fail to parse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// The file was removed after the vault was read
+    NotFound,
+    /// The file couldn't be read due to permissions
+    PermissionDenied,
+    /// The file isn't valid UTF-8 (usually a binary file with a `.md` extension)
+    InvalidUtf8,
+    /// Any other error
+    Other,
+}
+
+/// An error for a specific file that failed to parse. Errors returned from [`parse_files`] and
+/// [`parse_file`] can be downcast to this type to find out which file failed
+#[derive(Debug)]
+pub struct ParseError {
+    pub path: PathBuf,
+    source: std::io::Error,
+}
+
+impl ParseError {
+    /// The category of the error
+    pub fn category(&self) -> ErrorCategory {
+        match self.source.kind() {
+            std::io::ErrorKind::NotFound => ErrorCategory::NotFound,
+            std::io::ErrorKind::PermissionDenied => ErrorCategory::PermissionDenied,
+            std::io::ErrorKind::InvalidData => ErrorCategory::InvalidUtf8,
+            _ => ErrorCategory::Other,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to load {} from disk", self.path.display())
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// A single file that failed to parse
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseFailure {
+    /// The path of the file, if known
+    pub path: Option<PathBuf>,
+    pub category: ErrorCategory,
+    /// The full error message
+    pub error: String,
+}
+
+/// A report of all files that failed to parse. See [`report_error_iter`]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseReport {
+    pub failures: Vec<ParseFailure>,
+}
+
+impl ParseReport {
+    /// Returns true if no failures were recorded
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Record an error in the report
+    pub fn record(&mut self, error: &anyhow::Error) {
+        let parse_error = error.downcast_ref::<ParseError>();
+        self.failures.push(ParseFailure {
+            path: parse_error.map(|e| e.path.clone()),
+            category: parse_error.map_or(ErrorCategory::Other, ParseError::category),
+            error: format!("{error:#}"),
+        });
+    }
+}
+
+/// Like [`ignore_error_iter`], but also records every error in the given report so they can be
+/// shown to the user once processing is done
+pub fn report_error_iter<'a, I>(
+    iter: I,
+    report: &mut ParseReport,
+) -> impl Iterator<Item = ParsedFile<'a>>
+where
+    I: IntoIterator<Item = Result<ParsedFile<'a>>>,
+{
+    iter.into_iter().filter_map(|res| match res {
+        Ok(v) => Some(v),
+        Err(e) => {
+            log::error!("Ignoring error when parsing file: {e}");
+            report.record(&e);
+            None
+        }
+    })
+}
+
 /// A helper to ignore errors from an iterator of Results, yielding only the Ok values and logging
 /// the error instead
 pub fn ignore_error_iter<'a, I>(iter: I) -> impl Iterator<Item = ParsedFile<'a>>
@@ -57,37 +224,121 @@ pub fn parse_files<'a>(
 ) -> impl Iterator<Item = Result<ParsedFile<'a>>> {
     entries
         .into_iter()
-        .filter(|e| {
-            e.path
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+        .filter(|e| is_markdown(&e.path))
+        .map(|entry| parse_entry(arena, entry))
+}
+
+/// The same as [`parse_files`], but reports progress to the given reporter as files are parsed. A
+/// [`ProgressEvent::Discovered`] event with the number of markdown files is sent before the first
+/// file is parsed
+pub fn parse_files_with_progress<'a, R>(
+    arena: &'a Arena<AstNode<'a>>,
+    entries: impl IntoIterator<Item = FileEntry>,
+    reporter: R,
+) -> impl Iterator<Item = Result<ParsedFile<'a>>>
+where
+    R: ProgressReporter + 'a,
+{
+    let entries: Vec<_> = entries
+        .into_iter()
+        .filter(|e| is_markdown(&e.path))
+        .collect();
+    reporter.report(ProgressEvent::Discovered {
+        total: entries.len(),
+    });
+    entries.into_iter().map(move |entry| {
+        let path = entry.path.clone();
+        let parsed = parse_entry(arena, entry);
+        match &parsed {
+            Ok(_) => reporter.report(ProgressEvent::Parsed { path: &path }),
+            Err(error) => reporter.report(ProgressEvent::Failed { path: &path, error }),
+        }
+        parsed
+    })
+}
+
+/// Parse a single file entry, keeping its path and metadata alongside the AST
+fn parse_entry<'a>(arena: &'a Arena<AstNode<'a>>, entry: FileEntry) -> Result<ParsedFile<'a>> {
+    let root = parse_file(arena, &entry.path)?;
+    Ok(ParsedFile {
+        path: entry.path,
+        metadata: entry.metadata,
+        ast: root,
+    })
+}
+
+/// An async version of [`parse_files`] that reads files without blocking the runtime. Files are
+/// still parsed on the calling task since the AST lives in the (non thread safe) arena, so the
+/// returned stream yields files one at a time in the same order as the entries
+#[cfg(feature = "async")]
+pub fn parse_files_stream<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    entries: impl IntoIterator<Item = FileEntry>,
+) -> impl tokio_stream::Stream<Item = Result<ParsedFile<'a>>> + 'a {
+    use tokio_stream::StreamExt;
+
+    let entries: Vec<_> = entries
+        .into_iter()
+        .filter(|e| is_markdown(&e.path))
+        .collect();
+    tokio_stream::iter(entries)
+        .then(|entry| async move {
+            let content = tokio::fs::read_to_string(&entry.path).await;
+            (entry, content)
         })
-        .map(|entry| {
-            let root = parse_file(arena, &entry.path)?;
+        .map(move |(entry, content)| {
+            let content = content.map_err(|source| ParseError {
+                path: entry.path.clone(),
+                source,
+            })?;
             Ok(ParsedFile {
                 path: entry.path,
                 metadata: entry.metadata,
-                ast: root,
+                ast: parse_content(arena, &content),
             })
         })
 }
 
+/// Returns true if the path has a markdown (`.md`) extension
+pub fn is_markdown(path: impl AsRef<Path>) -> bool {
+    path.as_ref()
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+}
+
 /// Parse a markdown file from disk into an AST node
 pub fn parse_file<'a>(
     arena: &'a Arena<AstNode<'a>>,
     path: impl AsRef<Path>,
 ) -> Result<&'a AstNode<'a>> {
-    let content = std::fs::read_to_string(&path).context("Failed to load file from disk")?;
-
+    let content = read_file(path)?;
     Ok(parse_content(arena, &content))
 }
 
+/// Read a markdown file from disk. Errors can be downcast to a [`ParseError`] like the ones from
+/// [`parse_file`], so they can be recorded in a [`ParseReport`]
+pub fn read_file(path: impl AsRef<Path>) -> Result<String> {
+    std::fs::read_to_string(&path).map_err(|source| {
+        ParseError {
+            path: path.as_ref().to_path_buf(),
+            source,
+        }
+        .into()
+    })
+}
+
 /// Parse markdown content into an AST node
 pub fn parse_content<'a>(arena: &'a Arena<AstNode<'a>>, content: &str) -> &'a AstNode<'a> {
     comrak::parse_document(arena, content, &PARSE_OPTIONS)
 }
 
+/// Render markdown content to HTML using the same extensions it is parsed with. Frontmatter is not
+/// included in the output
+pub fn render_html(content: &str) -> String {
+    comrak::markdown_to_html(content, &PARSE_OPTIONS)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -168,4 +419,99 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn node_text_flattens_inline_formatting() {
+        let arena = Arena::new();
+        let ast = parse_content(&arena, "Some **bold** and `code`\nwith a [link](x.md)");
+
+        assert_eq!(node_text(ast), "Some bold and code with a link");
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn parse_files_stream_matches_sync() -> Result<()> {
+        use tokio_stream::StreamExt;
+
+        let vault = vault_path();
+        let arena = Arena::new();
+        let mut expected: Vec<PathBuf> = parse_files(&arena, reader::read_dir(&vault, true)?)
+            .map(|pf| pf.map(|pf| pf.path))
+            .collect::<Result<_>>()?;
+        let mut actual: Vec<PathBuf> =
+            parse_files_stream(&arena, reader::read_dir_async(&vault, true).await?)
+                .map(|pf| pf.map(|pf| pf.path))
+                .collect::<Result<Vec<_>>>()
+                .await?;
+
+        expected.sort();
+        actual.sort();
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_files_with_progress_reports_events() -> Result<()> {
+        use std::cell::RefCell;
+
+        let vault = vault_path();
+        let mut entries = reader::read_dir(&vault, true)?;
+        let notes = entries.iter().filter(|e| is_markdown(&e.path)).count();
+        entries.push(FileEntry {
+            path: vault.join("Missing.md"),
+            metadata: std::fs::metadata(vault.join("Test.md"))?,
+        });
+        let events = RefCell::new(Vec::new());
+        let arena = Arena::new();
+        let reporter = |event: ProgressEvent<'_>| {
+            events.borrow_mut().push(match event {
+                ProgressEvent::Discovered { total } => format!("discovered {total}"),
+                ProgressEvent::Parsed { .. } => "parsed".to_string(),
+                ProgressEvent::Failed { path, .. } => {
+                    format!("failed {}", path.file_name().unwrap().to_string_lossy())
+                }
+            })
+        };
+        let parsed = parse_files_with_progress(&arena, entries, &reporter).count();
+
+        let events = events.borrow();
+        assert_eq!(parsed, notes + 1);
+        assert_eq!(events[0], format!("discovered {}", notes + 1));
+        assert_eq!(events.iter().filter(|e| *e == "parsed").count(), notes);
+        assert_eq!(events.last().unwrap(), "failed Missing.md");
+        Ok(())
+    }
+
+    #[test]
+    fn report_error_iter_records_failures() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("good.md"), "# Good")?;
+        std::fs::write(dir.path().join("binary.md"), [0xff, 0xfe, 0x00])?;
+        let mut entries = reader::read_dir(dir.path(), false)?;
+        entries.push(FileEntry {
+            path: dir.path().join("gone.md"),
+            metadata: std::fs::metadata(dir.path().join("good.md"))?,
+        });
+
+        let arena = Arena::new();
+        let mut report = ParseReport::default();
+        let parsed = report_error_iter(parse_files(&arena, entries), &mut report).count();
+
+        assert_eq!(parsed, 1);
+        let mut failures: Vec<_> = report
+            .failures
+            .iter()
+            .map(|f| (f.path.clone().unwrap(), f.category))
+            .collect();
+        failures.sort();
+        assert_eq!(
+            failures,
+            vec![
+                (dir.path().join("binary.md"), ErrorCategory::InvalidUtf8),
+                (dir.path().join("gone.md"), ErrorCategory::NotFound),
+            ]
+        );
+        assert!(report.failures[0].error.contains("Failed to load"));
+        Ok(())
+    }
 }
@@ -0,0 +1,364 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+
+/// The default format Obsidian uses for daily note file names
+pub const DEFAULT_DAILY_FORMAT: &str = "YYYY-MM-DD";
+/// The path (relative to the vault root) of the core Daily Notes plugin's settings
+pub const DAILY_NOTES_CONFIG: &str = ".obsidian/daily-notes.json";
+
+/// A date format used to name periodic notes (like daily notes). This parses and formats file
+/// names using the same [Moment.js](https://momentjs.com/docs/#/displaying/format/) format strings
+/// that Obsidian uses, so the format can be copied straight out of the Obsidian settings.
+///
+/// Formats may contain `/` to put notes in date based folders (e.g. `YYYY/MM/YYYY-MM-DD`). Weekly
+/// formats (e.g. `gggg-[W]ww`) are also supported, in which case the date of a note is the Monday
+/// of its week. Week numbers are always ISO weeks, regardless of locale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteDateFormat {
+    moment: String,
+    chrono: String,
+    weekly: bool,
+    folder: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DailyNotesConfig {
+    format: Option<String>,
+    folder: Option<String>,
+    template: Option<String>,
+}
+
+impl DailyNotesConfig {
+    fn load(vault_root: &Path) -> Result<Self> {
+        let config_path = vault_root.join(DAILY_NOTES_CONFIG);
+        match std::fs::read(&config_path) {
+            Ok(raw) => serde_json::from_slice(&raw)
+                .with_context(|| format!("Invalid daily notes config {}", config_path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(DailyNotesConfig::default()),
+            Err(e) => Err(e).context("Failed to read daily notes config"),
+        }
+    }
+}
+
+/// Get the template (relative to the vault root, without an extension) configured for new daily
+/// notes in the Daily Notes plugin settings, if any
+pub fn daily_notes_template(vault_root: impl AsRef<Path>) -> Result<Option<String>> {
+    Ok(DailyNotesConfig::load(vault_root.as_ref())?
+        .template
+        .map(|t| t.trim_matches('/').to_string())
+        .filter(|t| !t.is_empty()))
+}
+
+impl NoteDateFormat {
+    /// Create a new format from a Moment.js format string. Returns an error if the format uses
+    /// tokens that can't be used to identify a single date
+    pub fn new(format: impl Into<String>) -> Result<Self> {
+        let moment = format.into();
+        let chrono = moment_to_chrono(&moment)?;
+        let weekly = chrono.contains("%G")
+            && !["%d", "%-d", "%j", "%a", "%A"]
+                .iter()
+                .any(|day| chrono.contains(day));
+        Ok(Self {
+            moment,
+            chrono,
+            weekly,
+            folder: None,
+        })
+    }
+
+    /// Load the daily note format for a vault from the Daily Notes plugin settings. If the vault has
+    /// no settings (or they don't set a format), the Obsidian default of `YYYY-MM-DD` is used. If
+    /// the settings specify a folder, only notes in that folder are considered daily notes
+    pub fn daily_notes(vault_root: impl AsRef<Path>) -> Result<Self> {
+        let vault_root = vault_root.as_ref();
+        let config = DailyNotesConfig::load(vault_root)?;
+        let format = config
+            .format
+            .filter(|f| !f.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_DAILY_FORMAT.to_string());
+        let folder = config
+            .folder
+            .map(|f| f.trim_matches('/').to_string())
+            .filter(|f| !f.is_empty())
+            .map(|f| vault_root.join(f));
+        Ok(Self::new(format)?.with_folder(folder))
+    }
+
+    /// Only consider notes inside the given folder (or any subfolder of it). The folder should use
+    /// the same path style (absolute or relative) as the paths that will be checked
+    pub fn with_folder(mut self, folder: Option<PathBuf>) -> Self {
+        self.folder = folder;
+        self
+    }
+
+    /// The folder notes in this format are kept in, if any
+    pub fn folder(&self) -> Option<&Path> {
+        self.folder.as_deref()
+    }
+
+    /// The Moment.js format string for this format
+    pub fn as_str(&self) -> &str {
+        &self.moment
+    }
+
+    /// Get the date of a note from its path, returning `None` if the path doesn't match the format
+    pub fn note_date(&self, path: impl AsRef<Path>) -> Option<NaiveDate> {
+        let path = path.as_ref();
+        if let Some(folder) = &self.folder
+            && !path.starts_with(folder)
+        {
+            return None;
+        }
+        // A format with folders in it needs to match the same number of trailing path components
+        let depth = self.moment.matches('/').count() + 1;
+        let stem = path.with_extension("");
+        let components: Vec<_> = stem
+            .components()
+            .rev()
+            .take(depth)
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect();
+        if components.len() < depth {
+            return None;
+        }
+        let candidate = components.into_iter().rev().collect::<Vec<_>>().join("/");
+        self.parse_date(&candidate)
+    }
+
+    /// Get the path (relative to the vault root or the configured folder, without an extension)
+    /// that a note for the given date should have
+    pub fn file_stem(&self, date: NaiveDate) -> String {
+        self.format_date(date)
+    }
+
+    /// Get the path (relative to the vault root, without an extension) that a note for the given
+    /// date should have, including the configured folder. The vault root should be the same path
+    /// the folder was set with, such as the one passed to [`NoteDateFormat::daily_notes`]
+    pub fn note_path(&self, vault_root: impl AsRef<Path>, date: NaiveDate) -> PathBuf {
+        let folder = match &self.folder {
+            Some(folder) => folder.strip_prefix(vault_root).unwrap_or(folder),
+            None => Path::new(""),
+        };
+        folder.join(self.file_stem(date))
+    }
+
+    /// Parse a date written in this format (ignoring any configured folder)
+    pub fn parse_date(&self, s: &str) -> Option<NaiveDate> {
+        if self.weekly {
+            // A week on its own isn't a date, so parse it as the Monday of that week
+            NaiveDate::parse_from_str(&format!("{s} 1"), &format!("{} %u", self.chrono)).ok()
+        } else {
+            NaiveDate::parse_from_str(s, &self.chrono).ok()
+        }
+    }
+
+    /// Whether this is a weekly format (i.e. it has a week number but no day)
+    pub fn is_weekly(&self) -> bool {
+        self.weekly
+    }
+
+    /// Format a date using this format
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        date.format(&self.chrono).to_string()
+    }
+}
+
+impl Default for NoteDateFormat {
+    fn default() -> Self {
+        Self::new(DEFAULT_DAILY_FORMAT).expect("default format is valid")
+    }
+}
+
+/// Convert a Moment.js format string into a chrono format string
+fn moment_to_chrono(format: &str) -> Result<String> {
+    let (out, has_year) = convert_moment(format)?;
+    if !has_year {
+        anyhow::bail!("Date format {format} must contain a year");
+    }
+    if (out.contains("%V") || out.contains("%-V")) && !out.contains("%G") {
+        anyhow::bail!("Weekly date format {format} must use a week year (GGGG or gggg)");
+    }
+    Ok(out)
+}
+
+/// Format a date and time using a Moment.js format string. Unlike [`NoteDateFormat`], the format
+/// doesn't need to identify a full date, so it can be used for things like `HH:mm`
+pub fn format_moment(format: &str, datetime: NaiveDateTime) -> Result<String> {
+    let (chrono, _) = convert_moment(format)?;
+    Ok(datetime.format(&chrono).to_string())
+}
+
+/// Convert a Moment.js format string into a chrono format string, returning whether the format
+/// contains a year
+fn convert_moment(format: &str) -> Result<(String, bool)> {
+    // Longest tokens first so that e.g. `YYYY` isn't matched as two `YY`s
+    const TOKENS: &[(&str, &str)] = &[
+        ("YYYY", "%Y"),
+        ("YY", "%y"),
+        ("GGGG", "%G"),
+        ("gggg", "%G"),
+        ("WW", "%V"),
+        ("ww", "%V"),
+        ("W", "%-V"),
+        ("w", "%-V"),
+        ("MMMM", "%B"),
+        ("MMM", "%b"),
+        ("MM", "%m"),
+        ("M", "%-m"),
+        ("DDDD", "%j"),
+        ("DD", "%d"),
+        ("D", "%-d"),
+        ("dddd", "%A"),
+        ("ddd", "%a"),
+        ("HH", "%H"),
+        ("H", "%-H"),
+        ("hh", "%I"),
+        ("h", "%-I"),
+        ("mm", "%M"),
+        ("m", "%-M"),
+        ("ss", "%S"),
+        ("s", "%-S"),
+        ("A", "%p"),
+        ("a", "%P"),
+    ];
+
+    let mut out = String::new();
+    let mut has_year = false;
+    let mut rest = format;
+    while !rest.is_empty() {
+        // Anything in square brackets is an escaped literal
+        if let Some(escaped) = rest.strip_prefix('[') {
+            let (literal, after) = escaped
+                .split_once(']')
+                .context("Unterminated '[' in date format")?;
+            out.push_str(&literal.replace('%', "%%"));
+            rest = after;
+            continue;
+        }
+        if let Some((token, chrono)) = TOKENS.iter().find(|(token, _)| rest.starts_with(token)) {
+            has_year |= token.starts_with(['Y', 'G', 'g']);
+            out.push_str(chrono);
+            rest = &rest[token.len()..];
+            continue;
+        }
+        let c = rest.chars().next().expect("rest is not empty");
+        if c.is_ascii_alphabetic() {
+            anyhow::bail!("Unsupported token '{c}' in date format {format}");
+        }
+        if c == '%' {
+            out.push('%');
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    Ok((out, has_year))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn parses_default_daily_note_names() {
+        let format = NoteDateFormat::default();
+
+        assert_eq!(
+            format.note_date("/vault/Daily/2025-01-15.md"),
+            Some(date(2025, 1, 15))
+        );
+        assert_eq!(format.note_date("/vault/2025-02-30.md"), None);
+        assert_eq!(format.note_date("/vault/Meeting 2025-01-15.md"), None);
+        assert_eq!(format.file_stem(date(2025, 1, 5)), "2025-01-05");
+    }
+
+    #[test]
+    fn supports_literals_names_and_folders() -> Result<()> {
+        let format = NoteDateFormat::new("YYYY/MMMM/[Journal] D MMM, YYYY (dddd)")?;
+
+        assert_eq!(
+            format.note_date("/vault/2025/January/Journal 5 Jan, 2025 (Sunday).md"),
+            Some(date(2025, 1, 5))
+        );
+        assert_eq!(
+            format.note_date("Journal 5 Jan, 2025 (Sunday).md"),
+            None,
+            "folders in the format must be present"
+        );
+        assert_eq!(
+            format.file_stem(date(2025, 1, 5)),
+            "2025/January/Journal 5 Jan, 2025 (Sunday)"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn supports_weekly_formats() -> Result<()> {
+        let format = NoteDateFormat::new("gggg-[W]ww")?;
+        assert!(format.is_weekly());
+        assert!(!NoteDateFormat::default().is_weekly());
+
+        assert_eq!(
+            format.note_date("/vault/2025-W03.md"),
+            Some(date(2025, 1, 13))
+        );
+        // ISO week 1 of 2025 starts in 2024
+        assert_eq!(format.parse_date("2025-W01"), Some(date(2024, 12, 30)));
+        assert_eq!(format.file_stem(date(2025, 1, 15)), "2025-W03");
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_formats_without_a_full_date() {
+        assert!(NoteDateFormat::new("MM-DD").is_err());
+        assert!(NoteDateFormat::new("YYYY-[W]ww").is_err());
+        assert!(NoteDateFormat::new("YYYY-[MM").is_err());
+    }
+
+    #[test]
+    fn daily_notes_config_sets_format_and_folder() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join(".obsidian"))?;
+        std::fs::write(
+            dir.path().join(DAILY_NOTES_CONFIG),
+            r#"{"format": "DD.MM.YYYY", "folder": "Journal/"}"#,
+        )?;
+        let format = NoteDateFormat::daily_notes(dir.path())?;
+
+        assert_eq!(format.as_str(), "DD.MM.YYYY");
+        assert_eq!(
+            format.note_date(dir.path().join("Journal/15.01.2025.md")),
+            Some(date(2025, 1, 15))
+        );
+        assert_eq!(format.note_date(dir.path().join("15.01.2025.md")), None);
+
+        // A vault without the config falls back to the default format
+        let format = NoteDateFormat::daily_notes(dir.path().join("missing"))?;
+        assert_eq!(format.as_str(), DEFAULT_DAILY_FORMAT);
+        Ok(())
+    }
+
+    #[test]
+    fn note_paths_are_relative_to_the_vault() -> Result<()> {
+        let vault = Path::new("notes/vault");
+        let format = NoteDateFormat::daily_notes(vault)?;
+        assert_eq!(
+            format.note_path(vault, date(2025, 1, 5)),
+            PathBuf::from("2025-01-05")
+        );
+
+        let format = format.with_folder(Some(vault.join("Journal/Daily")));
+        assert_eq!(
+            format.note_path(vault, date(2025, 1, 5)),
+            PathBuf::from("Journal/Daily/2025-01-05")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    cell::Cell,
+    io::{IsTerminal, Write},
+    path::Path,
+};
+
+/// An event emitted while processing files in a vault
+#[derive(Debug)]
+pub enum ProgressEvent<'a> {
+    /// The total number of markdown files that will be parsed
+    Discovered { total: usize },
+    /// A file was parsed successfully
+    Parsed { path: &'a Path },
+    /// A file failed to parse
+    Failed {
+        path: &'a Path,
+        error: &'a anyhow::Error,
+    },
+}
+
+/// Something that receives progress events. This is implemented for any `Fn(ProgressEvent)`, so a
+/// closure can be used to forward events elsewhere (e.g. over a channel)
+pub trait ProgressReporter {
+    fn report(&self, event: ProgressEvent<'_>);
+}
+
+impl<F: Fn(ProgressEvent<'_>)> ProgressReporter for F {
+    fn report(&self, event: ProgressEvent<'_>) {
+        self(event)
+    }
+}
+
+/// A simple progress bar drawn on stderr. Nothing is drawn if the bar is disabled or stderr isn't a
+/// terminal, so it is always safe to pass to the parser
+#[derive(Debug, Default)]
+pub struct ProgressBar {
+    enabled: bool,
+    /// Track progress without drawing anything (used by tests so they don't write to stderr)
+    hidden: bool,
+    total: Cell<usize>,
+    done: Cell<usize>,
+    failed: Cell<usize>,
+}
+
+const BAR_WIDTH: usize = 30;
+
+impl ProgressBar {
+    /// Create a new progress bar. If `enabled` is false, this does nothing
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: enabled && std::io::stderr().is_terminal(),
+            ..Default::default()
+        }
+    }
+
+    fn draw(&self) {
+        let (done, total, failed) = (self.done.get(), self.total.get(), self.failed.get());
+        // Only redraw every percent so huge vaults don't spend their time writing to the terminal
+        let step = (total / 100).max(1);
+        if done != total && done % step != 0 {
+            return;
+        }
+        if self.hidden {
+            return;
+        }
+        let mut stderr = std::io::stderr().lock();
+        let _ = render(&mut stderr, done, total, failed);
+    }
+}
+
+/// Write a single frame of the bar to the given output
+fn render(out: &mut impl Write, done: usize, total: usize, failed: usize) -> std::io::Result<()> {
+    let filled = (done * BAR_WIDTH).checked_div(total).unwrap_or(BAR_WIDTH);
+    write!(
+        out,
+        "\r[{}{}] {done}/{total} files",
+        "=".repeat(filled),
+        " ".repeat(BAR_WIDTH - filled),
+    )?;
+    if failed > 0 {
+        write!(out, " ({failed} failed)")?;
+    }
+    if done == total {
+        writeln!(out)?;
+    }
+    out.flush()
+}
+
+impl ProgressReporter for ProgressBar {
+    fn report(&self, event: ProgressEvent<'_>) {
+        if !self.enabled {
+            return;
+        }
+        match event {
+            ProgressEvent::Discovered { total } => self.total.set(total),
+            ProgressEvent::Parsed { .. } => self.done.set(self.done.get() + 1),
+            ProgressEvent::Failed { .. } => {
+                self.done.set(self.done.get() + 1);
+                self.failed.set(self.failed.get() + 1);
+            }
+        }
+        self.draw();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_bar_counts_files() {
+        let path = Path::new("note.md");
+        let error = anyhow::anyhow!("broken");
+        let events = || {
+            [
+                ProgressEvent::Discovered { total: 3 },
+                ProgressEvent::Parsed { path },
+                ProgressEvent::Failed {
+                    path,
+                    error: &error,
+                },
+                ProgressEvent::Parsed { path },
+            ]
+        };
+
+        let disabled = ProgressBar::new(false);
+        // Skip the terminal check so this works when stderr is captured
+        let enabled = ProgressBar {
+            enabled: true,
+            hidden: true,
+            ..Default::default()
+        };
+        for bar in [&disabled, &enabled] {
+            for event in events() {
+                bar.report(event);
+            }
+        }
+
+        assert_eq!((disabled.done.get(), disabled.failed.get()), (0, 0));
+        assert_eq!(enabled.total.get(), 3);
+        assert_eq!((enabled.done.get(), enabled.failed.get()), (3, 1));
+    }
+
+    #[test]
+    fn renders_bar() {
+        let mut out = Vec::new();
+        render(&mut out, 1, 2, 0).unwrap();
+        render(&mut out, 2, 2, 1).unwrap();
+        let bar =
+            |filled: usize| format!("{}{}", "=".repeat(filled), " ".repeat(BAR_WIDTH - filled));
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "\r[{}] 1/2 files\r[{}] 2/2 files (1 failed)\n",
+                bar(15),
+                bar(30)
+            )
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::{cmp::Ordering, str::FromStr};
+
+use crate::{
+    coerce::{CoercionPolicy, TypedValue},
+    frontmatter::Frontmatter,
+};
+
+/// A simple predicate on a frontmatter property, parsed from strings like `status=done`,
+/// `priority>=2`, `due<2025-01-01`, `draft` (the property is set), or `!draft` (the property is
+/// not set).
+///
+/// For list properties (like `tags`), `=` and `!=` check whether any item matches. Values are
+/// converted with a [`CoercionPolicy`] first, so numbers, dates, and booleans compare by value and
+/// anything else compares as text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyFilter {
+    Exists(String),
+    Missing(String),
+    Compare {
+        key: String,
+        op: CompareOp,
+        value: String,
+    },
+}
+
+/// A comparison operator for a [`PropertyFilter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+impl FromStr for PropertyFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Two character operators need to be checked first so `>=` isn't parsed as `>`
+        const OPS: [(&str, CompareOp); 6] = [
+            ("!=", CompareOp::NotEq),
+            ("<=", CompareOp::LtEq),
+            (">=", CompareOp::GtEq),
+            ("=", CompareOp::Eq),
+            ("<", CompareOp::Lt),
+            (">", CompareOp::Gt),
+        ];
+        let s = s.trim();
+        let op = OPS
+            .iter()
+            .filter_map(|(token, op)| s.find(token).map(|idx| (idx, token, op)))
+            .min_by_key(|(idx, token, _)| (*idx, std::cmp::Reverse(token.len())));
+        if let Some((idx, token, op)) = op {
+            let key = s[..idx].trim();
+            anyhow::ensure!(!key.is_empty(), "Missing property name in filter {s}");
+            return Ok(PropertyFilter::Compare {
+                key: key.to_string(),
+                op: *op,
+                value: s[idx + token.len()..].trim().to_string(),
+            });
+        }
+        let (key, missing) = match s.strip_prefix('!') {
+            Some(key) => (key.trim(), true),
+            None => (s, false),
+        };
+        anyhow::ensure!(!key.is_empty(), "Empty property filter");
+        Ok(if missing {
+            PropertyFilter::Missing(key.to_string())
+        } else {
+            PropertyFilter::Exists(key.to_string())
+        })
+    }
+}
+
+impl PropertyFilter {
+    /// Check whether the given frontmatter matches this filter, inferring the types of values with
+    /// the default (lenient) [`CoercionPolicy`]. Files without frontmatter only match
+    /// [`PropertyFilter::Missing`] and `!=` filters
+    pub fn matches(&self, frontmatter: Option<&Frontmatter>) -> bool {
+        self.matches_with(frontmatter, &CoercionPolicy::default())
+    }
+
+    /// Same as [`PropertyFilter::matches`], but converts values with the given policy before
+    /// comparing them. For example, with a strict policy `"10"` (quoted) is compared as text
+    pub fn matches_with(&self, frontmatter: Option<&Frontmatter>, policy: &CoercionPolicy) -> bool {
+        let get = |key: &str| frontmatter.and_then(|fm| fm.get(key));
+        match self {
+            PropertyFilter::Exists(key) => get(key).is_some_and(|v| !v.is_null()),
+            PropertyFilter::Missing(key) => get(key).is_none_or(|v| v.is_null()),
+            PropertyFilter::Compare { key, op, value } => {
+                let items = match get(key).map(|actual| policy.coerce(key, &actual)) {
+                    Some(TypedValue::List(items)) => items,
+                    Some(TypedValue::Null) | None => Vec::new(),
+                    Some(item) => vec![item],
+                };
+                let mut orderings = items
+                    .iter()
+                    .filter(|item| !matches!(item, TypedValue::Null | TypedValue::List(_)))
+                    .map(|item| item.compare_text(value));
+                match op {
+                    CompareOp::Eq => orderings.any(|o| o == Ordering::Equal),
+                    CompareOp::NotEq => !orderings.any(|o| o == Ordering::Equal),
+                    CompareOp::Lt => orderings.any(|o| o == Ordering::Less),
+                    CompareOp::LtEq => orderings.any(|o| o != Ordering::Greater),
+                    CompareOp::Gt => orderings.any(|o| o == Ordering::Greater),
+                    CompareOp::GtEq => orderings.any(|o| o != Ordering::Less),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        coerce::{CoercionMode, PropertyType},
+        frontmatter,
+    };
+
+    fn fm(yaml: &str) -> Frontmatter {
+        frontmatter::parse_frontmatter_text(yaml, frontmatter::ParseMode::Strict)
+            .unwrap()
+            .frontmatter
+    }
+
+    fn filter(s: &str) -> PropertyFilter {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_filters() {
+        assert_eq!(filter("draft"), PropertyFilter::Exists("draft".into()));
+        assert_eq!(filter("!draft"), PropertyFilter::Missing("draft".into()));
+        assert_eq!(
+            filter("priority >= 2"),
+            PropertyFilter::Compare {
+                key: "priority".into(),
+                op: CompareOp::GtEq,
+                value: "2".into()
+            }
+        );
+        assert_eq!(
+            filter("title=a=b"),
+            PropertyFilter::Compare {
+                key: "title".into(),
+                op: CompareOp::Eq,
+                value: "a=b".into()
+            }
+        );
+        assert!("=value".parse::<PropertyFilter>().is_err());
+        assert!("".parse::<PropertyFilter>().is_err());
+    }
+
+    #[test]
+    fn matches_frontmatter() {
+        let note = fm("tags: [work, urgent]\nstatus: done\npriority: 10\ndue: 2025-01-15\nempty:");
+
+        assert!(filter("tags=work").matches(Some(&note)));
+        assert!(filter("tags!=home").matches(Some(&note)));
+        assert!(filter("status=done").matches(Some(&note)));
+        assert!(filter("priority>9").matches(Some(&note)));
+        assert!(
+            !filter("priority<9").matches(Some(&note)),
+            "numeric compare"
+        );
+        assert!(filter("due<2025-02-01").matches(Some(&note)));
+        assert!(filter("!empty").matches(Some(&note)));
+        assert!(filter("!missing").matches(Some(&note)));
+        assert!(!filter("status").matches(None));
+        assert!(filter("!status").matches(None));
+    }
+
+    #[test]
+    fn matches_with_coercion_policy() {
+        let note = fm("count: '10'
+id: '007'
+done: 'false'");
+        let strict = CoercionPolicy::new(CoercionMode::Strict);
+
+        assert!(filter("count>9").matches(Some(&note)));
+        assert!(
+            !filter("count>9").matches_with(Some(&note), &strict),
+            "strict mode compares quoted numbers as text"
+        );
+        assert!(filter("done=false").matches(Some(&note)));
+        assert!(filter("id=7").matches(Some(&note)));
+        let typed = strict.with_type("id", PropertyType::Text);
+        assert!(!filter("id=7").matches_with(Some(&note), &typed));
+        assert!(filter("id=007").matches_with(Some(&note), &typed));
+    }
+
+    #[test]
+    fn matches_links_in_lists() {
+        let note = fm("related: ['[[Projects/Homelab]]', '[[Garden|the garden]]']");
+        let strict =
+            CoercionPolicy::new(CoercionMode::Strict).with_type("related", PropertyType::List);
+        for policy in [CoercionPolicy::default(), strict] {
+            assert!(filter("related=[[Garden]]").matches_with(Some(&note), &policy));
+            assert!(filter("related=Projects/Homelab").matches_with(Some(&note), &policy));
+            assert!(!filter("related=Homelab").matches_with(Some(&note), &policy));
+        }
+    }
+}
@@ -3,7 +3,9 @@ use std::fs::Metadata;
 use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+
+use crate::{parser::ParseReport, progress::ProgressBar};
 
 #[derive(Args, Debug)]
 pub struct ReaderOpts {
@@ -18,21 +20,78 @@ pub struct ReaderOpts {
     /// When reading from stdin, if --recurse is set to true, files in directories will also be
     /// read. Otherwise, only files will be read and all other paths ignored.
     pub dir: Option<PathBuf>,
+
+    /// Show a progress bar on stderr while parsing files. This is only drawn when stderr is a
+    /// terminal, so it won't end up in redirected output
+    #[arg(long, default_value_t = false)]
+    pub progress: bool,
+
+    /// Print a report of every file that failed to parse (and why) to stderr once done. Failed
+    /// files are always skipped, this just makes it easy to find out which ones broke
+    #[arg(long = "report-errors", default_value_t = false)]
+    pub report_errors: bool,
+
+    /// Skip files matching this glob pattern (e.g. `Templates` or `**/*.png`). Patterns are
+    /// matched against paths relative to the directory being read, and a pattern matching a folder
+    /// skips everything in it. Can be passed multiple times
+    #[arg(long = "exclude")]
+    pub exclude: Vec<String>,
 }
 
 impl ReaderOpts {
     /// Get this list of file entries from stdin or by the directory specified in the options.
     pub fn read_files(&self) -> Result<Vec<FileEntry>> {
         // If a directory is explicitly provided, use it regardless of stdin state
-        if let Some(dir) = &self.dir {
-            read_dir(dir, self.recurse)
+        let entries = if let Some(dir) = &self.dir {
+            read_dir(dir, self.recurse)?
         } else if !std::io::stdin().is_terminal() {
             // Only read from stdin if no directory was provided
-            read_stdin(self.recurse)
+            read_stdin(self.recurse)?
         } else {
-            Err(anyhow::anyhow!(
+            return Err(anyhow::anyhow!(
                 "No vault directory specified and no input from stdin. Cannot proceed."
-            ))
+            ));
+        };
+        if self.exclude.is_empty() {
+            return Ok(entries);
+        }
+        let patterns = self
+            .exclude
+            .iter()
+            .map(|p| glob::Pattern::new(p).with_context(|| format!("Invalid exclude pattern {p}")))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(entries
+            .into_iter()
+            .filter(|entry| {
+                let relative = self
+                    .dir
+                    .as_ref()
+                    .and_then(|dir| entry.path.strip_prefix(dir).ok())
+                    .unwrap_or(&entry.path);
+                !is_excluded(relative, &patterns)
+            })
+            .collect())
+    }
+
+    /// Get a progress bar to pass to [`crate::parser::parse_files_with_progress`]. This does nothing
+    /// unless `--progress` was passed
+    pub fn progress_bar(&self) -> ProgressBar {
+        ProgressBar::new(self.progress)
+    }
+
+    /// Print the given parse report to stderr if `--report-errors` was passed and anything failed
+    pub fn print_report(&self, report: &ParseReport) {
+        if !self.report_errors || report.is_empty() {
+            return;
+        }
+        eprintln!("{} file(s) failed to parse:", report.failures.len());
+        for failure in &report.failures {
+            let path = failure
+                .path
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "<unknown>".to_string());
+            eprintln!("  {path} ({:?}): {}", failure.category, failure.error);
         }
     }
 }
@@ -62,6 +121,30 @@ pub fn read_dir(path: impl AsRef<Path>, recurse: bool) -> Result<Vec<FileEntry>>
     Ok(entries)
 }
 
+/// An async version of [`read_dir`] for use in long running services that shouldn't block their
+/// runtime while walking large vaults. This finds exactly the same entries as [`read_dir`]
+#[cfg(feature = "async")]
+pub async fn read_dir_async(path: impl AsRef<Path>, recurse: bool) -> Result<Vec<FileEntry>> {
+    let mut entries = vec![];
+    // Directories are walked depth first with an explicit stack since async fns can't recurse
+    // without boxing
+    let mut stack = vec![tokio::fs::read_dir(path.as_ref()).await?];
+    while let Some(dir) = stack.last_mut() {
+        let Some(entry) = dir.next_entry().await? else {
+            stack.pop();
+            continue;
+        };
+        let p = entry.path();
+        let metadata = entry.metadata().await?;
+        if metadata.is_dir() && recurse {
+            stack.push(tokio::fs::read_dir(&p).await?);
+        } else if metadata.is_file() {
+            entries.push(FileEntry { path: p, metadata });
+        }
+    }
+    Ok(entries)
+}
+
 pub fn read_stdin(recurse: bool) -> Result<Vec<FileEntry>> {
     let mut entries = vec![];
     for line in std::io::stdin().lines() {
@@ -79,6 +162,14 @@ pub fn read_stdin(recurse: bool) -> Result<Vec<FileEntry>> {
     Ok(entries)
 }
 
+/// Whether the path matches any of the patterns. A pattern matching a folder excludes everything
+/// in it
+fn is_excluded(path: &Path, patterns: &[glob::Pattern]) -> bool {
+    path.ancestors()
+        .filter(|p| !p.as_os_str().is_empty())
+        .any(|p| patterns.iter().any(|pattern| pattern.matches_path(p)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,4 +229,38 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn excludes_folders_and_files() {
+        let patterns: Vec<_> = ["Templates", "*.png", "Archive/**/*.md"]
+            .iter()
+            .map(|p| glob::Pattern::new(p).unwrap())
+            .collect();
+        assert!(is_excluded(Path::new("Templates/Daily.md"), &patterns));
+        assert!(is_excluded(Path::new("images/cat.png"), &patterns));
+        assert!(is_excluded(Path::new("Archive/2024/Old.md"), &patterns));
+        assert!(!is_excluded(Path::new("Projects/Templates.md"), &patterns));
+        assert!(!is_excluded(Path::new("Archive/2024/cat.jpg"), &patterns));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn read_dir_async_matches_sync() -> anyhow::Result<()> {
+        let vault = vault_path();
+        for recurse in [true, false] {
+            let mut expected: Vec<PathBuf> = read_dir(&vault, recurse)?
+                .into_iter()
+                .map(|e| e.path)
+                .collect();
+            let mut actual: Vec<PathBuf> = read_dir_async(&vault, recurse)
+                .await?
+                .into_iter()
+                .map(|e| e.path)
+                .collect();
+            expected.sort();
+            actual.sort();
+            assert_eq!(actual, expected, "recurse = {recurse}");
+        }
+        Ok(())
+    }
 }
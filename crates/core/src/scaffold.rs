@@ -0,0 +1,260 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+
+use crate::periodic;
+
+/// The path (relative to the vault root) of the core Templates plugin's settings
+pub const TEMPLATES_CONFIG: &str = ".obsidian/templates.json";
+/// The default format for `{{date}}`, matching the Templates plugin
+pub const DEFAULT_DATE_FORMAT: &str = "YYYY-MM-DD";
+/// The default format for `{{time}}`, matching the Templates plugin
+pub const DEFAULT_TIME_FORMAT: &str = "HH:mm";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TemplatesConfig {
+    folder: Option<String>,
+    date_format: Option<String>,
+    time_format: Option<String>,
+}
+
+/// Creates new notes from templates, using the same variables as the core Templates plugin:
+///
+/// - `{{title}}`: The name of the new note (without the extension)
+/// - `{{date}}` and `{{time}}`: The current date and time in the configured formats
+/// - `{{date:FORMAT}}` and `{{time:FORMAT}}`: The current date and time in a custom Moment.js format
+///
+/// Any other `{{...}}` text is left as is so templates meant for other plugins still work.
+#[derive(Debug, Clone)]
+pub struct Scaffold {
+    vault_root: PathBuf,
+    templates_folder: Option<PathBuf>,
+    date_format: String,
+    time_format: String,
+}
+
+impl Scaffold {
+    /// Create a new scaffold for the given vault using the default Templates plugin settings
+    pub fn new(vault_root: impl Into<PathBuf>) -> Self {
+        Self {
+            vault_root: vault_root.into(),
+            templates_folder: None,
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            time_format: DEFAULT_TIME_FORMAT.to_string(),
+        }
+    }
+
+    /// Create a new scaffold for the given vault, loading the templates folder and date/time
+    /// formats from the Templates plugin settings if they exist
+    pub fn from_vault(vault_root: impl Into<PathBuf>) -> Result<Self> {
+        let mut scaffold = Self::new(vault_root);
+        let config_path = scaffold.vault_root.join(TEMPLATES_CONFIG);
+        let config: TemplatesConfig = match std::fs::read(&config_path) {
+            Ok(raw) => serde_json::from_slice(&raw)
+                .with_context(|| format!("Invalid templates config {}", config_path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => TemplatesConfig::default(),
+            Err(e) => return Err(e).context("Failed to read templates config"),
+        };
+        scaffold.templates_folder = config
+            .folder
+            .map(|f| f.trim_matches('/').to_string())
+            .filter(|f| !f.is_empty())
+            .map(|f| scaffold.vault_root.join(f));
+        if let Some(format) = config.date_format.filter(|f| !f.trim().is_empty()) {
+            scaffold.date_format = format;
+        }
+        if let Some(format) = config.time_format.filter(|f| !f.trim().is_empty()) {
+            scaffold.time_format = format;
+        }
+        Ok(scaffold)
+    }
+
+    /// Find a template by name. Names are looked up in the templates folder first and then relative
+    /// to the vault root. The `.md` extension is optional
+    pub fn find_template(&self, name: &str) -> Result<PathBuf> {
+        let file = with_markdown_extension(Path::new(name));
+        self.templates_folder
+            .iter()
+            .map(|folder| folder.join(&file))
+            .chain(std::iter::once(self.vault_root.join(&file)))
+            .find(|path| path.is_file())
+            .with_context(|| format!("Unable to find template {name}"))
+    }
+
+    /// Fill in the template variables in the given template text
+    pub fn render(&self, template: &str, title: &str, now: NaiveDateTime) -> Result<String> {
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                rest = &rest[start..];
+                break;
+            };
+            let variable = after[..end].trim();
+            let (name, format) = match variable.split_once(':') {
+                Some((name, format)) => (name.trim(), Some(format.trim())),
+                None => (variable, None),
+            };
+            match (name, format) {
+                ("title", None) => out.push_str(title),
+                ("date", format) => out.push_str(&periodic::format_moment(
+                    format.unwrap_or(&self.date_format),
+                    now,
+                )?),
+                ("time", format) => out.push_str(&periodic::format_moment(
+                    format.unwrap_or(&self.time_format),
+                    now,
+                )?),
+                _ => out.push_str(&rest[start..start + end + 4]),
+            }
+            rest = &after[end + 2..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// Create a new note at the given path (relative to the vault root, `.md` optional) from a
+    /// template, returning the path of the new note. If no template is given, an empty note is
+    /// created. Missing parent folders are created, but an existing note is never overwritten
+    pub fn create_note(
+        &self,
+        template: Option<&Path>,
+        note: impl AsRef<Path>,
+        now: NaiveDateTime,
+    ) -> Result<PathBuf> {
+        self.create(template, note.as_ref(), now)
+            .map(|(path, _)| path)
+    }
+
+    /// Same as [`Scaffold::create_note`], but also adds the new note to a search index so it can
+    /// be found without reindexing the vault
+    #[cfg(feature = "search")]
+    pub fn create_indexed_note(
+        &self,
+        template: Option<&Path>,
+        note: impl AsRef<Path>,
+        now: NaiveDateTime,
+        index: &mut crate::search::VaultIndex,
+    ) -> Result<PathBuf> {
+        let (path, content) = self.create(template, note.as_ref(), now)?;
+        let arena = comrak::Arena::new();
+        index.add(&path, crate::parser::parse_content(&arena, &content));
+        Ok(path)
+    }
+
+    fn create(
+        &self,
+        template: Option<&Path>,
+        note: &Path,
+        now: NaiveDateTime,
+    ) -> Result<(PathBuf, String)> {
+        let path = self.vault_root.join(with_markdown_extension(note));
+        let title = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        let content = match template {
+            Some(template) => {
+                let raw = std::fs::read_to_string(template)
+                    .with_context(|| format!("Failed to read template {}", template.display()))?;
+                self.render(&raw, &title, now)?
+            }
+            None => String::new(),
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create folder {}", parent.display()))?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .with_context(|| format!("Failed to create note {}", path.display()))?;
+        file.write_all(content.as_bytes())
+            .with_context(|| format!("Failed to write note {}", path.display()))?;
+        Ok((path, content))
+    }
+}
+
+fn with_markdown_extension(path: &Path) -> PathBuf {
+    if crate::parser::is_markdown(path) {
+        path.to_path_buf()
+    } else {
+        // Don't use `with_extension` as note names can contain dots
+        let mut file = path.as_os_str().to_owned();
+        file.push(".md");
+        PathBuf::from(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, 5)
+            .unwrap()
+            .and_hms_opt(14, 30, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn renders_template_variables() -> Result<()> {
+        let scaffold = Scaffold::new("/vault");
+        let rendered = scaffold.render(
+            "# {{title}}\nCreated {{date}} at {{ time }} ({{date:dddd, MMMM D}} {{time:h:mm A}})\n{{other}} {{unterminated",
+            "Project",
+            now(),
+        )?;
+        assert_eq!(
+            rendered,
+            "# Project\nCreated 2025-01-05 at 14:30 (Sunday, January 5 2:30 PM)\n{{other}} {{unterminated"
+        );
+        assert!(scaffold.render("{{date:Q}}", "", now()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn creates_notes_from_templates() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir_all(dir.path().join(".obsidian"))?;
+        std::fs::create_dir_all(dir.path().join("Templates"))?;
+        std::fs::write(
+            dir.path().join(TEMPLATES_CONFIG),
+            r#"{"folder": "Templates/", "dateFormat": "DD.MM.YYYY"}"#,
+        )?;
+        std::fs::write(
+            dir.path().join("Templates/Project.md"),
+            "---\ncreated: {{date}}\n---\n# {{title}}\n",
+        )?;
+
+        let scaffold = Scaffold::from_vault(dir.path())?;
+        let template = scaffold.find_template("Project")?;
+        let path = scaffold.create_note(Some(&template), "Projects/v1.0 Launch", now())?;
+
+        assert_eq!(path, dir.path().join("Projects/v1.0 Launch.md"));
+        assert_eq!(
+            std::fs::read_to_string(&path)?,
+            "---\ncreated: 05.01.2025\n---\n# v1.0 Launch\n"
+        );
+        assert!(
+            scaffold
+                .create_note(None, "Projects/v1.0 Launch.md", now())
+                .is_err(),
+            "existing notes should not be overwritten"
+        );
+        assert!(scaffold.find_template("Missing").is_err());
+        Ok(())
+    }
+}
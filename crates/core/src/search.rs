@@ -0,0 +1,269 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Bound,
+    path::{Path, PathBuf},
+};
+
+use comrak::nodes::{AstNode, NodeValue};
+use serde::{Deserialize, Serialize};
+
+use crate::parser::ParsedFile;
+
+// BM25 tuning parameters. These are the commonly used defaults
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// An in-memory full text index over the notes in a vault. Both note bodies (including code) and
+/// frontmatter are indexed.
+///
+/// Text is split into lowercased alphanumeric terms and results are ranked with BM25, so notes that
+/// mention rare terms often rank above notes that mention common terms once.
+#[derive(Debug, Default)]
+pub struct VaultIndex {
+    paths: Vec<PathBuf>,
+    lengths: Vec<usize>,
+    total_length: usize,
+    /// A mapping of term to the documents containing it and how many times it appears in each
+    postings: BTreeMap<String, BTreeMap<usize, usize>>,
+}
+
+/// A single search result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub path: PathBuf,
+    /// The relevance score of the note. Higher is more relevant. Scores are only comparable
+    /// between results of the same query
+    pub score: f64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum QueryTerm {
+    Exact(String),
+    Prefix(String),
+}
+
+/// Build an index from a list of ParsedFiles
+pub fn index_files<'a>(entries: impl IntoIterator<Item = ParsedFile<'a>>) -> VaultIndex {
+    let mut index = VaultIndex::new();
+    for pf in entries {
+        index.add(&pf.path, pf.ast);
+    }
+    index
+}
+
+impl VaultIndex {
+    /// Create a new, empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of notes in the index
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Returns true if no notes have been indexed
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Add a note to the index from its AST
+    pub fn add<'a>(&mut self, path: impl AsRef<Path>, ast: &'a AstNode<'a>) {
+        let doc = self.paths.len();
+        self.paths.push(path.as_ref().to_path_buf());
+
+        let terms = tokenize(&searchable_text(ast));
+        self.lengths.push(terms.len());
+        self.total_length += terms.len();
+        for term in terms {
+            *self
+                .postings
+                .entry(term)
+                .or_default()
+                .entry(doc)
+                .or_default() += 1;
+        }
+    }
+
+    /// Search the index, returning matching notes with the most relevant first.
+    ///
+    /// Every term in the query must appear in a note for it to match. A term ending in `*` matches
+    /// any term starting with it (e.g. `rust*` matches "rustacean"). Punctuation in the query is
+    /// treated the same way as in notes, so `foo-bar` searches for both "foo" and "bar"
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let terms = parse_query(query);
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scores: Option<HashMap<usize, f64>> = None;
+        for term in &terms {
+            let mut term_scores: HashMap<usize, f64> = HashMap::new();
+            for postings in self.matching_postings(term) {
+                let idf = self.idf(postings.len());
+                for (&doc, &freq) in postings {
+                    *term_scores.entry(doc).or_default() += idf * self.term_frequency(doc, freq);
+                }
+            }
+            scores = Some(match scores {
+                None => term_scores,
+                Some(previous) => previous
+                    .into_iter()
+                    .filter_map(|(doc, score)| term_scores.get(&doc).map(|s| (doc, score + s)))
+                    .collect(),
+            });
+        }
+
+        let mut hits: Vec<SearchHit> = scores
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(doc, score)| SearchHit {
+                path: self.paths[doc].clone(),
+                score,
+            })
+            .collect();
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        hits
+    }
+
+    fn matching_postings<'a>(
+        &'a self,
+        term: &'a QueryTerm,
+    ) -> Box<dyn Iterator<Item = &'a BTreeMap<usize, usize>> + 'a> {
+        match term {
+            QueryTerm::Exact(term) => Box::new(self.postings.get(term).into_iter()),
+            QueryTerm::Prefix(prefix) => Box::new(
+                self.postings
+                    .range::<str, _>((Bound::Included(prefix.as_str()), Bound::Unbounded))
+                    .take_while(move |(term, _)| term.starts_with(prefix.as_str()))
+                    .map(|(_, postings)| postings),
+            ),
+        }
+    }
+
+    fn idf(&self, matching_docs: usize) -> f64 {
+        let n = self.paths.len() as f64;
+        let matching = matching_docs as f64;
+        (1.0 + (n - matching + 0.5) / (matching + 0.5)).ln()
+    }
+
+    fn term_frequency(&self, doc: usize, freq: usize) -> f64 {
+        let average_length = self.total_length as f64 / self.paths.len() as f64;
+        let length_ratio = if average_length > 0.0 {
+            self.lengths[doc] as f64 / average_length
+        } else {
+            0.0
+        };
+        let freq = freq as f64;
+        freq * (K1 + 1.0) / (freq + K1 * (1.0 - B + B * length_ratio))
+    }
+}
+
+fn parse_query(query: &str) -> Vec<QueryTerm> {
+    query
+        .split_whitespace()
+        .flat_map(|word| match word.strip_suffix('*') {
+            Some(prefix) => {
+                let mut terms = tokenize(prefix);
+                // Only the last piece of something like `foo-ba*` is a prefix
+                let last = terms.pop();
+                terms
+                    .into_iter()
+                    .map(QueryTerm::Exact)
+                    .chain(last.map(QueryTerm::Prefix))
+                    .collect::<Vec<_>>()
+            }
+            None => tokenize(word).into_iter().map(QueryTerm::Exact).collect(),
+        })
+        .collect()
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Collect all of the text in a note that should be searchable, including the raw frontmatter
+fn searchable_text<'a>(ast: &'a AstNode<'a>) -> String {
+    let mut text = String::new();
+    for node in ast.descendants() {
+        let data = node.data.borrow();
+        match &data.value {
+            NodeValue::FrontMatter(fm) => text.push_str(fm),
+            NodeValue::Code(code) => text.push_str(&code.literal),
+            NodeValue::CodeBlock(block) => text.push_str(&block.literal),
+            value => {
+                if let Some(t) = value.text() {
+                    text.push_str(t);
+                }
+            }
+        }
+        if data.value.block() || matches!(data.value, NodeValue::SoftBreak | NodeValue::LineBreak) {
+            text.push(' ');
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_for(notes: &[(&str, &str)]) -> VaultIndex {
+        let arena = comrak::Arena::new();
+        let mut index = VaultIndex::new();
+        for (path, content) in notes {
+            let ast = crate::parser::parse_content(&arena, content);
+            index.add(path, ast);
+        }
+        index
+    }
+
+    fn paths(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.path.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn ranks_and_requires_all_terms() {
+        let index = index_for(&[
+            ("a.md", "# Rust\n\nRust is a language. Rust is fast.\n"),
+            ("b.md", "Notes about Rust and Go.\n"),
+            ("c.md", "---\ntags: [golang]\n---\nA Go note.\n"),
+        ]);
+
+        assert_eq!(paths(&index.search("rust")), vec!["a.md", "b.md"]);
+        assert_eq!(paths(&index.search("RUST go")), vec!["b.md"]);
+        assert_eq!(
+            paths(&index.search("golang")),
+            vec!["c.md"],
+            "frontmatter should be indexed"
+        );
+        assert!(index.search("python").is_empty());
+        assert!(index.search("  ").is_empty());
+    }
+
+    #[test]
+    fn supports_prefix_queries() {
+        let index = index_for(&[
+            ("a.md", "The **rust**acean crab\n"),
+            ("b.md", "```sh\ncargo build\n```\n"),
+        ]);
+
+        assert_eq!(paths(&index.search("rust*")), vec!["a.md"]);
+        assert_eq!(paths(&index.search("car*")), vec!["b.md"]);
+        assert!(index.search("rust").is_empty());
+        assert_eq!(
+            parse_query("foo-ba*"),
+            vec![
+                QueryTerm::Exact("foo".to_string()),
+                QueryTerm::Prefix("ba".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use comrak::nodes::{AstNode, NodeValue};
+use serde::{Deserialize, Serialize};
+
+use crate::parser::ParsedFile;
+
+/// The average reading speed (in words per minute) used to estimate reading time
+pub const WORDS_PER_MINUTE: usize = 200;
+
+/// Statistics about the content of a single markdown file. These only count the body of the file,
+/// so frontmatter is excluded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentStats {
+    /// The number of words in the file. Code blocks are not counted, but inline code is
+    pub words: usize,
+    /// The number of characters in the text of the file (including whitespace), counted the same
+    /// way as words
+    pub characters: usize,
+    /// The number of headings in the file
+    pub headings: usize,
+    /// The number of links (both internal and external) in the file
+    pub links: usize,
+    /// The estimated reading time in minutes, rounded up. This is 0 only for files without words
+    pub reading_time_minutes: usize,
+}
+
+/// Compute content statistics from a list of ParsedFiles, returning an iterator of tuples of the
+/// [`ParsedFile`] returned as is and the stats for the file
+pub fn parse_stats<'a>(
+    entries: impl IntoIterator<Item = ParsedFile<'a>>,
+) -> impl Iterator<Item = (ParsedFile<'a>, ContentStats)> {
+    entries.into_iter().map(|pf| {
+        let stats = stats_from_ast(pf.ast);
+        (pf, stats)
+    })
+}
+
+/// Compute content statistics from the AST of a markdown file
+pub fn stats_from_ast<'a>(ast: &'a AstNode<'a>) -> ContentStats {
+    let mut stats = ContentStats::default();
+    // We build up all the text so words split across formatting (e.g. `**bo**ld`) are counted once
+    let mut text = String::new();
+    for node in ast.descendants() {
+        let data = node.data.borrow();
+        let value = &data.value;
+        match value {
+            NodeValue::Heading(_) => stats.headings += 1,
+            NodeValue::Link(_) | NodeValue::WikiLink(_) => stats.links += 1,
+            NodeValue::Code(code) => text.push_str(&code.literal),
+            NodeValue::SoftBreak | NodeValue::LineBreak => text.push(' '),
+            _ => {}
+        }
+        if value.block() {
+            text.push('\n');
+        } else if let Some(t) = value.text() {
+            text.push_str(t);
+        }
+    }
+
+    let text = text.trim();
+    stats.words = text.split_whitespace().count();
+    stats.characters = text
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.chars().count())
+        .sum();
+    stats.reading_time_minutes = stats.words.div_ceil(WORDS_PER_MINUTE);
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats_for(input: &str) -> ContentStats {
+        let arena = comrak::Arena::new();
+        let ast = crate::parser::parse_content(&arena, input);
+        stats_from_ast(ast)
+    }
+
+    #[test]
+    fn counts_words_headings_and_links() {
+        let stats = stats_for(
+            "---\ntags: [ignored, words]\n---\n# Heading one\n\nSome **bo**ld text with a [link](x.md) and `code`.\n\n## Two\n\n```rust\nfn not_counted() {}\n```\n\n[[Wiki]] and https://example.com\n",
+        );
+
+        assert_eq!(stats.headings, 2);
+        assert_eq!(stats.links, 3);
+        // "Heading one" + "Some bold text with a link and code." + "Two" + "Wiki and https://..."
+        assert_eq!(stats.words, 2 + 8 + 1 + 3);
+        assert_eq!(stats.reading_time_minutes, 1);
+    }
+
+    #[test]
+    fn counts_characters_and_reading_time() {
+        let stats = stats_for("Hello world");
+        assert_eq!(stats.characters, 11);
+
+        let long = "word ".repeat(WORDS_PER_MINUTE + 1);
+        assert_eq!(stats_for(&long).reading_time_minutes, 2);
+
+        assert_eq!(stats_for("").reading_time_minutes, 0);
+    }
+}
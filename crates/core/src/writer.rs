@@ -0,0 +1,596 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde_norway::{Mapping, Value};
+
+use crate::parser::FRONTMATTER_DELIMITER;
+
+/// Split the raw content of a markdown file into the YAML text of its frontmatter (without the
+/// delimiters) and the rest of the body. If the file has no frontmatter, `None` is returned along
+/// with the full content as the body.
+pub fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content
+        .strip_prefix(FRONTMATTER_DELIMITER)
+        .and_then(|rest| {
+            rest.strip_prefix("\r\n")
+                .or_else(|| rest.strip_prefix('\n'))
+        })
+    else {
+        return (None, content);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == FRONTMATTER_DELIMITER {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    // An unterminated delimiter isn't frontmatter, so treat it as part of the body
+    (None, content)
+}
+
+/// Apply an edit to the frontmatter of the given markdown content, returning the new content if
+/// the edit changed anything. Files without frontmatter are handed an empty mapping and will only
+/// gain a frontmatter block if the edit adds values to it.
+///
+/// Only the properties that changed are rewritten, so comments, quoting, and formatting of
+/// everything else in the frontmatter block are kept as is. Properties that are added by the edit
+/// are placed after the property before them in the edited mapping. If the frontmatter can't be
+/// split into separate properties (e.g. it uses anchors), the whole block is rewritten instead,
+/// which fails if the block has comments since they would be lost. The body is always left
+/// untouched.
+pub fn edit_frontmatter_content<F>(content: &str, edit: F) -> Result<Option<String>>
+where
+    F: FnOnce(&mut Mapping) -> Result<()>,
+{
+    let (yaml, body) = split_frontmatter(content);
+    let original = match yaml {
+        Some(yaml) => parse_mapping(yaml)?,
+        None => Mapping::new(),
+    };
+
+    let mut updated = original.clone();
+    edit(&mut updated)?;
+    if updated == original {
+        return Ok(None);
+    }
+
+    let Some(yaml) = yaml else {
+        return Ok(Some(render(&updated, body)?));
+    };
+    if let Some(spliced) = splice_properties(yaml, &original, &updated)? {
+        return Ok(Some(format!(
+            "{FRONTMATTER_DELIMITER}\n{spliced}{FRONTMATTER_DELIMITER}\n{body}"
+        )));
+    }
+    anyhow::ensure!(
+        !yaml.lines().any(|line| line.trim_start().starts_with('#')),
+        "Unable to edit the frontmatter without losing its comments"
+    );
+    Ok(Some(render(&updated, body)?))
+}
+
+/// Render a frontmatter mapping and body back into the full content of a markdown file
+pub fn render(frontmatter: &Mapping, body: &str) -> Result<String> {
+    let yaml = if frontmatter.is_empty() {
+        String::new()
+    } else {
+        serde_norway::to_string(frontmatter).context("Failed to serialize frontmatter")?
+    };
+    Ok(format!(
+        "{FRONTMATTER_DELIMITER}\n{yaml}{FRONTMATTER_DELIMITER}\n{body}"
+    ))
+}
+
+/// A top level piece of a frontmatter block: either a single property (with any lines that
+/// continue its value) or lines that aren't part of a property, like comments
+struct Segment<'a> {
+    key: Option<Value>,
+    text: &'a str,
+}
+
+/// Split frontmatter YAML into its top level properties, keeping the exact text of each. Returns
+/// `None` if the text can't be split into properties that match the parsed mapping
+fn split_properties<'a>(yaml: &'a str, mapping: &Mapping) -> Option<Vec<Segment<'a>>> {
+    // Segments are byte ranges into the YAML text
+    let mut ranges: Vec<(bool, usize, usize)> = Vec::new();
+    // Blank lines only belong to a property if more of its value follows them
+    let mut blank_start = None;
+    let mut offset = 0;
+    for line in yaml.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        if line.trim().is_empty() {
+            blank_start.get_or_insert(start);
+            continue;
+        }
+        let continues = line.starts_with([' ', '\t']) || line.starts_with('-');
+        match ranges.last_mut() {
+            Some((true, _, end)) if continues => {
+                *end = offset;
+                blank_start = None;
+                continue;
+            }
+            _ if continues => return None,
+            _ => {}
+        }
+        if let Some(blank) = blank_start.take() {
+            ranges.push((false, blank, start));
+        }
+        ranges.push((!line.starts_with('#'), start, offset));
+    }
+    if let Some(blank) = blank_start {
+        ranges.push((false, blank, yaml.len()));
+    }
+
+    let mut segments = Vec::with_capacity(ranges.len());
+    let mut keys = Vec::new();
+    for (is_property, start, end) in ranges {
+        let text = &yaml[start..end];
+        let key = if is_property {
+            let property: Mapping = serde_norway::from_str(text).ok()?;
+            let (key, value) = property.into_iter().next()?;
+            if mapping.get(&key) != Some(&value) {
+                return None;
+            }
+            keys.push(key.clone());
+            Some(key)
+        } else {
+            None
+        };
+        segments.push(Segment { key, text });
+    }
+    keys.iter().eq(mapping.keys()).then_some(segments)
+}
+
+/// Rewrite only the properties that differ between the original and updated mappings, keeping
+/// the text of everything else. Returns `None` if the original YAML can't be split into properties
+/// or the edit reordered existing properties
+fn splice_properties(yaml: &str, original: &Mapping, updated: &Mapping) -> Result<Option<String>> {
+    let Some(segments) = split_properties(yaml, original) else {
+        return Ok(None);
+    };
+    let kept: Vec<&Value> = original
+        .keys()
+        .filter(|key| updated.contains_key(*key))
+        .collect();
+    if !updated
+        .keys()
+        .filter(|key| original.contains_key(*key))
+        .eq(kept.iter().copied())
+    {
+        return Ok(None);
+    }
+
+    // New properties are placed after the existing property that comes before them
+    let mut added: Vec<(Option<&Value>, &Value, &Value)> = Vec::new();
+    let mut previous = None;
+    for (key, value) in updated {
+        if original.contains_key(key) {
+            previous = Some(key);
+        } else {
+            added.push((previous, key, value));
+        }
+    }
+    let render_added = |out: &mut String, after: Option<&Value>| -> Result<()> {
+        for (_, key, value) in added.iter().filter(|(prev, _, _)| *prev == after) {
+            out.push_str(&render_property(key, value)?);
+        }
+        Ok(())
+    };
+
+    let mut out = String::with_capacity(yaml.len());
+    render_added(&mut out, None)?;
+    for segment in segments {
+        let Some(key) = &segment.key else {
+            out.push_str(segment.text);
+            continue;
+        };
+        match updated.get(key) {
+            Some(value) if original.get(key) == Some(value) => out.push_str(segment.text),
+            Some(value) => out.push_str(&render_property(key, value)?),
+            None => {}
+        }
+        render_added(&mut out, Some(key))?;
+    }
+    Ok(Some(out))
+}
+
+fn render_property(key: &Value, value: &Value) -> Result<String> {
+    let property = Mapping::from_iter([(key.clone(), value.clone())]);
+    serde_norway::to_string(&property).context("Failed to serialize frontmatter")
+}
+
+fn parse_mapping(yaml: &str) -> Result<Mapping> {
+    match serde_norway::from_str(yaml).context("Failed to parse frontmatter")? {
+        Value::Mapping(mapping) => Ok(mapping),
+        Value::Null => Ok(Mapping::new()),
+        _ => anyhow::bail!("Frontmatter is not a map of properties"),
+    }
+}
+
+/// A set of file edits that are staged in memory and then written to disk together.
+///
+/// All edits are computed (and therefore validated) before anything is written, so an invalid
+/// edit to any file means no files are touched. When committing, every new file is first written
+/// to a temporary file next to the original and only once all of those writes succeed are they
+/// renamed over the originals. Temporary files are removed if anything fails, but a failure while
+/// renaming them (which is rare) can leave some files updated and others not.
+///
+/// Files can also be moved as part of a transaction. Moves happen after all edits are written, so
+/// edits to a moved file should be staged using its original path.
+#[derive(Debug, Default)]
+pub struct Transaction {
+    staged: BTreeMap<PathBuf, String>,
+    moves: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Transaction {
+    /// Create a new, empty transaction
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stage an edit to the frontmatter of the file at the given path. If the file already has a
+    /// staged edit, the new edit is applied on top of it. Returns true if the edit changed the
+    /// file.
+    pub fn edit_frontmatter<F>(&mut self, path: impl AsRef<Path>, edit: F) -> Result<bool>
+    where
+        F: FnOnce(&mut Mapping) -> Result<()>,
+    {
+        let path = path.as_ref();
+        let content = match self.staged.get(path) {
+            Some(staged) => Cow::Borrowed(staged.as_str()),
+            None => Cow::Owned(
+                std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?,
+            ),
+        };
+        let updated = edit_frontmatter_content(&content, edit)
+            .with_context(|| format!("Failed to edit frontmatter of {}", path.display()))?;
+        Ok(self.stage(path, updated))
+    }
+
+    /// Stage the full new content of a file. This is mostly useful for edits to the body of a file
+    /// rather than its frontmatter.
+    pub fn set_content(&mut self, path: impl Into<PathBuf>, content: String) {
+        self.staged.insert(path.into(), content);
+    }
+
+    /// Get the currently staged content for a file, if any
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&str> {
+        self.staged.get(path.as_ref()).map(String::as_str)
+    }
+
+    fn stage(&mut self, path: &Path, updated: Option<String>) -> bool {
+        match updated {
+            Some(content) => {
+                self.staged.insert(path.to_path_buf(), content);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stage moving a file to a new path. Missing parent folders of the new path are created when
+    /// committing
+    pub fn move_file(&mut self, from: impl Into<PathBuf>, to: impl Into<PathBuf>) {
+        self.moves.push((from.into(), to.into()));
+    }
+
+    /// Get an iterator over all staged moves
+    pub fn moves(&self) -> impl Iterator<Item = (&PathBuf, &PathBuf)> {
+        self.moves.iter().map(|(from, to)| (from, to))
+    }
+
+    /// Returns true if there are no staged changes
+    pub fn is_empty(&self) -> bool {
+        self.staged.is_empty() && self.moves.is_empty()
+    }
+
+    /// Get an iterator over all staged files and their new content. This can be used to preview
+    /// changes (i.e. a dry run) without committing them
+    pub fn iter(&self) -> impl Iterator<Item = (&PathBuf, &str)> {
+        self.staged
+            .iter()
+            .map(|(path, content)| (path, content.as_str()))
+    }
+
+    /// Render a unified diff of each staged file against its current content on disk. This is
+    /// useful for showing what a dry run would change
+    pub fn diffs(&self) -> Result<Vec<(PathBuf, String)>> {
+        self.iter()
+            .map(|(path, staged)| {
+                let current = match std::fs::read_to_string(path) {
+                    Ok(current) => current,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                    Err(e) => {
+                        return Err(e)
+                            .with_context(|| format!("Failed to read {}", path.display()));
+                    }
+                };
+                let name = path.display().to_string();
+                let diff = similar::TextDiff::from_lines(current.as_str(), staged)
+                    .unified_diff()
+                    .header(&name, &name)
+                    .to_string();
+                Ok((path.clone(), diff))
+            })
+            .collect()
+    }
+
+    /// Write all staged changes to disk and then perform any staged moves, returning the list of
+    /// files that were written. Nothing is written if the target of a move already exists or its
+    /// folder can't be created. If a move still fails, the edits (and any earlier moves) have
+    /// already been applied
+    pub fn commit(self) -> Result<Vec<PathBuf>> {
+        // Check every move up front so edits (like links to a moved file) are never written for a
+        // move that can't happen
+        for (from, to) in &self.moves {
+            anyhow::ensure!(
+                !to.exists(),
+                "Unable to move {} as {} already exists",
+                from.display(),
+                to.display()
+            );
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create folder {}", parent.display()))?;
+            }
+        }
+
+        let mut written = Vec::with_capacity(self.staged.len());
+        for (path, content) in &self.staged {
+            let tmp = temp_path(path);
+            if let Err(e) = std::fs::write(&tmp, content) {
+                // Clean up anything we've already staged on disk before bailing
+                for (_, tmp) in written {
+                    let _ = std::fs::remove_file(tmp);
+                }
+                let _ = std::fs::remove_file(&tmp);
+                return Err(e).with_context(|| format!("Failed to write {}", tmp.display()));
+            }
+            written.push((path.clone(), tmp));
+        }
+
+        let mut committed = Vec::with_capacity(written.len());
+        let mut written = written.into_iter();
+        while let Some((path, tmp)) = written.next() {
+            if let Err(e) = std::fs::rename(&tmp, &path) {
+                // Don't leave the temporary files for this or any of the remaining files behind
+                let _ = std::fs::remove_file(&tmp);
+                for (_, tmp) in written {
+                    let _ = std::fs::remove_file(tmp);
+                }
+                return Err(e).with_context(|| format!("Failed to replace {}", path.display()));
+            }
+            committed.push(path);
+        }
+
+        for (from, to) in self.moves {
+            anyhow::ensure!(
+                !to.exists(),
+                "Unable to move {} as {} already exists",
+                from.display(),
+                to.display()
+            );
+            std::fs::rename(&from, &to).with_context(|| {
+                format!("Failed to move {} to {}", from.display(), to.display())
+            })?;
+        }
+        Ok(committed)
+    }
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{file_name}.obsidian-utils.tmp"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_frontmatter_separates_yaml_and_body() {
+        let content = "---\ntags: [a]\n---\n# Title\n";
+        let (yaml, body) = split_frontmatter(content);
+        assert_eq!(yaml, Some("tags: [a]\n"));
+        assert_eq!(body, "# Title\n");
+
+        let (yaml, body) = split_frontmatter("# No frontmatter\n---\n");
+        assert!(yaml.is_none());
+        assert_eq!(body, "# No frontmatter\n---\n");
+    }
+
+    #[test]
+    fn edit_frontmatter_content_preserves_body() -> Result<()> {
+        let content = "---\ntags:\n- a\n---\nBody text\n";
+        let updated = edit_frontmatter_content(content, |fm| {
+            fm.insert("rating".into(), 5.into());
+            Ok(())
+        })?
+        .expect("expected content to change");
+
+        assert!(updated.starts_with("---\n"));
+        assert!(updated.ends_with("---\nBody text\n"));
+        let (yaml, _) = split_frontmatter(&updated);
+        let mapping = parse_mapping(yaml.unwrap())?;
+        assert_eq!(mapping.get("rating"), Some(&Value::from(5)));
+        assert!(mapping.contains_key("tags"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn edit_frontmatter_content_keeps_untouched_properties() -> Result<()> {
+        let content = "---\n# project metadata\ntitle: \"My: note\"\nrating: 005 # stars\n\ntags:\n  - a\nold: 1\n---\nBody\n";
+        let updated = edit_frontmatter_content(content, |fm| {
+            fm.insert("tags".into(), Value::Sequence(vec!["a".into(), "b".into()]));
+            fm.remove("old");
+            fm.insert("url".into(), "https://example.com".into());
+            Ok(())
+        })?
+        .expect("expected content to change");
+        assert_eq!(
+            updated,
+            "---\n# project metadata\ntitle: \"My: note\"\nrating: 005 # stars\n\ntags:\n- a\n- b\nurl: https://example.com\n---\nBody\n"
+        );
+
+        // Anchors can't be split into separate properties, so comments can't be kept
+        let result = edit_frontmatter_content("---\n# comment\na: &x 1\nb: *x\n---\n", |fm| {
+            fm.insert("c".into(), 3.into());
+            Ok(())
+        });
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn edit_frontmatter_content_adds_block_when_missing() -> Result<()> {
+        let updated = edit_frontmatter_content("Just a body\n", |fm| {
+            fm.insert("url".into(), "https://example.com".into());
+            Ok(())
+        })?
+        .expect("expected content to change");
+
+        assert_eq!(updated, "---\nurl: https://example.com\n---\nJust a body\n");
+        Ok(())
+    }
+
+    #[test]
+    fn edit_frontmatter_content_noop_returns_none() -> Result<()> {
+        let result = edit_frontmatter_content("---\na: 1\n---\n", |fm| {
+            fm.insert("a".into(), 1.into());
+            Ok(())
+        })?;
+        assert!(result.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn edit_frontmatter_content_rejects_non_map_frontmatter() {
+        let result = edit_frontmatter_content("---\n- a\n- b\n---\n", |_| Ok(()));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn transaction_commit_writes_all_files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let first = dir.path().join("first.md");
+        let second = dir.path().join("second.md");
+        std::fs::write(&first, "---\na: 1\n---\nfirst\n")?;
+        std::fs::write(&second, "second\n")?;
+
+        let mut tx = Transaction::new();
+        assert!(tx.edit_frontmatter(&first, |fm| {
+            fm.insert("b".into(), 2.into());
+            Ok(())
+        })?);
+        assert!(tx.edit_frontmatter(&second, |fm| {
+            fm.insert("c".into(), 3.into());
+            Ok(())
+        })?);
+        // Unchanged files are not written
+        assert!(!tx.edit_frontmatter(&first, |_| Ok(()))?);
+
+        let written = tx.commit()?;
+        assert_eq!(written.len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(&first)?,
+            "---\na: 1\nb: 2\n---\nfirst\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&second)?,
+            "---\nc: 3\n---\nsecond\n"
+        );
+        assert_eq!(
+            std::fs::read_dir(dir.path())?.count(),
+            2,
+            "temporary files should not be left behind"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn transaction_cleans_up_after_failed_rename() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let first = dir.path().join("a.md");
+        let folder = dir.path().join("b");
+        let last = dir.path().join("c.md");
+        std::fs::write(&first, "a\n")?;
+        std::fs::create_dir(&folder)?;
+        std::fs::write(&last, "c\n")?;
+
+        // Files are replaced in order, and a file can't be renamed over a folder
+        let mut tx = Transaction::new();
+        tx.set_content(&first, "new a\n".to_string());
+        tx.set_content(&folder, "new b\n".to_string());
+        tx.set_content(&last, "new c\n".to_string());
+        assert!(tx.commit().is_err());
+
+        assert_eq!(std::fs::read_to_string(&last)?, "c\n");
+        assert_eq!(
+            std::fs::read_dir(dir.path())?.count(),
+            3,
+            "temporary files should not be left behind"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn transaction_moves_files_after_edits() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let from = dir.path().join("from.md");
+        let to = dir.path().join("nested/to.md");
+        std::fs::write(&from, "old\n")?;
+
+        let mut tx = Transaction::new();
+        tx.set_content(&from, "new\n".to_string());
+        tx.move_file(&from, &to);
+        let diffs = tx.diffs()?;
+        assert!(diffs[0].1.contains("-old\n+new\n"), "{}", diffs[0].1);
+
+        tx.commit()?;
+        assert!(!from.exists());
+        assert_eq!(std::fs::read_to_string(&to)?, "new\n");
+        Ok(())
+    }
+
+    #[test]
+    fn transaction_checks_moves_before_writing() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let from = dir.path().join("from.md");
+        let backlink = dir.path().join("backlink.md");
+        let taken = dir.path().join("taken.md");
+        std::fs::write(&from, "note\n")?;
+        std::fs::write(&backlink, "[note](from.md)\n")?;
+
+        let mut tx = Transaction::new();
+        tx.set_content(&backlink, "[note](taken.md)\n".to_string());
+        tx.move_file(&from, &taken);
+        // The target shows up after the move was staged
+        std::fs::write(&taken, "other\n")?;
+        assert!(tx.commit().is_err());
+        assert_eq!(std::fs::read_to_string(&backlink)?, "[note](from.md)\n");
+
+        // A file where the target's folder should be means the folder can't be created
+        let mut tx = Transaction::new();
+        tx.set_content(&backlink, "[note](taken.md/from.md)\n".to_string());
+        tx.move_file(&from, taken.join("from.md"));
+        assert!(tx.commit().is_err());
+        assert_eq!(std::fs::read_to_string(&backlink)?, "[note](from.md)\n");
+        assert!(from.exists());
+        assert_eq!(std::fs::read_dir(dir.path())?.count(), 3);
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use anyhow::{Context, Result};
+use comrak::Arena;
+use serde::{Deserialize, Serialize};
+
+use obsidian_core::{frontmatter, parser, reader::FileEntry, stats};
+use obsidian_links::parser::LinkStyle;
+
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod site;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+/// Everything exported about a single note. Paths are relative to the vault root, except for
+/// links that point outside of the vault, which are absolute
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoteRecord {
+    /// The path of the note
+    pub path: PathBuf,
+    /// When the note was last modified, in milliseconds since the Unix epoch
+    pub modified: i64,
+    /// The size of the note on disk, in bytes
+    pub size: u64,
+    /// The number of words in the note
+    pub words: usize,
+    /// The tags in the note's frontmatter
+    pub tags: Vec<String>,
+    /// The files the note links to. External links are not included
+    pub links: Vec<PathBuf>,
+    /// Every frontmatter property (including tags) as JSON
+    pub properties: BTreeMap<String, serde_json::Value>,
+}
+
+/// A summary of what an export wrote
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExportReport {
+    /// The number of notes that were exported. For incremental exports, this only counts notes
+    /// that were new or modified since the last export
+    pub exported: usize,
+    /// The number of notes that were skipped because they haven't been modified. Always zero for
+    /// full exports
+    pub unchanged: usize,
+    /// The number of notes removed from the export because they no longer exist. Always zero for
+    /// full exports
+    pub removed: usize,
+}
+
+/// The modification time of a file in milliseconds since the Unix epoch. This is what
+/// [`NoteRecord::modified`] is set to and is used to skip unchanged notes when updating an
+/// existing export
+pub fn modified_millis(entry: &FileEntry) -> i64 {
+    entry
+        .metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|since| since.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// Parse the markdown files in the given entries into records. Links are resolved relative to the
+/// given vault root using the given link style, the same way `obsidian-links` does. Files that fail
+/// to parse are skipped. Tags are also read from any of the given tag keys
+pub fn note_records(
+    entries: Vec<FileEntry>,
+    vault_root: &Path,
+    link_style: LinkStyle,
+    tag_keys: &[String],
+) -> Result<Vec<NoteRecord>> {
+    let vault = vault_root
+        .canonicalize()
+        .with_context(|| format!("Unable to open vault {}", vault_root.display()))?;
+    let mut modified: BTreeMap<PathBuf, i64> = BTreeMap::new();
+    let notes: Vec<_> = entries
+        .into_iter()
+        .filter(|e| parser::is_markdown(&e.path))
+        .inspect(|e| {
+            modified.insert(e.path.clone(), modified_millis(e));
+        })
+        .collect();
+
+    let arena = Arena::with_capacity(notes.len());
+    let parsed_files = parser::ignore_error_iter(parser::parse_files(&arena, notes));
+    obsidian_links::parser::parse_links(parsed_files, &vault, link_style)
+        .map(|(pf, targets)| {
+            let fm = frontmatter::parse_frontmatter_from_ast(pf.ast)
+                .map(|fm| fm.with_tag_keys(tag_keys));
+            let properties = fm
+                .iter()
+                .flat_map(|fm| fm.keys().filter_map(|key| Some((key, fm.get(key)?))))
+                .map(|(key, value)| {
+                    let value = serde_json::to_value(&value).unwrap_or_else(|e| {
+                        log::warn!("Unable to convert property {key} to JSON: {e}");
+                        serde_json::Value::Null
+                    });
+                    (key.to_string(), value)
+                })
+                .collect();
+            let links = targets
+                .iter()
+                .map(|target| {
+                    obsidian_links::resolve_link_path(target).map(|path| relative_to(&path, &vault))
+                })
+                .collect::<Result<_>>()?;
+            Ok(NoteRecord {
+                path: relative_to(&pf.path.canonicalize()?, &vault),
+                modified: modified.get(&pf.path).copied().unwrap_or_default(),
+                size: pf.metadata.len(),
+                words: stats::stats_from_ast(pf.ast).words,
+                tags: fm.and_then(|fm| fm.tags).unwrap_or_default(),
+                links,
+                properties,
+            })
+        })
+        .collect()
+}
+
+/// Get the path of a file relative to the vault, or the original path if it isn't in the vault.
+/// The path should already be canonicalized
+pub fn relative_to(path: &Path, vault: &Path) -> PathBuf {
+    path.strip_prefix(vault).unwrap_or(path).to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_records_for_notes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("Projects"))?;
+        std::fs::write(
+            dir.path().join("Projects/Homelab.md"),
+            "---\ntags: [infra]\nstatus: active\npriority: 2\n---\nSee [idea](../Idea.md) and https://example.com\n",
+        )?;
+        std::fs::write(dir.path().join("Idea.md"), "Just words here\n")?;
+        std::fs::write(dir.path().join("image.png"), "")?;
+
+        let entries = obsidian_core::reader::read_dir(dir.path(), true)?;
+        let records = note_records(entries, dir.path(), LinkStyle::Infer, &[])?;
+        assert_eq!(records.len(), 2, "only markdown files should be exported");
+
+        let homelab = records
+            .iter()
+            .find(|r| r.path == Path::new("Projects/Homelab.md"))
+            .expect("Homelab should be exported");
+        assert_eq!(homelab.tags, vec!["infra"]);
+        assert_eq!(homelab.links, vec![PathBuf::from("Idea.md")]);
+        assert_eq!(homelab.properties["status"], serde_json::json!("active"));
+        assert_eq!(homelab.properties["priority"], serde_json::json!(2));
+        assert_eq!(homelab.properties["tags"], serde_json::json!(["infra"]));
+        assert!(homelab.modified > 0);
+
+        let entries = obsidian_core::reader::read_dir(dir.path().join("Projects"), true)?;
+        let records = note_records(entries, dir.path(), LinkStyle::Infer, &["status".into()])?;
+        assert_eq!(records[0].tags, vec!["infra", "active"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+//! Export note metadata to Parquet files for analysis with tools like pandas, polars, or DuckDB.
+//!
+//! An export is a directory with two files:
+//!
+//! `notes.parquet` has one row per note:
+//!
+//! | Column     | Type                     | Description                                   |
+//! |------------|--------------------------|-----------------------------------------------|
+//! | `path`     | string                   | The path of the note relative to the vault    |
+//! | `modified` | timestamp (ms, UTC)      | When the note was last modified               |
+//! | `size`     | uint64                   | The size of the note on disk, in bytes        |
+//! | `words`    | uint64                   | The number of words in the note               |
+//! | `tags`     | list of string           | The tags in the note's frontmatter            |
+//! | `links`    | list of string           | The files the note links to                   |
+//!
+//! `properties.parquet` has one row per frontmatter property of each note, so properties with
+//! different types in different notes can still be queried:
+//!
+//! | Column    | Type              | Description                                               |
+//! |-----------|-------------------|-----------------------------------------------------------|
+//! | `path`    | string            | The path of the note relative to the vault                |
+//! | `key`     | string            | The name of the property                                  |
+//! | `value`   | string            | The value of the property as JSON                         |
+//! | `text`    | string (nullable) | The value if it is a string                               |
+//! | `number`  | float64 (nullable)| The value if it is a number                               |
+//! | `boolean` | bool (nullable)   | The value if it is a boolean                              |
+//!
+//! Link targets and paths are both relative to the vault root, so links can be joined against
+//! `notes.path`.
+
+use std::{path::Path, sync::Arc};
+
+use anyhow::{Context, Result};
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, RecordBatch, StringArray, TimestampMillisecondArray,
+    UInt64Array,
+    builder::{ListBuilder, StringBuilder},
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use parquet::arrow::ArrowWriter;
+
+use crate::{ExportReport, NoteRecord};
+
+/// The name of the file with one row per note
+pub const NOTES_FILE: &str = "notes.parquet";
+/// The name of the file with one row per note property
+pub const PROPERTIES_FILE: &str = "properties.parquet";
+
+/// The schema of [`NOTES_FILE`]. See the module docs for details
+pub fn notes_schema() -> SchemaRef {
+    let string_list = DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)));
+    Arc::new(Schema::new(vec![
+        Field::new("path", DataType::Utf8, false),
+        Field::new(
+            "modified",
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            false,
+        ),
+        Field::new("size", DataType::UInt64, false),
+        Field::new("words", DataType::UInt64, false),
+        Field::new("tags", string_list.clone(), false),
+        Field::new("links", string_list, false),
+    ]))
+}
+
+/// The schema of [`PROPERTIES_FILE`]. See the module docs for details
+pub fn properties_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("path", DataType::Utf8, false),
+        Field::new("key", DataType::Utf8, false),
+        Field::new("value", DataType::Utf8, false),
+        Field::new("text", DataType::Utf8, true),
+        Field::new("number", DataType::Float64, true),
+        Field::new("boolean", DataType::Boolean, true),
+    ]))
+}
+
+/// Convert records into a batch matching [`notes_schema`]
+pub fn notes_batch(records: &[NoteRecord]) -> Result<RecordBatch> {
+    let mut tags = ListBuilder::new(StringBuilder::new());
+    let mut links = ListBuilder::new(StringBuilder::new());
+    for record in records {
+        tags.append_value(record.tags.iter().map(Some));
+        links.append_value(record.links.iter().map(|link| Some(link.to_string_lossy())));
+    }
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            records.iter().map(|r| r.path.to_string_lossy()),
+        )),
+        Arc::new(
+            TimestampMillisecondArray::from_iter_values(records.iter().map(|r| r.modified))
+                .with_timezone("UTC"),
+        ),
+        Arc::new(UInt64Array::from_iter_values(
+            records.iter().map(|r| r.size),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            records.iter().map(|r| r.words as u64),
+        )),
+        Arc::new(tags.finish()),
+        Arc::new(links.finish()),
+    ];
+    RecordBatch::try_new(notes_schema(), columns).context("Failed to build notes batch")
+}
+
+/// Convert the properties of the records into a batch matching [`properties_schema`]
+pub fn properties_batch(records: &[NoteRecord]) -> Result<RecordBatch> {
+    let rows: Vec<_> = records
+        .iter()
+        .flat_map(|r| {
+            r.properties
+                .iter()
+                .map(move |(key, value)| (r.path.to_string_lossy(), key, value))
+        })
+        .collect();
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|(path, _, _)| path),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|(_, key, _)| key),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|(_, _, value)| value.to_string()),
+        )),
+        Arc::new(StringArray::from_iter(
+            rows.iter().map(|(_, _, value)| value.as_str()),
+        )),
+        Arc::new(Float64Array::from_iter(
+            rows.iter().map(|(_, _, value)| value.as_f64()),
+        )),
+        Arc::new(BooleanArray::from_iter(
+            rows.iter().map(|(_, _, value)| value.as_bool()),
+        )),
+    ];
+    RecordBatch::try_new(properties_schema(), columns).context("Failed to build properties batch")
+}
+
+/// Write the records to [`NOTES_FILE`] and [`PROPERTIES_FILE`] in the given directory, creating it
+/// if needed. Existing files are overwritten, as Parquet files can't be updated in place
+pub fn export(dir: impl AsRef<Path>, records: &[NoteRecord]) -> Result<ExportReport> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create export directory {}", dir.display()))?;
+    write_batch(&dir.join(NOTES_FILE), notes_batch(records)?)?;
+    write_batch(&dir.join(PROPERTIES_FILE), properties_batch(records)?)?;
+    Ok(ExportReport {
+        exported: records.len(),
+        ..Default::default()
+    })
+}
+
+fn write_batch(path: &Path, batch: RecordBatch) -> Result<()> {
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), None)?;
+    writer
+        .write(&batch)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    use arrow_array::{Array, ListArray, cast::AsArray, types::Float64Type};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use serde_json::Value;
+
+    use super::*;
+
+    fn read(path: &Path) -> Result<RecordBatch> {
+        let reader =
+            ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(path)?)?.build()?;
+        let mut batches = reader.collect::<std::result::Result<Vec<_>, _>>()?;
+        assert_eq!(batches.len(), 1, "small exports should be a single batch");
+        Ok(batches.remove(0))
+    }
+
+    #[test]
+    fn round_trips_through_parquet() -> Result<()> {
+        let records = vec![
+            NoteRecord {
+                path: PathBuf::from("Projects/Homelab.md"),
+                modified: 1_736_899_200_000,
+                size: 120,
+                words: 20,
+                tags: vec!["infra".into()],
+                links: vec![PathBuf::from("Idea.md")],
+                properties: BTreeMap::from([
+                    ("status".to_string(), serde_json::json!("active")),
+                    ("priority".to_string(), serde_json::json!(2)),
+                    ("draft".to_string(), serde_json::json!(false)),
+                    ("tags".to_string(), serde_json::json!(["infra"])),
+                ]),
+            },
+            NoteRecord {
+                path: PathBuf::from("Idea.md"),
+                ..Default::default()
+            },
+        ];
+        let dir = tempfile::tempdir()?;
+        let report = export(dir.path(), &records)?;
+        assert_eq!(report.exported, 2);
+
+        let notes = read(&dir.path().join(NOTES_FILE))?;
+        assert_eq!(notes.schema(), notes_schema());
+        assert_eq!(notes.num_rows(), 2);
+        let tags = notes
+            .column_by_name("tags")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<ListArray>()
+            .unwrap();
+        assert_eq!(tags.value(0).as_string::<i32>().value(0), "infra");
+        assert!(tags.value(1).is_empty());
+
+        let properties = read(&dir.path().join(PROPERTIES_FILE))?;
+        assert_eq!(properties.num_rows(), 4);
+        let keys = properties.column_by_name("key").unwrap().as_string::<i32>();
+        let numbers = properties
+            .column_by_name("number")
+            .unwrap()
+            .as_primitive::<Float64Type>();
+        let texts = properties
+            .column_by_name("text")
+            .unwrap()
+            .as_string::<i32>();
+        let values = properties
+            .column_by_name("value")
+            .unwrap()
+            .as_string::<i32>();
+        for row in 0..properties.num_rows() {
+            let value: Value = serde_json::from_str(values.value(row))?;
+            assert_eq!(records[0].properties[keys.value(row)], value);
+            match keys.value(row) {
+                "priority" => assert_eq!(numbers.value(row), 2.0),
+                "status" => assert_eq!(texts.value(row), "active"),
+                _ => assert!(numbers.is_null(row) && texts.is_null(row)),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,324 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use obsidian_core::{
+    parser::{self, ParseReport},
+    reader::FileEntry,
+};
+use obsidian_links::parser::LinkStyle;
+
+use crate::{ExportReport, relative_to};
+
+/// The name of the generated page listing every note. It is not generated if the vault has its own
+/// `index.md` at the root
+pub const INDEX_PAGE: &str = "index.html";
+
+/// Export the notes in the given entries to a static HTML site in `out_dir`. Each note becomes an
+/// HTML page at the same path (with an `.html` extension) and every other file (like images) is
+/// copied as is, so the site can be served from any directory or opened straight from disk.
+///
+/// Links between notes are rewritten to relative URLs of the generated pages. Links are resolved
+/// using the given link style like everywhere else, and links that don't resolve to a file (like
+/// most wikilinks) fall back to the note with that name anywhere in the vault, the same way
+/// Obsidian finds them. Links that still can't be resolved are left alone. Hidden files and
+/// folders (like `.obsidian`) are skipped, as are notes that can't be read (like binary files with
+/// a `.md` extension), which are recorded in the given report instead
+pub fn export_site(
+    entries: Vec<FileEntry>,
+    vault_root: &Path,
+    link_style: LinkStyle,
+    out_dir: &Path,
+    failures: &mut ParseReport,
+) -> Result<ExportReport> {
+    let vault = vault_root
+        .canonicalize()
+        .with_context(|| format!("Unable to open vault {}", vault_root.display()))?;
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create folder {}", out_dir.display()))?;
+    let mut files = BTreeMap::new();
+    for entry in entries {
+        let path = entry.path.canonicalize()?;
+        let relative = relative_to(&path, &vault);
+        if relative.is_absolute() || is_hidden(&relative) {
+            continue;
+        }
+        files.insert(path, relative);
+    }
+    let site = Site::new(&vault, link_style, &files);
+
+    let mut report = ExportReport::default();
+    let mut skipped = HashSet::new();
+    for (path, relative) in &files {
+        let out = out_dir.join(site.output_path(relative));
+        if let Some(parent) = out.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create folder {}", parent.display()))?;
+        }
+        if !parser::is_markdown(path) {
+            std::fs::copy(path, &out)
+                .with_context(|| format!("Failed to copy {}", path.display()))?;
+            continue;
+        }
+        let content = match parser::read_file(path) {
+            Ok(content) => content,
+            Err(e) => {
+                log::error!("Skipping page that failed to load: {e}");
+                failures.record(&e);
+                skipped.insert(relative);
+                continue;
+            }
+        };
+        let body = site.rewrite_urls(path, relative, &parser::render_html(&content));
+        std::fs::write(&out, page(&title(relative), &body))
+            .with_context(|| format!("Failed to write {}", out.display()))?;
+        report.exported += 1;
+    }
+
+    let pages = files
+        .values()
+        .filter(|relative| parser::is_markdown(relative) && !skipped.contains(relative));
+    if !pages
+        .clone()
+        .any(|relative| relative == Path::new("index.md"))
+    {
+        let items: String = pages
+            .map(|relative| {
+                format!(
+                    "<li><a href=\"{}\">{}</a></li>\n",
+                    url(&site.output_path(relative)),
+                    escape(&relative.with_extension("").to_string_lossy())
+                )
+            })
+            .collect();
+        let index = out_dir.join(INDEX_PAGE);
+        std::fs::write(&index, page("Index", &format!("<ul>\n{items}</ul>\n")))
+            .with_context(|| format!("Failed to write {}", index.display()))?;
+    }
+    Ok(report)
+}
+
+/// Everything needed to resolve links while rendering pages
+struct Site<'a> {
+    vault: &'a Path,
+    link_style: LinkStyle,
+    files: &'a BTreeMap<PathBuf, PathBuf>,
+    /// Notes by their lowercase file name, for resolving links by name. `None` if the name is
+    /// ambiguous
+    by_name: HashMap<String, Option<&'a Path>>,
+}
+
+impl<'a> Site<'a> {
+    fn new(vault: &'a Path, link_style: LinkStyle, files: &'a BTreeMap<PathBuf, PathBuf>) -> Self {
+        let mut by_name = HashMap::new();
+        for relative in files.values() {
+            let Some(name) = relative.file_name() else {
+                continue;
+            };
+            by_name
+                .entry(name.to_string_lossy().to_lowercase())
+                .and_modify(|existing| *existing = None)
+                .or_insert(Some(relative.as_path()));
+        }
+        Self {
+            vault,
+            link_style,
+            files,
+            by_name,
+        }
+    }
+
+    /// The path of the exported file, relative to the output directory
+    fn output_path(&self, relative: &Path) -> PathBuf {
+        if parser::is_markdown(relative) {
+            relative.with_extension("html")
+        } else {
+            relative.to_path_buf()
+        }
+    }
+
+    /// Find the file a link points to, returning its path relative to the vault
+    fn resolve(&self, path: &Path, raw: &str) -> Option<&'a Path> {
+        let linked = self.link_style.resolve(raw, path, &self.vault)?;
+        let mut with_extension = linked.as_os_str().to_owned();
+        with_extension.push(".md");
+        [linked.clone(), PathBuf::from(with_extension)]
+            .iter()
+            .filter_map(|candidate| candidate.canonicalize().ok())
+            .find_map(|candidate| self.files.get(&candidate).map(PathBuf::as_path))
+            .or_else(|| {
+                let name = linked.file_name()?.to_string_lossy().to_lowercase();
+                self.by_name
+                    .get(&name)
+                    .or_else(|| self.by_name.get(&format!("{name}.md")))
+                    .copied()
+                    .flatten()
+            })
+    }
+
+    /// Rewrite the `href` and `src` attributes in the rendered HTML of a note that point at other
+    /// files in the vault
+    fn rewrite_urls(&self, path: &Path, relative: &Path, html: &str) -> String {
+        let page_dir = relative.parent().unwrap_or(Path::new(""));
+        let mut out = String::with_capacity(html.len());
+        let mut rest = html;
+        while let Some((start, attr)) = ["href=\"", "src=\""]
+            .iter()
+            .filter_map(|attr| rest.find(attr).map(|idx| (idx, attr)))
+            .min()
+        {
+            let value_start = start + attr.len();
+            let Some(len) = rest[value_start..].find('"') else {
+                break;
+            };
+            out.push_str(&rest[..value_start]);
+            let raw = unescape(&rest[value_start..value_start + len]);
+            match self.resolve(path, &raw) {
+                Some(target) => {
+                    let mut new_url = url(&relative_path(page_dir, &self.output_path(target)));
+                    if let Some((_, fragment)) = raw.split_once('#') {
+                        let fragment = urlencoding::decode(fragment)
+                            .map(|f| f.into_owned())
+                            .unwrap_or_else(|_| fragment.to_string());
+                        new_url.push('#');
+                        new_url.push_str(&urlencoding::encode(&fragment));
+                    }
+                    out.push_str(&new_url);
+                }
+                None => {
+                    log::debug!("Leaving unresolved link {raw} in {}", path.display());
+                    out.push_str(&rest[value_start..value_start + len]);
+                }
+            }
+            rest = &rest[value_start + len..];
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n<main>\n{body}</main>\n</body>\n</html>\n",
+        escape(title)
+    )
+}
+
+fn title(relative: &Path) -> String {
+    relative
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Turn a relative path into a percent encoded URL
+fn url(path: &Path) -> String {
+    path.components()
+        .map(|c| match c {
+            Component::ParentDir => "..".to_string(),
+            c => urlencoding::encode(&c.as_os_str().to_string_lossy()).into_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Get the path of `target` relative to the `base` directory. Both are relative to the vault root
+fn relative_path(base: &Path, target: &Path) -> PathBuf {
+    let base: Vec<_> = base.components().collect();
+    let target: Vec<_> = target.components().collect();
+    let common = base.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    std::iter::repeat_n(Component::ParentDir, base.len() - common)
+        .chain(target[common..].iter().copied())
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#x27;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.components().any(|c| match c {
+        Component::Normal(name) => name.to_str().is_some_and(|n| n.starts_with('.')),
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_linked_pages() -> Result<()> {
+        let vault = tempfile::tempdir()?;
+        let out = tempfile::tempdir()?;
+        std::fs::create_dir_all(vault.path().join("Projects/Homelab"))?;
+        std::fs::create_dir_all(vault.path().join(".obsidian"))?;
+        std::fs::write(
+            vault.path().join("Daily.md"),
+            "---\ntags: [daily]\n---\nWorked on [[Homelab Plan|the plan]], [setup](Projects/Homelab/Homelab%20Plan.md#First%20Setup), and [[Missing]]. ![cat](cat.png)\n",
+        )?;
+        std::fs::write(
+            vault.path().join("Projects/Homelab/Homelab Plan.md"),
+            "Back to [daily](../../Daily.md) & <https://example.com>\n",
+        )?;
+        std::fs::write(vault.path().join("cat.png"), "meow")?;
+        std::fs::write(vault.path().join(".obsidian/app.json"), "{}")?;
+        std::fs::write(vault.path().join("Broken.md"), [0xff, 0xfe, 0x00])?;
+
+        let entries = obsidian_core::reader::read_dir(vault.path(), true)?;
+        let mut failures = ParseReport::default();
+        let report = export_site(
+            entries,
+            vault.path(),
+            LinkStyle::Infer,
+            out.path(),
+            &mut failures,
+        )?;
+        assert_eq!(report.exported, 2);
+        assert_eq!(failures.failures.len(), 1);
+        assert_eq!(
+            failures.failures[0].category,
+            parser::ErrorCategory::InvalidUtf8
+        );
+        assert!(!out.path().join("Broken.html").exists());
+
+        let daily = std::fs::read_to_string(out.path().join("Daily.html"))?;
+        assert!(!daily.contains("tags:"), "frontmatter should be skipped");
+        assert!(
+            daily.contains("href=\"Projects/Homelab/Homelab%20Plan.html\""),
+            "{daily}"
+        );
+        assert!(
+            daily.contains("href=\"Projects/Homelab/Homelab%20Plan.html#First%20Setup\""),
+            "{daily}"
+        );
+        assert!(daily.contains("href=\"Missing\""), "{daily}");
+        assert!(daily.contains("src=\"cat.png\""), "{daily}");
+
+        let plan = std::fs::read_to_string(out.path().join("Projects/Homelab/Homelab Plan.html"))?;
+        assert!(plan.contains("href=\"../../Daily.html\""), "{plan}");
+        assert!(plan.contains("href=\"https://example.com\""), "{plan}");
+        assert!(plan.contains("<title>Homelab Plan</title>"));
+
+        assert_eq!(std::fs::read(out.path().join("cat.png"))?, b"meow");
+        assert!(!out.path().join(".obsidian").exists());
+        let index = std::fs::read_to_string(out.path().join(INDEX_PAGE))?;
+        assert!(index.contains("href=\"Daily.html\""), "{index}");
+        assert!(!index.contains("Broken"), "{index}");
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, params};
+
+use obsidian_core::{parser, reader::FileEntry};
+use obsidian_links::parser::LinkStyle;
+
+use crate::{ExportReport, NoteRecord, modified_millis, note_records, relative_to};
+
+/// The version of the schema below, stored in the database's `user_version`. Databases with a
+/// different version are rejected rather than silently mixing schemas
+pub const SCHEMA_VERSION: i32 = 1;
+
+/// The tables notes are exported to. Property values are stored as JSON so they can be queried with
+/// SQLite's JSON functions (e.g. `json_extract(value, '$[0]')`). Link targets and note paths are
+/// both relative to the vault root, so links can be joined against `notes.path`
+pub const SCHEMA: &str = "
+CREATE TABLE notes (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL UNIQUE,
+    modified INTEGER NOT NULL,
+    size INTEGER NOT NULL,
+    words INTEGER NOT NULL
+);
+CREATE TABLE tags (
+    note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (note_id, tag)
+);
+CREATE INDEX tags_by_tag ON tags(tag);
+CREATE TABLE links (
+    note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    target TEXT NOT NULL,
+    PRIMARY KEY (note_id, target)
+);
+CREATE INDEX links_by_target ON links(target);
+CREATE TABLE properties (
+    note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (note_id, key)
+);
+CREATE INDEX properties_by_key ON properties(key);
+";
+
+/// Export the markdown files in the given entries to the SQLite database at the given path,
+/// creating it if needed. See [`export_to`] for details
+pub fn export(
+    db_path: impl AsRef<Path>,
+    entries: Vec<FileEntry>,
+    vault_root: &Path,
+    link_style: LinkStyle,
+    tag_keys: &[String],
+) -> Result<ExportReport> {
+    let db_path = db_path.as_ref();
+    let mut conn = Connection::open(db_path)
+        .with_context(|| format!("Unable to open database {}", db_path.display()))?;
+    export_to(&mut conn, entries, vault_root, link_style, tag_keys)
+}
+
+/// Export the markdown files in the given entries to an open SQLite database. The export is
+/// incremental: notes whose modification time matches the one already in the database are not
+/// parsed again, and notes in the database that aren't in the entries are removed. Everything is
+/// written in a single transaction, so a failed export leaves the database as it was. Tags are also
+/// read from any of the given tag keys
+pub fn export_to(
+    conn: &mut Connection,
+    entries: Vec<FileEntry>,
+    vault_root: &Path,
+    link_style: LinkStyle,
+    tag_keys: &[String],
+) -> Result<ExportReport> {
+    init_schema(conn)?;
+    let vault = vault_root
+        .canonicalize()
+        .with_context(|| format!("Unable to open vault {}", vault_root.display()))?;
+
+    let existing: HashMap<String, i64> = conn
+        .prepare("SELECT path, modified FROM notes")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut report = ExportReport::default();
+    let mut current = HashSet::new();
+    let mut stale = Vec::new();
+    for entry in entries.into_iter().filter(|e| parser::is_markdown(&e.path)) {
+        let path = relative_to(&entry.path.canonicalize()?, &vault)
+            .to_string_lossy()
+            .into_owned();
+        if existing.get(&path) == Some(&modified_millis(&entry)) {
+            report.unchanged += 1;
+        } else {
+            stale.push(entry);
+        }
+        current.insert(path);
+    }
+    let records = note_records(stale, &vault, link_style, tag_keys)?;
+
+    let tx = conn.transaction()?;
+    for record in &records {
+        insert_note(&tx, record)?;
+    }
+    report.exported = records.len();
+    for path in existing.keys().filter(|path| !current.contains(*path)) {
+        tx.execute("DELETE FROM notes WHERE path = ?1", [path])?;
+        report.removed += 1;
+    }
+    tx.commit().context("Failed to write export")?;
+    Ok(report)
+}
+
+fn init_schema(conn: &Connection) -> Result<()> {
+    conn.pragma_update(None, "foreign_keys", true)?;
+    let version: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    match version {
+        0 => {
+            conn.execute_batch(SCHEMA)
+                .context("Failed to create database schema")?;
+            conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        }
+        SCHEMA_VERSION => {}
+        other => anyhow::bail!(
+            "Database has schema version {other}, but only version {SCHEMA_VERSION} is supported"
+        ),
+    }
+    Ok(())
+}
+
+fn insert_note(conn: &Connection, record: &NoteRecord) -> Result<()> {
+    let path = record.path.to_string_lossy();
+    // Deleting cascades to all of the note's tags, links, and properties
+    conn.execute("DELETE FROM notes WHERE path = ?1", [&path])?;
+    conn.execute(
+        "INSERT INTO notes (path, modified, size, words) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            path,
+            record.modified,
+            record.size as i64,
+            record.words as i64
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+
+    let mut insert_tag =
+        conn.prepare("INSERT OR IGNORE INTO tags (note_id, tag) VALUES (?1, ?2)")?;
+    for tag in &record.tags {
+        insert_tag.execute(params![id, tag])?;
+    }
+    let mut insert_link =
+        conn.prepare("INSERT OR IGNORE INTO links (note_id, target) VALUES (?1, ?2)")?;
+    for link in &record.links {
+        insert_link.execute(params![id, link.to_string_lossy()])?;
+    }
+    let mut insert_property =
+        conn.prepare("INSERT INTO properties (note_id, key, value) VALUES (?1, ?2, ?3)")?;
+    for (key, value) in &record.properties {
+        insert_property.execute(params![id, key, value.to_string()])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export_dir(conn: &mut Connection, dir: &Path) -> Result<ExportReport> {
+        let entries = obsidian_core::reader::read_dir(dir, true)?;
+        export_to(conn, entries, dir, LinkStyle::Infer, &[])
+    }
+
+    #[test]
+    fn exports_incrementally() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(
+            dir.path().join("Project.md"),
+            "---\ntags: [work]\nstatus: active\n---\nSee [notes](Notes.md)\n",
+        )?;
+        std::fs::write(dir.path().join("Notes.md"), "Some notes\n")?;
+        let mut conn = Connection::open_in_memory()?;
+
+        let report = export_dir(&mut conn, dir.path())?;
+        assert_eq!(
+            (report.exported, report.unchanged, report.removed),
+            (2, 0, 0)
+        );
+        let backlinks: Vec<String> = conn
+            .prepare(
+                "SELECT n.path FROM links l JOIN notes n ON n.id = l.note_id WHERE l.target = 'Notes.md'",
+            )?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        assert_eq!(backlinks, vec!["Project.md"]);
+        let status: String = conn.query_row(
+            "SELECT json_extract(value, '$') FROM properties WHERE key = 'status'",
+            [],
+            |row| row.get(0),
+        )?;
+        assert_eq!(status, "active");
+
+        std::fs::remove_file(dir.path().join("Notes.md"))?;
+        let report = export_dir(&mut conn, dir.path())?;
+        assert_eq!(
+            (report.exported, report.unchanged, report.removed),
+            (0, 1, 1)
+        );
+        let links: i64 = conn.query_row("SELECT COUNT(*) FROM links", [], |row| row.get(0))?;
+        assert_eq!(links, 1, "links from unchanged notes should be kept");
+
+        // Force the note to look modified so it gets exported again
+        conn.execute("UPDATE notes SET modified = 0", [])?;
+        std::fs::write(dir.path().join("Project.md"), "---\ntags: [done]\n---\n")?;
+        let report = export_dir(&mut conn, dir.path())?;
+        assert_eq!(
+            (report.exported, report.unchanged, report.removed),
+            (1, 0, 0)
+        );
+        let tags: Vec<String> = conn
+            .prepare("SELECT tag FROM tags")?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        assert_eq!(tags, vec!["done"]);
+        let links: i64 = conn.query_row("SELECT COUNT(*) FROM links", [], |row| row.get(0))?;
+        assert_eq!(links, 0, "old links should be removed with the old note");
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_unknown_schema_versions() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1)?;
+        assert!(init_schema(&conn).is_err());
+        Ok(())
+    }
+}
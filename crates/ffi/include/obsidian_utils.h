@@ -0,0 +1,81 @@
+#ifndef OBSIDIAN_UTILS_H
+#define OBSIDIAN_UTILS_H
+
+/* Generated with cbindgen. Do not edit by hand, run `cbindgen --config cbindgen.toml --output include/obsidian_utils.h` from crates/ffi instead */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// An indexed vault. This is an opaque handle for C callers
+typedef struct ObsidianVault ObsidianVault;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Open and index the vault at the given directory. Returns null on error.
+//
+// # Safety
+//
+// `path` must be a valid, null terminated string
+struct ObsidianVault *obsidian_vault_open(const char *path);
+
+// Free a vault returned by [`obsidian_vault_open`]. Passing null is a no-op.
+//
+// # Safety
+//
+// `vault` must be null or a pointer returned by [`obsidian_vault_open`] that hasn't already been
+// freed
+void obsidian_vault_free(struct ObsidianVault *vault);
+
+// The number of markdown notes in the vault. Returns 0 if `vault` is null
+//
+// # Safety
+//
+// `vault` must be null or a valid pointer returned by [`obsidian_vault_open`]
+uintptr_t obsidian_vault_note_count(const struct ObsidianVault *vault);
+
+// Get a JSON array of the absolute paths of all notes in the vault. Returns null on error.
+//
+// # Safety
+//
+// `vault` must be a valid pointer returned by [`obsidian_vault_open`]
+char *obsidian_vault_notes_json(const struct ObsidianVault *vault);
+
+// Get the full link graph of the vault as JSON, in the same format as `obsidian-links -o json
+// --include-orphans`. Returns null on error.
+//
+// # Safety
+//
+// `vault` must be a valid pointer returned by [`obsidian_vault_open`]
+char *obsidian_vault_links_json(const struct ObsidianVault *vault);
+
+// Get the links and backlinks of a single file as a JSON object with `exists`, `links`, and
+// `backlinks` keys. The path must be absolute. Returns null if the file isn't part of the link
+// graph or on error.
+//
+// # Safety
+//
+// `vault` must be a valid pointer returned by [`obsidian_vault_open`] and `path` must be a valid,
+// null terminated string
+char *obsidian_vault_file_links_json(const struct ObsidianVault *vault, const char *path);
+
+// Get the last error message recorded on this thread, or null if there hasn't been one. The
+// returned string is owned by the library and is valid until the next call into the library on
+// this thread. It must not be freed
+const char *obsidian_last_error(void);
+
+// Free a string returned by this library. Passing null is a no-op.
+//
+// # Safety
+//
+// `s` must be null or a string returned by this library that hasn't already been freed
+void obsidian_string_free(char *s);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* OBSIDIAN_UTILS_H */
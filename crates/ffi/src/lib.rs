@@ -0,0 +1,332 @@
+//! A C compatible interface to the vault indexing and link graph in obsidian-utils, so the same
+//! engine can be reused from other languages (or from Obsidian plugins via a native module).
+//!
+//! All functions are prefixed with `obsidian_`. Any function that can fail returns a null pointer
+//! and records a message that can be read with [`obsidian_last_error`]. Panics are caught and
+//! reported the same way, so they never unwind into the host process. Strings
+//! returned by this library must be freed with [`obsidian_string_free`] and vaults must be freed
+//! with [`obsidian_vault_free`].
+//!
+//! The C header lives in `include/obsidian_utils.h` and is generated with cbindgen.
+
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString, c_char},
+    panic::AssertUnwindSafe,
+    path::{Path, PathBuf},
+    ptr,
+};
+
+use anyhow::{Context, Result};
+use comrak::Arena;
+
+use obsidian_core::{parser, reader};
+use obsidian_links::{Links, parser::LinkStyle};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// An indexed vault. This is an opaque handle for C callers
+pub struct ObsidianVault {
+    notes: Vec<PathBuf>,
+    links: Links,
+}
+
+impl ObsidianVault {
+    /// Index the vault at the given root. Notes that can't be read are skipped (and logged)
+    fn open(root: &Path) -> Result<Self> {
+        let root = root
+            .canonicalize()
+            .with_context(|| format!("Unable to open vault {}", root.display()))?;
+        let entries = reader::read_dir(&root, true)?;
+        let arena = Arena::with_capacity(entries.len());
+        let parsed_files = parser::ignore_error_iter(parser::parse_files(&arena, entries));
+
+        let mut notes = Vec::new();
+        let mut links = Links::new();
+        for (pf, targets) in
+            obsidian_links::parser::parse_links(parsed_files, &root, LinkStyle::default())
+        {
+            let path = match pf.path.canonicalize() {
+                Ok(path) => path,
+                Err(e) => {
+                    log::error!("Skipping {}: {e}", pf.path.display());
+                    continue;
+                }
+            };
+            let targets = targets
+                .iter()
+                .map(|p| obsidian_links::resolve_link_path(p))
+                .collect::<Result<Vec<_>>>()?;
+            links.insert_links(path.clone(), targets);
+            notes.push(path);
+        }
+        notes.sort();
+        Ok(Self { notes, links })
+    }
+}
+
+/// Open and index the vault at the given directory. Returns null on error.
+///
+/// # Safety
+///
+/// `path` must be a valid, null terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn obsidian_vault_open(path: *const c_char) -> *mut ObsidianVault {
+    let result = catch_panic(|| {
+        // SAFETY: The caller guarantees the pointer is a valid C string
+        let path = unsafe { str_from_ptr(path) }?;
+        ObsidianVault::open(Path::new(path))
+    });
+    match result {
+        Ok(vault) => Box::into_raw(Box::new(vault)),
+        Err(e) => {
+            set_last_error(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Free a vault returned by [`obsidian_vault_open`]. Passing null is a no-op.
+///
+/// # Safety
+///
+/// `vault` must be null or a pointer returned by [`obsidian_vault_open`] that hasn't already been
+/// freed
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn obsidian_vault_free(vault: *mut ObsidianVault) {
+    let result = catch_panic(|| {
+        if !vault.is_null() {
+            // SAFETY: The caller guarantees the pointer came from Box::into_raw in
+            // obsidian_vault_open
+            drop(unsafe { Box::from_raw(vault) });
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        set_last_error(e);
+    }
+}
+
+/// The number of markdown notes in the vault. Returns 0 if `vault` is null
+///
+/// # Safety
+///
+/// `vault` must be null or a valid pointer returned by [`obsidian_vault_open`]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn obsidian_vault_note_count(vault: *const ObsidianVault) -> usize {
+    // SAFETY: The caller guarantees the pointer is null or valid
+    let result = catch_panic(|| Ok(unsafe { vault.as_ref() }.map_or(0, |v| v.notes.len())));
+    result.unwrap_or_else(|e| {
+        set_last_error(e);
+        0
+    })
+}
+
+/// Get a JSON array of the absolute paths of all notes in the vault. Returns null on error.
+///
+/// # Safety
+///
+/// `vault` must be a valid pointer returned by [`obsidian_vault_open`]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn obsidian_vault_notes_json(vault: *const ObsidianVault) -> *mut c_char {
+    // SAFETY: The caller guarantees the pointer is null or valid
+    let result = catch_panic(|| unsafe { vault_ref(vault) }.and_then(|v| to_json(&v.notes)));
+    into_c_string(result)
+}
+
+/// Get the full link graph of the vault as JSON, in the same format as `obsidian-links -o json
+/// --include-orphans`. Returns null on error.
+///
+/// # Safety
+///
+/// `vault` must be a valid pointer returned by [`obsidian_vault_open`]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn obsidian_vault_links_json(vault: *const ObsidianVault) -> *mut c_char {
+    // SAFETY: The caller guarantees the pointer is null or valid
+    let result = catch_panic(|| unsafe { vault_ref(vault) }.and_then(|v| to_json(&v.links)));
+    into_c_string(result)
+}
+
+/// Get the links and backlinks of a single file as a JSON object with `exists`, `links`, and
+/// `backlinks` keys. The path must be absolute. Returns null if the file isn't part of the link
+/// graph or on error.
+///
+/// # Safety
+///
+/// `vault` must be a valid pointer returned by [`obsidian_vault_open`] and `path` must be a valid,
+/// null terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn obsidian_vault_file_links_json(
+    vault: *const ObsidianVault,
+    path: *const c_char,
+) -> *mut c_char {
+    // SAFETY: The caller guarantees both pointers are null or valid
+    let result = catch_panic(|| {
+        let v = unsafe { vault_ref(vault) }?;
+        let path = unsafe { str_from_ptr(path) }?;
+        let path = obsidian_links::resolve_link_path(Path::new(path))?;
+        let file_links = v
+            .links
+            .get(&path)
+            .with_context(|| format!("{} is not in the link graph", path.display()))?;
+        to_json(file_links)
+    });
+    into_c_string(result)
+}
+
+/// Get the last error message recorded on this thread, or null if there hasn't been one. The
+/// returned string is owned by the library and is valid until the next call into the library on
+/// this thread. It must not be freed
+#[unsafe(no_mangle)]
+pub extern "C" fn obsidian_last_error() -> *const c_char {
+    catch_panic(|| Ok(LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))))
+        .unwrap_or(ptr::null())
+}
+
+/// Free a string returned by this library. Passing null is a no-op.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by this library that hasn't already been freed
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn obsidian_string_free(s: *mut c_char) {
+    let result = catch_panic(|| {
+        if !s.is_null() {
+            // SAFETY: The caller guarantees the pointer came from CString::into_raw
+            drop(unsafe { CString::from_raw(s) });
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        set_last_error(e);
+    }
+}
+
+/// Run the given function, turning a panic into an error. A panic unwinding out of an `extern "C"`
+/// function aborts the whole process, which for a native module would be the host application
+fn catch_panic<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    std::panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown cause".to_string());
+        Err(anyhow::anyhow!("Internal error (panic): {message}"))
+    })
+}
+
+/// # Safety
+///
+/// `ptr` must be null or a valid, null terminated string that outlives the returned reference
+unsafe fn str_from_ptr<'a>(ptr: *const c_char) -> Result<&'a str> {
+    anyhow::ensure!(!ptr.is_null(), "Got a null string");
+    // SAFETY: Checked for null above and the caller guarantees validity
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .context("String is not valid UTF-8")
+}
+
+/// # Safety
+///
+/// `vault` must be null or a valid pointer returned by [`obsidian_vault_open`]
+unsafe fn vault_ref<'a>(vault: *const ObsidianVault) -> Result<&'a ObsidianVault> {
+    // SAFETY: The caller guarantees the pointer is null or valid
+    unsafe { vault.as_ref() }.context("Got a null vault")
+}
+
+fn to_json<T: serde::Serialize + ?Sized>(value: &T) -> Result<String> {
+    serde_json::to_string(value).context("Unable to serialize to JSON")
+}
+
+fn into_c_string(result: Result<String>) -> *mut c_char {
+    match result.and_then(|s| CString::new(s).context("String contains a null byte")) {
+        Ok(s) => s.into_raw(),
+        Err(e) => {
+            set_last_error(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+fn set_last_error(err: anyhow::Error) {
+    // Alternate formatting of anyhow errors includes the full chain of causes
+    let message =
+        CString::new(format!("{err:#}").replace('\0', "")).expect("null bytes have been removed");
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_vault() -> CString {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../test-vault");
+        CString::new(path.to_str().unwrap()).unwrap()
+    }
+
+    unsafe fn take_string(s: *mut c_char) -> String {
+        assert!(!s.is_null(), "expected a string");
+        let owned = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+        unsafe { obsidian_string_free(s) };
+        owned
+    }
+
+    #[test]
+    fn queries_links_through_c_api() {
+        unsafe {
+            let vault = obsidian_vault_open(test_vault().as_ptr());
+            assert!(!vault.is_null());
+            assert_eq!(obsidian_vault_note_count(vault), 10);
+
+            let notes: Vec<PathBuf> =
+                serde_json::from_str(&take_string(obsidian_vault_notes_json(vault))).unwrap();
+            let source = notes
+                .iter()
+                .find(|p| p.ends_with("links/Source.md"))
+                .unwrap();
+            let source = CString::new(source.to_str().unwrap()).unwrap();
+            let links: serde_json::Value = serde_json::from_str(&take_string(
+                obsidian_vault_file_links_json(vault, source.as_ptr()),
+            ))
+            .unwrap();
+            assert_eq!(links["exists"], true);
+            assert_eq!(links["links"].as_array().unwrap().len(), 5);
+
+            let graph = take_string(obsidian_vault_links_json(vault));
+            assert!(graph.contains("Sibling.md"));
+
+            obsidian_vault_free(vault);
+        }
+    }
+
+    #[test]
+    fn reports_errors() {
+        let missing = CString::new("/definitely/not/a/vault").unwrap();
+        unsafe {
+            assert!(obsidian_vault_open(missing.as_ptr()).is_null());
+            let message = CStr::from_ptr(obsidian_last_error()).to_str().unwrap();
+            assert!(message.contains("Unable to open vault"), "got {message}");
+
+            assert!(obsidian_vault_links_json(ptr::null()).is_null());
+            assert_eq!(obsidian_vault_note_count(ptr::null()), 0);
+        }
+    }
+
+    #[test]
+    fn reports_panics_as_errors() {
+        let result = catch_panic(|| -> Result<String> { panic!("boom") });
+        assert!(into_c_string(result).is_null());
+        let message = unsafe { CStr::from_ptr(obsidian_last_error()) }
+            .to_str()
+            .unwrap();
+        assert!(
+            message.contains("panic") && message.contains("boom"),
+            "got {message}"
+        );
+
+        let result = catch_panic(|| -> Result<()> { std::panic::panic_any(5) });
+        assert!(result.unwrap_err().to_string().contains("unknown cause"));
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use obsidian_core::{parser, writer::Transaction};
+
+use crate::{
+    Links,
+    parser::LinkStyle,
+    rename::{normalize, retarget_links, same_note},
+};
+
+/// The default minimum similarity (from 0 to 1) between a broken link and a note name for the
+/// note to be suggested
+pub const DEFAULT_MIN_SIMILARITY: f64 = 0.6;
+
+/// A suggested fix for a broken link
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkSuggestion {
+    /// The path the broken link points to
+    pub target: PathBuf,
+    /// The existing note that the link most likely meant
+    pub suggestion: PathBuf,
+    /// How similar the note name is to the link, from 0 to 1
+    pub similarity: f64,
+    /// The files containing the broken link
+    pub sources: BTreeSet<PathBuf>,
+}
+
+/// Suggest the closest existing note for each broken link in the link graph, based on the edit
+/// distance between file names (ignoring case and the `.md` extension). Links without a note with
+/// at least `min_similarity` are left out. Extensionless links (like most wikilinks) to notes that
+/// exist and links to files that exist on disk (like images) aren't considered broken
+pub fn suggest_fixes(links: &Links, min_similarity: f64) -> Vec<LinkSuggestion> {
+    let notes: Vec<(&PathBuf, String)> = links
+        .iter()
+        .filter(|(_, info)| info.exists)
+        .map(|(path, _)| (path, note_name(path)))
+        .collect();
+
+    links
+        .iter()
+        .filter(|(target, info)| {
+            !info.exists
+                && !target.exists()
+                && !notes.iter().any(|(note, _)| same_note(target, note))
+        })
+        .filter_map(|(target, info)| {
+            let name = note_name(target);
+            let (suggestion, similarity) = notes
+                .iter()
+                .map(|(note, note_name)| (*note, similarity(&name, note_name)))
+                .filter(|(_, similarity)| *similarity >= min_similarity)
+                // Prefer the most similar note and then the one closest to the broken link
+                .max_by(|(a, a_score), (b, b_score)| {
+                    a_score
+                        .total_cmp(b_score)
+                        .then_with(|| shared_prefix(a, target).cmp(&shared_prefix(b, target)))
+                        .then_with(|| b.cmp(a))
+                })?;
+            Some(LinkSuggestion {
+                target: normalize(target),
+                suggestion: suggestion.clone(),
+                similarity,
+                sources: info.backlinks.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Stage rewriting the broken links in every source file to point at the suggested notes,
+/// returning the paths of the files that changed. Links keep their original form, as with
+/// [`crate::rename::rename_note`]
+pub fn apply_fixes(
+    vault_root: impl AsRef<Path>,
+    link_style: LinkStyle,
+    suggestions: &[LinkSuggestion],
+    tx: &mut Transaction,
+) -> Result<Vec<PathBuf>> {
+    let vault_root = vault_root.as_ref();
+    let vault = vault_root
+        .canonicalize()
+        .with_context(|| format!("Unable to open vault {}", vault_root.display()))?;
+
+    let mut by_source: BTreeMap<&PathBuf, BTreeMap<PathBuf, &PathBuf>> = BTreeMap::new();
+    for suggestion in suggestions {
+        for source in &suggestion.sources {
+            by_source
+                .entry(source)
+                .or_default()
+                .insert(normalize(&suggestion.target), &suggestion.suggestion);
+        }
+    }
+
+    let mut changed = Vec::new();
+    for (source, fixes) in by_source {
+        let updated = retarget_links(source, source, &vault, link_style, |resolved, _| {
+            fixes.get(resolved).map(|fix| fix.to_path_buf())
+        })?;
+        if let Some(updated) = updated {
+            tx.set_content(source, updated);
+            changed.push(source.clone());
+        }
+    }
+    Ok(changed)
+}
+
+fn note_name(path: &Path) -> String {
+    let name = if parser::is_markdown(path) {
+        path.file_stem()
+    } else {
+        path.file_name()
+    };
+    name.map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn shared_prefix(a: &Path, b: &Path) -> usize {
+    a.components()
+        .zip(b.components())
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+/// The normalized Levenshtein similarity between two strings, from 0 (nothing in common) to 1
+/// (identical)
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    // Only keep the previous row of the distance matrix around
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similarity_is_normalized_edit_distance() {
+        assert_eq!(similarity("kitten", "kitten"), 1.0);
+        assert!((similarity("kitten", "sitting") - (1.0 - 3.0 / 7.0)).abs() < f64::EPSILON);
+        assert_eq!(similarity("", "abc"), 0.0);
+    }
+
+    #[test]
+    fn suggests_and_applies_fixes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let vault = dir.path().canonicalize()?;
+        std::fs::create_dir(vault.join("Tools"))?;
+        std::fs::create_dir(vault.join("img"))?;
+        std::fs::write(vault.join("img/diagram.png"), "")?;
+        std::fs::write(vault.join("diagram.md"), "")?;
+        std::fs::write(vault.join("Roo Code.md"), "")?;
+        std::fs::write(vault.join("Tools/Ghostty.md"), "")?;
+        std::fs::write(vault.join("Existing.md"), "")?;
+        std::fs::write(
+            vault.join("Source.md"),
+            "[[RooCode]], [ghost](Tools/Ghosty.md#Config), [[Existing]], and [[Unrelated Thing]]\n[doc](img/diagram.png) and [pdf](Existing.pdf)\n",
+        )?;
+
+        let links = crate::rename::vault_links(&vault, LinkStyle::Infer)?;
+        let suggestions = suggest_fixes(&links, DEFAULT_MIN_SIMILARITY);
+        let fixes: BTreeMap<_, _> = suggestions
+            .iter()
+            .map(|s| (note_name(&s.target), note_name(&s.suggestion)))
+            .collect();
+        assert_eq!(
+            fixes,
+            BTreeMap::from([
+                ("existing.pdf".to_string(), "existing".to_string()),
+                ("ghosty".to_string(), "ghostty".to_string()),
+                ("roocode".to_string(), "roo code".to_string()),
+            ])
+        );
+
+        let mut tx = Transaction::new();
+        let changed = apply_fixes(&vault, LinkStyle::Infer, &suggestions, &mut tx)?;
+        assert_eq!(changed, vec![vault.join("Source.md")]);
+        tx.commit()?;
+        assert_eq!(
+            std::fs::read_to_string(vault.join("Source.md"))?,
+            "[[Roo Code]], [ghost](Tools/Ghostty.md#Config), [[Existing]], and [[Unrelated Thing]]\n[doc](img/diagram.png) and [pdf](Existing.md)\n",
+            "links to attachments that exist are left alone and retargeted links keep the extension"
+        );
+        Ok(())
+    }
+}
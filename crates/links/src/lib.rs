@@ -6,7 +6,21 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
+pub mod fix;
 pub mod parser;
+pub mod rename;
+
+/// Resolve a link target parsed from a file into the path used as a key in [`Links`]. Files that
+/// exist are canonicalized. Obsidian allows linking to files that don't exist yet, so those can't
+/// be canonicalized and are instead made as absolute as possible.
+pub fn resolve_link_path(path: &Path) -> anyhow::Result<PathBuf> {
+    match path.canonicalize() {
+        Ok(canon) => Ok(canon),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => std::path::absolute(path)
+            .map_err(|e| anyhow::anyhow!("Failed to get absolute path for {:?}: {}", path, e)),
+        Err(e) => Err(anyhow::Error::new(e).context("Error canonicalizing path")),
+    }
+}
 
 /// Information about links associated with a file
 #[derive(Debug, Serialize, Deserialize)]
@@ -35,7 +35,7 @@ impl FromStr for LinkStyle {
 }
 
 impl LinkStyle {
-    fn path_from_link<'a, T: AsRef<Path>>(
+    pub(crate) fn path_from_link<'a, T: AsRef<Path>>(
         &self,
         raw_link: PathBuf,
         file_path: &'a Path,
@@ -63,6 +63,18 @@ impl LinkStyle {
             LinkStyle::RelativeToFile => file_path.parent().unwrap_or(Path::new("")).join(raw_link),
         }
     }
+
+    /// Resolve the raw URL of a link found in the file at `file_path` into the path it points to,
+    /// the same way [`parse_links`] does. Returns `None` for external links and links to headings
+    /// within the same file
+    pub fn resolve<T: AsRef<Path>>(
+        &self,
+        raw_link: &str,
+        file_path: &Path,
+        vault_root: &T,
+    ) -> Option<PathBuf> {
+        link_target(raw_link).map(|target| self.path_from_link(target, file_path, vault_root))
+    }
 }
 
 /// Parse the links from a list of ParsedFiles, returning an iterator of tuples of the
@@ -94,49 +106,55 @@ fn parse_links_from_ast<'a, T: AsRef<Path>>(
                 NodeValue::WikiLink(link) => link.url.clone(),
                 _ => return None,
             };
-            // A normal file path does not parse as a URL, so if it does, we skip it
-            if url::Url::parse(&raw_path).is_ok() {
-                return None;
-            }
-
-            // Links may be percent-encoded, so we decode them first
-            let decoded_path = match urlencoding::decode(&raw_path).ok() {
-                Some(dp) => dp.into_owned(),
-                None => {
-                    log::warn!("Failed to decode link path: {}", raw_path);
-                    return None;
-                }
-            };
-
-            // Convert to PathBuf
-            let mut decoded_path = PathBuf::from(decoded_path);
-
-            // Now remove any fragment components (e.g. #heading) from the path since these are
-            // valid in markdown links. These will only be in the filename, so we pull that off,
-            // remove the fragment, and reattach it.
-
-            let maybe_cleaned = if let Some((file_stem, _)) = decoded_path
-                .file_name()
-                .and_then(|fname| fname.to_str())
-                .and_then(|s| s.split_once('#'))
-            {
-                // This would be internal document links (i.e. just a heading), so we skip it
-                if file_stem.is_empty() {
-                    return None;
-                }
-                // Clone the cleaned filename so we release the borrow on decoded_path
-                Some(file_stem.to_owned())
-            } else {
-                None
-            };
-            if let Some(cleaned) = maybe_cleaned {
-                decoded_path.set_file_name(cleaned);
-            }
-            Some(link_style.path_from_link(decoded_path, file_path, vault_root))
+            link_style.resolve(&raw_path, file_path, vault_root)
         })
         .collect()
 }
 
+/// Turn the raw URL of a link into the path it points to (relative to whatever the link style
+/// says). Returns `None` for external links and links to headings within the same file
+pub(crate) fn link_target(raw_path: &str) -> Option<PathBuf> {
+    // A normal file path does not parse as a URL, so if it does, we skip it
+    if url::Url::parse(raw_path).is_ok() {
+        return None;
+    }
+
+    // Links may be percent-encoded, so we decode them first
+    let decoded_path = match urlencoding::decode(raw_path).ok() {
+        Some(dp) => dp.into_owned(),
+        None => {
+            log::warn!("Failed to decode link path: {}", raw_path);
+            return None;
+        }
+    };
+
+    // Convert to PathBuf
+    let mut decoded_path = PathBuf::from(decoded_path);
+
+    // Now remove any fragment components (e.g. #heading) from the path since these are
+    // valid in markdown links. These will only be in the filename, so we pull that off,
+    // remove the fragment, and reattach it.
+
+    let maybe_cleaned = if let Some((file_stem, _)) = decoded_path
+        .file_name()
+        .and_then(|fname| fname.to_str())
+        .and_then(|s| s.split_once('#'))
+    {
+        // This would be internal document links (i.e. just a heading), so we skip it
+        if file_stem.is_empty() {
+            return None;
+        }
+        // Clone the cleaned filename so we release the borrow on decoded_path
+        Some(file_stem.to_owned())
+    } else {
+        None
+    };
+    if let Some(cleaned) = maybe_cleaned {
+        decoded_path.set_file_name(cleaned);
+    }
+    Some(decoded_path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,10 +184,7 @@ mod tests {
         load_file(arena, encoded_file_path())
     }
 
-    fn load_file<'a>(
-        arena: &'a Arena<AstNode<'a>>,
-        path: PathBuf,
-    ) -> Result<ParsedFile<'a>> {
+    fn load_file<'a>(arena: &'a Arena<AstNode<'a>>, path: PathBuf) -> Result<ParsedFile<'a>> {
         let metadata = std::fs::metadata(&path)?;
         let ast = parser::parse_file(arena, &path)?;
         Ok(ParsedFile {
@@ -0,0 +1,425 @@
+use std::{
+    collections::BTreeSet,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use comrak::{Arena, nodes::NodeValue};
+use serde::Serialize;
+
+use obsidian_core::{parser, reader, writer::Transaction};
+
+use crate::{
+    Links,
+    parser::{LinkStyle, link_target},
+    resolve_link_path,
+};
+
+/// A reason a note can't be renamed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenameConflict {
+    /// The note being renamed doesn't exist
+    SourceMissing,
+    /// A file already exists at the new path
+    TargetExists,
+    /// The new path is outside of the vault
+    OutsideVault,
+}
+
+impl std::fmt::Display for RenameConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            RenameConflict::SourceMissing => "the note to rename doesn't exist",
+            RenameConflict::TargetExists => "a file already exists at the new path",
+            RenameConflict::OutsideVault => "the new path is outside of the vault",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// The result of staging a rename
+#[derive(Debug, Serialize)]
+pub struct RenameReport {
+    /// The absolute path of the note being renamed
+    pub from: PathBuf,
+    /// The absolute path the note will be moved to
+    pub to: PathBuf,
+    /// Files (by their current path) that had links updated to point at the new path. This
+    /// includes the renamed note itself if its own relative links needed updating
+    pub updated: Vec<PathBuf>,
+    /// Any conflicts that prevented the rename. If this isn't empty, nothing was staged
+    pub conflicts: Vec<RenameConflict>,
+}
+
+impl RenameReport {
+    /// Returns true if the rename couldn't be staged
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+}
+
+/// Stage renaming (or moving) a note along with updating every wiki and markdown link in the vault
+/// that points at it. Relative links inside the renamed note are also updated if it moves to a
+/// different folder. Paths can be absolute or relative to the current directory, and the `.md`
+/// extension is added to the new path if it is missing.
+///
+/// Links keep their original form as much as possible: fragments (`#heading`), aliases
+/// (`[[note|alias]]`), percent encoding, and whether the `.md` extension was included are all
+/// preserved. Nothing is written until the transaction is committed, so this can be used for a
+/// dry run by inspecting the transaction instead.
+///
+/// NOTE: Links are rewritten by matching their text in the file, so identical link text inside
+/// code blocks will also be updated.
+pub fn rename_note(
+    vault_root: impl AsRef<Path>,
+    old_path: impl AsRef<Path>,
+    new_path: impl AsRef<Path>,
+    link_style: LinkStyle,
+    tx: &mut Transaction,
+) -> Result<RenameReport> {
+    let vault_root = vault_root.as_ref();
+    let vault = vault_root
+        .canonicalize()
+        .with_context(|| format!("Unable to open vault {}", vault_root.display()))?;
+    let from = absolute_path(old_path.as_ref())?;
+    let mut to = absolute_path(new_path.as_ref())?;
+    if parser::is_markdown(&from) && !parser::is_markdown(&to) {
+        let mut file = to.into_os_string();
+        file.push(".md");
+        to = PathBuf::from(file);
+    }
+
+    let mut report = RenameReport {
+        from: from.clone(),
+        to: to.clone(),
+        updated: Vec::new(),
+        conflicts: Vec::new(),
+    };
+    if !from.is_file() {
+        report.conflicts.push(RenameConflict::SourceMissing);
+    }
+    if to != from && to.exists() {
+        report.conflicts.push(RenameConflict::TargetExists);
+    }
+    if !to.starts_with(&vault) {
+        report.conflicts.push(RenameConflict::OutsideVault);
+    }
+    if report.has_conflicts() || to == from {
+        return Ok(report);
+    }
+
+    let links = vault_links(&vault, link_style)?;
+    let mut candidates: BTreeSet<PathBuf> = links
+        .iter()
+        .filter(|(path, _)| same_note(path, &from))
+        .flat_map(|(_, info)| info.backlinks.iter().cloned())
+        .collect();
+    candidates.insert(from.clone());
+
+    for path in candidates {
+        // The renamed note's own relative links need to be relative to its new location
+        let location = if path == from { &to } else { &path };
+        let updated = retarget_links(&path, location, &vault, link_style, |resolved, relative| {
+            if same_note(resolved, &from) {
+                Some(to.clone())
+            } else if path == from && relative {
+                Some(resolved.to_path_buf())
+            } else {
+                None
+            }
+        })?;
+        if let Some(updated) = updated {
+            tx.set_content(&path, updated);
+            report.updated.push(path);
+        }
+    }
+    tx.move_file(&from, &to);
+
+    Ok(report)
+}
+
+/// Rewrite links in the file at `path` (which will be located at `location` after any move) to
+/// point at new targets. The `retarget` function is given the resolved target of each link and
+/// whether the link is relative to the file, and returns the new target for links that should
+/// change. Returns the new content of the file if any links changed
+pub(crate) fn retarget_links<F>(
+    path: &Path,
+    location: &Path,
+    vault: &Path,
+    link_style: LinkStyle,
+    retarget: F,
+) -> Result<Option<String>>
+where
+    F: Fn(&Path, bool) -> Option<PathBuf>,
+{
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let arena = Arena::new();
+    let ast = parser::parse_content(&arena, &content);
+
+    let mut replacements = Vec::new();
+    for node in ast.descendants() {
+        let (raw, is_wiki) = match &node.data.borrow().value {
+            NodeValue::Link(link) => (link.url.clone(), false),
+            NodeValue::WikiLink(link) => (link.url.clone(), true),
+            _ => continue,
+        };
+        let Some(target) = link_target(&raw) else {
+            continue;
+        };
+        let linked = link_style.path_from_link(target.clone(), path, &vault);
+        let relative_to_file = linked == path.parent().unwrap_or(Path::new("")).join(&target);
+        let resolved = normalize(&resolve_link_path(&linked)?);
+
+        let Some(new_target) = retarget(&resolved, relative_to_file && !target.has_root()) else {
+            continue;
+        };
+        let base = if relative_to_file {
+            location.parent().unwrap_or(Path::new(""))
+        } else {
+            vault
+        };
+        let new_raw = link_text(&raw, &target, &new_target, base, is_wiki);
+        if new_raw != raw {
+            replacements.push((raw, new_raw, is_wiki));
+        }
+    }
+    replacements.sort();
+    replacements.dedup();
+
+    let mut updated = content.clone();
+    for (raw, new_raw, is_wiki) in &replacements {
+        let before = updated.clone();
+        updated = if *is_wiki {
+            replace_link(&updated, "[[", raw, new_raw, &["]]", "|"])
+        } else {
+            let angled = replace_link(&updated, "](<", raw, new_raw, &[">"]);
+            replace_link(&angled, "](", raw, new_raw, &[")", " "])
+        };
+        if updated == before {
+            log::warn!(
+                "Unable to find link text {raw} in {}, it will not be updated",
+                path.display()
+            );
+        }
+    }
+    Ok((updated != content).then_some(updated))
+}
+
+/// Build the link graph for every note in the vault
+pub(crate) fn vault_links(vault: &Path, link_style: LinkStyle) -> Result<Links> {
+    let entries = reader::read_dir(vault, true)?;
+    let arena = Arena::with_capacity(entries.len());
+    let parsed_files = parser::ignore_error_iter(parser::parse_files(&arena, entries));
+    crate::parser::parse_links(parsed_files, &vault, link_style).try_fold(
+        Links::new(),
+        |mut acc, (from, to)| {
+            let to = to
+                .iter()
+                .map(|p| resolve_link_path(p).map(|p| normalize(&p)))
+                .collect::<Result<Vec<_>>>()?;
+            acc.insert_links(from.path.canonicalize()?, to);
+            Ok(acc)
+        },
+    )
+}
+
+/// Whether a resolved link points at the given note. Wikilinks usually leave off the `.md`
+/// extension, so those are matched as well
+pub fn same_note(link: &Path, note: &Path) -> bool {
+    let link = &normalize(link);
+    if link == note {
+        return true;
+    }
+    let mut with_extension = link.as_os_str().to_owned();
+    with_extension.push(".md");
+    !parser::is_markdown(link) && Path::new(&with_extension) == note
+}
+
+/// Build the new raw text for a link, keeping the style of the original
+fn link_text(raw: &str, target: &Path, new_target: &Path, base: &Path, is_wiki: bool) -> String {
+    let mut relative = relative_path(base, new_target);
+    // Only leave off the extension if the original link did, since a markdown link to another kind
+    // of file (like `diagram.png`) wouldn't find the note without it
+    let extensionless = is_wiki || target.extension().is_none();
+    if extensionless && !parser::is_markdown(target) && parser::is_markdown(&relative) {
+        relative.set_extension("");
+    }
+    let mut text = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    if target.starts_with(".") && !text.starts_with("..") {
+        text = format!("./{text}");
+    }
+
+    if raw.contains('%') {
+        text = text
+            .split('/')
+            .map(|part| urlencoding::encode(part).into_owned())
+            .collect::<Vec<_>>()
+            .join("/");
+    } else if !is_wiki && !raw.contains(' ') {
+        // Markdown links can't contain spaces unless they are wrapped in angle brackets
+        text = text.replace(' ', "%20");
+    }
+
+    // Keep any fragment, which is always in the last path component
+    let file_name_start = raw.rfind('/').map_or(0, |idx| idx + 1);
+    if let Some(idx) = raw[file_name_start..].find('#') {
+        text.push_str(&raw[file_name_start + idx..]);
+    }
+    text
+}
+
+/// Replace every occurrence of `prefix` + `old` that is followed by one of the terminators with
+/// `prefix` + `new`
+fn replace_link(content: &str, prefix: &str, old: &str, new: &str, terminators: &[&str]) -> String {
+    let needle = format!("{prefix}{old}");
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(idx) = rest.find(&needle) {
+        let after = &rest[idx + needle.len()..];
+        out.push_str(&rest[..idx]);
+        out.push_str(prefix);
+        if terminators.iter().any(|t| after.starts_with(t)) {
+            out.push_str(new);
+        } else {
+            out.push_str(old);
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Get the path of `target` relative to the `base` directory. Both paths must be absolute
+fn relative_path(base: &Path, target: &Path) -> PathBuf {
+    let base: Vec<_> = base.components().collect();
+    let target: Vec<_> = target.components().collect();
+    let common = base.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    std::iter::repeat_n(Component::ParentDir, base.len() - common)
+        .chain(target[common..].iter().copied())
+        .collect()
+}
+
+/// Make a path absolute and canonicalize as much of it as exists, so it can be compared to the
+/// canonicalized paths in the link graph
+fn absolute_path(path: &Path) -> Result<PathBuf> {
+    let absolute = normalize(
+        &std::path::absolute(path)
+            .with_context(|| format!("Failed to get absolute path for {}", path.display()))?,
+    );
+    let mut existing = absolute.as_path();
+    let mut missing = Vec::new();
+    while !existing.exists() {
+        let Some(parent) = existing.parent() else {
+            return Ok(absolute);
+        };
+        missing.extend(existing.file_name());
+        existing = parent;
+    }
+    let mut resolved = existing.canonicalize()?;
+    resolved.extend(missing.into_iter().rev());
+    Ok(resolved)
+}
+
+/// Lexically remove `.` and `..` components from a path
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rename_rewrites_links_and_moves_note() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let vault = dir.path().canonicalize()?;
+        std::fs::create_dir(vault.join("a"))?;
+        std::fs::write(
+            vault.join("a/Old.md"),
+            "Link [[Sibling]] and [up](../Top.md) and [[#Heading]]\n",
+        )?;
+        std::fs::write(
+            vault.join("a/Sibling.md"),
+            "[[Old]], [[Old#Heading|alias]], [md](Old.md), and [[Other]]\n",
+        )?;
+        std::fs::write(
+            vault.join("Top.md"),
+            "[old](a/Old.md#Section) and [[a/Old]]\n`[[Old]]`\n",
+        )?;
+
+        let mut tx = Transaction::new();
+        let report = rename_note(
+            &vault,
+            vault.join("a/Old.md"),
+            vault.join("b/New Name"),
+            LinkStyle::Infer,
+            &mut tx,
+        )?;
+        assert!(!report.has_conflicts());
+        assert_eq!(report.to, vault.join("b/New Name.md"));
+        assert_eq!(report.updated.len(), 3);
+
+        tx.commit()?;
+        assert!(!vault.join("a/Old.md").exists());
+        assert_eq!(
+            std::fs::read_to_string(vault.join("b/New Name.md"))?,
+            "Link [[../a/Sibling]] and [up](../Top.md) and [[#Heading]]\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(vault.join("a/Sibling.md"))?,
+            "[[../b/New Name]], [[../b/New Name#Heading|alias]], [md](../b/New%20Name.md), and [[Other]]\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(vault.join("Top.md"))?,
+            "[old](b/New%20Name.md#Section) and [[b/New Name]]\n`[[Old]]`\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rename_reports_conflicts() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("One.md"), "one")?;
+        std::fs::write(dir.path().join("Two.md"), "two")?;
+
+        let mut tx = Transaction::new();
+        let report = rename_note(
+            dir.path(),
+            dir.path().join("One.md"),
+            dir.path().join("Two.md"),
+            LinkStyle::Infer,
+            &mut tx,
+        )?;
+        assert_eq!(report.conflicts, vec![RenameConflict::TargetExists]);
+
+        let report = rename_note(
+            dir.path(),
+            dir.path().join("Missing.md"),
+            dir.path().join("../Outside.md"),
+            LinkStyle::Infer,
+            &mut tx,
+        )?;
+        assert_eq!(
+            report.conflicts,
+            vec![RenameConflict::SourceMissing, RenameConflict::OutsideVault]
+        );
+        assert!(tx.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use comrak::Arena;
+use serde::{Deserialize, Serialize};
+
+use obsidian_core::{digest, frontmatter, parser, reader::FileEntry};
+use obsidian_links::parser::LinkStyle;
+
+/// A record of the notes in a vault at a point in time, which can be compared with another
+/// snapshot using [`diff`]. Snapshots can be serialized, so a vault can also be compared against
+/// an earlier saved copy of itself. All paths are relative to the vault root
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VaultSnapshot {
+    pub notes: BTreeMap<PathBuf, NoteSnapshot>,
+}
+
+/// The parts of a note that are compared between snapshots
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoteSnapshot {
+    /// A digest of the full contents of the note, used to detect changes
+    pub digest: String,
+    /// The tags in the note's frontmatter
+    pub tags: BTreeSet<String>,
+    /// The notes (and other files) this note links to. Links that point outside of the vault are
+    /// kept as absolute paths
+    pub links: BTreeSet<PathBuf>,
+}
+
+impl VaultSnapshot {
+    /// Parse all of the markdown files in the given entries into a snapshot. Links are resolved
+    /// relative to the given vault root using the given link style, the same way `obsidian-links`
+    /// does. Tags are also read from any of the given tag keys
+    pub fn from_entries(
+        entries: Vec<FileEntry>,
+        vault_root: &Path,
+        link_style: LinkStyle,
+        tag_keys: &[String],
+    ) -> Result<Self> {
+        let vault = vault_root
+            .canonicalize()
+            .with_context(|| format!("Unable to open vault {}", vault_root.display()))?;
+        let notes: Vec<_> = entries
+            .into_iter()
+            .filter(|e| parser::is_markdown(&e.path))
+            .collect();
+
+        let arena = Arena::with_capacity(notes.len());
+        let parsed_files = parser::ignore_error_iter(parser::parse_files(&arena, notes));
+        let mut snapshot = VaultSnapshot::default();
+        for (pf, targets) in obsidian_links::parser::parse_links(parsed_files, &vault, link_style) {
+            let content = std::fs::read(&pf.path)
+                .with_context(|| format!("Failed to read {}", pf.path.display()))?;
+            let tags = frontmatter::parse_frontmatter_from_ast(pf.ast)
+                .and_then(|fm| fm.with_tag_keys(tag_keys).tags)
+                .unwrap_or_default()
+                .into_iter()
+                .collect();
+            let links = targets
+                .iter()
+                .map(|target| {
+                    obsidian_links::resolve_link_path(target).map(|path| relative_to(&path, &vault))
+                })
+                .collect::<Result<_>>()?;
+            snapshot.notes.insert(
+                relative_to(&pf.path.canonicalize()?, &vault),
+                NoteSnapshot {
+                    digest: digest::hex_digest(content),
+                    tags,
+                    links,
+                },
+            );
+        }
+        Ok(snapshot)
+    }
+
+    /// Load a snapshot previously saved as JSON or CBOR (the `json` and `binary` output formats)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let raw = std::fs::read(path)
+            .with_context(|| format!("Failed to read snapshot {}", path.display()))?;
+        // A JSON snapshot is always an object, which can't be the first byte of a CBOR map
+        if raw.trim_ascii_start().starts_with(b"{") {
+            serde_json::from_slice(&raw)
+                .with_context(|| format!("Invalid JSON snapshot {}", path.display()))
+        } else {
+            ciborium::from_reader(raw.as_slice())
+                .with_context(|| format!("Invalid binary snapshot {}", path.display()))
+        }
+    }
+}
+
+/// Items that were added to and removed from a set between two snapshots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetChanges<T: Ord> {
+    pub added: BTreeSet<T>,
+    pub removed: BTreeSet<T>,
+}
+
+impl<T: Ord + Clone> SetChanges<T> {
+    fn between(old: &BTreeSet<T>, new: &BTreeSet<T>) -> Option<Self> {
+        let changes = SetChanges {
+            added: new.difference(old).cloned().collect(),
+            removed: old.difference(new).cloned().collect(),
+        };
+        (!changes.added.is_empty() || !changes.removed.is_empty()).then_some(changes)
+    }
+}
+
+/// The differences between two vault snapshots. Notes that were added or removed are only listed
+/// once, so their tags and links aren't repeated in `tags` and `links`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VaultDiff {
+    /// Notes that only exist in the new snapshot
+    pub added: BTreeSet<PathBuf>,
+    /// Notes that only exist in the old snapshot
+    pub removed: BTreeSet<PathBuf>,
+    /// Notes in both snapshots whose contents changed
+    pub changed: BTreeSet<PathBuf>,
+    /// Tag changes for each changed note
+    pub tags: BTreeMap<PathBuf, SetChanges<String>>,
+    /// Outgoing link changes for each changed note
+    pub links: BTreeMap<PathBuf, SetChanges<PathBuf>>,
+}
+
+impl VaultDiff {
+    /// Whether the snapshots were identical
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare two snapshots of a vault
+pub fn diff(old: &VaultSnapshot, new: &VaultSnapshot) -> VaultDiff {
+    let mut diff = VaultDiff {
+        removed: old
+            .notes
+            .keys()
+            .filter(|path| !new.notes.contains_key(*path))
+            .cloned()
+            .collect(),
+        ..Default::default()
+    };
+    for (path, note) in &new.notes {
+        let Some(old_note) = old.notes.get(path) else {
+            diff.added.insert(path.clone());
+            continue;
+        };
+        if old_note.digest == note.digest {
+            continue;
+        }
+        diff.changed.insert(path.clone());
+        if let Some(changes) = SetChanges::between(&old_note.tags, &note.tags) {
+            diff.tags.insert(path.clone(), changes);
+        }
+        if let Some(changes) = SetChanges::between(&old_note.links, &note.links) {
+            diff.links.insert(path.clone(), changes);
+        }
+    }
+    diff
+}
+
+fn relative_to(path: &Path, vault: &Path) -> PathBuf {
+    path.strip_prefix(vault).unwrap_or(path).to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_vault(dir: &Path, files: &[(&str, &str)]) -> Result<VaultSnapshot> {
+        for (name, content) in files {
+            std::fs::write(dir.join(name), content)?;
+        }
+        let entries = obsidian_core::reader::read_dir(dir, true)?;
+        VaultSnapshot::from_entries(entries, dir, LinkStyle::Infer, &[])
+    }
+
+    #[test]
+    fn diffs_notes_tags_and_links() -> Result<()> {
+        let old_dir = tempfile::tempdir()?;
+        let new_dir = tempfile::tempdir()?;
+        let old = write_vault(
+            old_dir.path(),
+            &[
+                (
+                    "Project.md",
+                    "---\ntags: [work]\n---\nSee [notes](Notes.md)\n",
+                ),
+                ("Notes.md", "Same\n"),
+                ("Old.md", "Gone soon\n"),
+            ],
+        )?;
+        let new = write_vault(
+            new_dir.path(),
+            &[
+                (
+                    "Project.md",
+                    "---\ntags: [work, done]\n---\nSee [new](New.md)\n",
+                ),
+                ("Notes.md", "Same\n"),
+                ("New.md", "Fresh\n"),
+            ],
+        )?;
+        assert_eq!(
+            old.notes[Path::new("Project.md")].links,
+            BTreeSet::from([PathBuf::from("Notes.md")])
+        );
+
+        let diff = diff(&old, &new);
+        let project = PathBuf::from("Project.md");
+        assert_eq!(diff.added, BTreeSet::from([PathBuf::from("New.md")]));
+        assert_eq!(diff.removed, BTreeSet::from([PathBuf::from("Old.md")]));
+        assert_eq!(diff.changed, BTreeSet::from([project.clone()]));
+        assert_eq!(
+            diff.tags[&project],
+            SetChanges {
+                added: BTreeSet::from(["done".to_string()]),
+                removed: BTreeSet::new(),
+            }
+        );
+        assert_eq!(
+            diff.links[&project],
+            SetChanges {
+                added: BTreeSet::from([PathBuf::from("New.md")]),
+                removed: BTreeSet::from([PathBuf::from("Notes.md")]),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn loads_saved_snapshots() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let snapshot = write_vault(dir.path(), &[("Note.md", "---\ntags: [a]\n---\n")])?;
+
+        let json = dir.path().join("snapshot.json");
+        std::fs::write(&json, serde_json::to_vec(&snapshot)?)?;
+        assert_eq!(VaultSnapshot::load(&json)?, snapshot);
+
+        let cbor = dir.path().join("snapshot.cbor");
+        let mut raw = Vec::new();
+        ciborium::into_writer(&snapshot, &mut raw)?;
+        std::fs::write(&cbor, raw)?;
+        assert_eq!(VaultSnapshot::load(&cbor)?, snapshot);
+        assert!(diff(&snapshot, &VaultSnapshot::load(&json)?).is_empty());
+        Ok(())
+    }
+}
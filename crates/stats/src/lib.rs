@@ -0,0 +1,255 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use comrak::Arena;
+use serde::{Deserialize, Serialize};
+
+use obsidian_core::{
+    frontmatter::{self, Frontmatter},
+    parser::{self, ParseReport, ParsedFile},
+    progress::{ProgressEvent, ProgressReporter},
+    reader::FileEntry,
+    stats::{self, ContentStats},
+};
+use obsidian_links::{Links, parser::LinkStyle, rename::same_note};
+
+pub mod diff;
+pub mod periodic;
+
+/// Aggregate statistics for a whole vault
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VaultSummary {
+    /// The number of markdown notes in the vault
+    pub notes: usize,
+    /// The number of non-markdown files in the vault (images, PDFs, etc). Hidden files and folders
+    /// (like `.obsidian`) are not counted
+    pub attachments: usize,
+    /// The number of distinct tags used in note frontmatter
+    pub tags: usize,
+    /// The total number of times a tag is used across all notes
+    pub tag_usages: usize,
+    /// The number of distinct links between notes (external links are not counted)
+    pub links: usize,
+    /// The number of links pointing to files that don't exist
+    pub broken_links: usize,
+    /// The number of notes without any links or backlinks
+    pub orphans: usize,
+    /// The total number of words across all notes
+    pub total_words: usize,
+    /// The average number of words per note
+    pub average_words: usize,
+    /// The average size of a note on disk, in bytes
+    pub average_note_bytes: u64,
+    /// The number of notes created in each month (formatted as `YYYY-MM`, in UTC). If the file
+    /// system doesn't record creation times, the last modified time is used instead
+    pub notes_per_month: BTreeMap<String, usize>,
+}
+
+/// Incrementally builds a [`VaultSummary`] as files are processed. This is useful for callers that
+/// are already parsing the vault for other reasons and don't want to parse it twice. Otherwise,
+/// [`summarize`] handles everything.
+#[derive(Debug, Default)]
+pub struct SummaryBuilder {
+    vault_root: PathBuf,
+    summary: VaultSummary,
+    tags: BTreeSet<String>,
+    total_bytes: u64,
+}
+
+impl SummaryBuilder {
+    /// Create a new, empty builder for the vault at the given root. The root is only used to
+    /// determine which files are hidden
+    pub fn new(vault_root: impl Into<PathBuf>) -> Self {
+        Self {
+            vault_root: vault_root.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Record a non-markdown file. Hidden files are ignored
+    pub fn add_attachment(&mut self, entry: &FileEntry) {
+        let relative = entry
+            .path
+            .strip_prefix(&self.vault_root)
+            .unwrap_or(&entry.path);
+        if !is_hidden(relative) {
+            self.summary.attachments += 1;
+        }
+    }
+
+    /// Record a parsed note along with its frontmatter and content stats
+    pub fn add_note(
+        &mut self,
+        file: &ParsedFile<'_>,
+        frontmatter: Option<&Frontmatter>,
+        stats: &ContentStats,
+    ) {
+        self.summary.notes += 1;
+        self.summary.total_words += stats.words;
+        self.total_bytes += file.metadata.len();
+
+        if let Some(tags) = frontmatter.and_then(|fm| fm.tags.as_ref()) {
+            self.summary.tag_usages += tags.len();
+            self.tags.extend(tags.iter().cloned());
+        }
+
+        let created = file
+            .metadata
+            .created()
+            .or_else(|_| file.metadata.modified());
+        if let Ok(created) = created {
+            let month = DateTime::<Utc>::from(created).format("%Y-%m").to_string();
+            *self.summary.notes_per_month.entry(month).or_default() += 1;
+        }
+    }
+
+    /// Finish the summary using the link graph for the vault. Links without an extension (like
+    /// most wikilinks) to notes that exist and links to attachments that exist aren't counted as
+    /// broken, and notes linked to that way aren't orphans
+    pub fn finish(mut self, links: &Links) -> VaultSummary {
+        self.summary.tags = self.tags.len();
+        let notes: Vec<&PathBuf> = links
+            .iter()
+            .filter(|(_, file_links)| file_links.exists)
+            .map(|(path, _)| path)
+            .collect();
+        let mut linked = BTreeSet::new();
+        for (target, file_links) in links.iter() {
+            self.summary.links += file_links.links.len();
+            if file_links.exists {
+                continue;
+            }
+            match notes.iter().find(|note| same_note(target, note)) {
+                Some(note) if !file_links.backlinks.is_empty() => {
+                    linked.insert(*note);
+                }
+                Some(_) => {}
+                None if target.exists() => {}
+                None => self.summary.broken_links += file_links.backlinks.len(),
+            }
+        }
+        self.summary.orphans = links
+            .iter_orphans()
+            .filter(|path| !linked.contains(path))
+            .count();
+        self.summary.average_words = self
+            .summary
+            .total_words
+            .checked_div(self.summary.notes)
+            .unwrap_or_default();
+        self.summary.average_note_bytes = self
+            .total_bytes
+            .checked_div(self.summary.notes as u64)
+            .unwrap_or_default();
+        self.summary
+    }
+}
+
+/// Parse all of the given file entries and summarize them. Links are resolved relative to the
+/// given vault root using the given link style, the same way `obsidian-links` does. Tags are also
+/// read from any of the given tag keys (see [`Frontmatter::with_tag_keys`])
+pub fn summarize(
+    entries: Vec<FileEntry>,
+    vault_root: &Path,
+    link_style: LinkStyle,
+    tag_keys: &[String],
+) -> Result<VaultSummary> {
+    summarize_with_progress(
+        entries,
+        vault_root,
+        link_style,
+        tag_keys,
+        |_: ProgressEvent<'_>| {},
+        &mut ParseReport::default(),
+    )
+}
+
+/// The same as [`summarize`], but reports progress to the given reporter as notes are parsed and
+/// records any notes that fail to parse in the given report
+pub fn summarize_with_progress<R: ProgressReporter>(
+    entries: Vec<FileEntry>,
+    vault_root: &Path,
+    link_style: LinkStyle,
+    tag_keys: &[String],
+    reporter: R,
+    report: &mut ParseReport,
+) -> Result<VaultSummary> {
+    let mut builder = SummaryBuilder::new(vault_root);
+    let (notes, attachments): (Vec<_>, Vec<_>) = entries
+        .into_iter()
+        .partition(|e| parser::is_markdown(&e.path));
+    for attachment in &attachments {
+        builder.add_attachment(attachment);
+    }
+
+    let arena = Arena::with_capacity(notes.len());
+    let parsed_files = parser::report_error_iter(
+        parser::parse_files_with_progress(&arena, notes, reporter),
+        report,
+    );
+    let mut links = Links::new();
+    for (pf, targets) in obsidian_links::parser::parse_links(parsed_files, &vault_root, link_style)
+    {
+        let fm =
+            frontmatter::parse_frontmatter_from_ast(pf.ast).map(|fm| fm.with_tag_keys(tag_keys));
+        let content_stats = stats::stats_from_ast(pf.ast);
+        builder.add_note(&pf, fm.as_ref(), &content_stats);
+
+        let targets = targets
+            .iter()
+            .map(|p| obsidian_links::resolve_link_path(p))
+            .collect::<Result<Vec<_>>>()?;
+        links.insert_links(pf.path.canonicalize()?, targets);
+    }
+
+    Ok(builder.finish(&links))
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.components().any(|c| match c {
+        Component::Normal(name) => name.to_str().is_some_and(|n| n.starts_with('.')),
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../test-vault")
+    }
+
+    #[test]
+    fn summarizes_test_vault() -> Result<()> {
+        let vault = vault_path();
+        let entries = obsidian_core::reader::read_dir(&vault, true)?;
+        let summary = summarize(entries, &vault, LinkStyle::Infer, &[])?;
+
+        assert_eq!(summary.notes, 10);
+        assert_eq!(summary.attachments, 1, "only notes.txt should count");
+        assert_eq!(summary.tags, 2);
+        assert_eq!(summary.tag_usages, 2);
+        // Source.md has 5 links and Encoded.md has 2
+        assert_eq!(summary.links, 7);
+        // `nested/Deep.md` is inferred to be relative to Source.md, so it doesn't resolve to a
+        // file. The two wikilinks don't include an extension but still point at notes
+        assert_eq!(summary.broken_links, 1);
+        // Other.md and Deep.md
+        assert_eq!(summary.orphans, 2);
+        assert_eq!(summary.notes_per_month.values().sum::<usize>(), 10);
+        assert!(summary.average_note_bytes > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn hidden_paths_are_detected() {
+        assert!(is_hidden(Path::new(".obsidian/app.json")));
+        assert!(!is_hidden(Path::new("images/cat.png")));
+    }
+}
@@ -0,0 +1,156 @@
+use std::{collections::BTreeSet, path::Path};
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use obsidian_core::periodic::NoteDateFormat;
+
+/// Coverage statistics for periodic notes (like daily or weekly notes) over a range of dates
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeriodicReport {
+    /// The first period in the analyzed range. This is `None` if there were no notes and no range
+    /// was given
+    pub start: Option<NaiveDate>,
+    /// The last period in the analyzed range
+    pub end: Option<NaiveDate>,
+    /// Whether each period is a week (starting on Monday) rather than a day
+    pub weekly: bool,
+    /// The number of periods in the range that have a note
+    pub notes: usize,
+    /// The total number of periods in the range
+    pub expected: usize,
+    /// The fraction of periods in the range that have a note, from 0 to 1
+    pub coverage: f64,
+    /// The number of consecutive periods with a note ending at the end of the range
+    pub current_streak: usize,
+    /// The longest run of consecutive periods with a note
+    pub longest_streak: usize,
+    /// Every period in the range without a note
+    pub missing: Vec<NaiveDate>,
+}
+
+/// Analyze which periods have a note, given the paths of all notes in the vault. Paths that don't
+/// match the format are ignored. If `since` or `until` aren't given, the range starts at the first
+/// note and ends at the last one
+pub fn analyze<P: AsRef<Path>>(
+    paths: impl IntoIterator<Item = P>,
+    format: &NoteDateFormat,
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+) -> PeriodicReport {
+    analyze_dates(
+        paths.into_iter().filter_map(|path| format.note_date(path)),
+        format.is_weekly(),
+        since,
+        until,
+    )
+}
+
+/// Same as [`analyze`], but for dates that have already been extracted from note names
+pub fn analyze_dates(
+    dates: impl IntoIterator<Item = NaiveDate>,
+    weekly: bool,
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+) -> PeriodicReport {
+    let period_start = |date: NaiveDate| {
+        if weekly {
+            date.week(chrono::Weekday::Mon).first_day()
+        } else {
+            date
+        }
+    };
+    let step = Days::new(if weekly { 7 } else { 1 });
+    let dates: BTreeSet<NaiveDate> = dates.into_iter().map(period_start).collect();
+
+    let mut report = PeriodicReport {
+        weekly,
+        ..Default::default()
+    };
+    let (Some(start), Some(end)) = (
+        since.or_else(|| dates.first().copied()).map(period_start),
+        until.or_else(|| dates.last().copied()).map(period_start),
+    ) else {
+        return report;
+    };
+    report.start = Some(start);
+    report.end = Some(end);
+
+    let mut streak = 0;
+    let mut current = start;
+    while current <= end {
+        report.expected += 1;
+        if dates.contains(&current) {
+            report.notes += 1;
+            streak += 1;
+            report.longest_streak = report.longest_streak.max(streak);
+        } else {
+            report.missing.push(current);
+            streak = 0;
+        }
+        let Some(next) = current.checked_add_days(step) else {
+            break;
+        };
+        current = next;
+    }
+    report.current_streak = streak;
+    if report.expected > 0 {
+        report.coverage = report.notes as f64 / report.expected as f64;
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn reports_gaps_and_streaks_for_daily_notes() {
+        let paths = [
+            "/vault/2025-01-01.md",
+            "/vault/2025-01-02.md",
+            "/vault/2025-01-03.md",
+            "/vault/2025-01-05.md",
+            "/vault/2025-01-06.md",
+            "/vault/Meeting notes.md",
+        ];
+        let report = analyze(paths, &NoteDateFormat::default(), None, None);
+
+        assert_eq!(report.start, Some(date(2025, 1, 1)));
+        assert_eq!(report.end, Some(date(2025, 1, 6)));
+        assert_eq!((report.notes, report.expected), (5, 6));
+        assert_eq!(report.missing, vec![date(2025, 1, 4)]);
+        assert_eq!(report.longest_streak, 3);
+        assert_eq!(report.current_streak, 2);
+
+        let report = analyze(
+            paths,
+            &NoteDateFormat::default(),
+            None,
+            Some(date(2025, 1, 8)),
+        );
+        assert_eq!(report.current_streak, 0);
+        assert_eq!(report.missing.len(), 3);
+    }
+
+    #[test]
+    fn groups_weekly_notes_by_week() -> anyhow::Result<()> {
+        let format = NoteDateFormat::new("gggg-[W]ww")?;
+        let report = analyze(
+            ["2025-W01.md", "2025-W02.md", "2025-W04.md"],
+            &format,
+            None,
+            None,
+        );
+
+        assert!(report.weekly);
+        assert_eq!((report.notes, report.expected), (3, 4));
+        assert_eq!(report.missing, vec![date(2025, 1, 13)]);
+        assert!((report.coverage - 0.75).abs() < f64::EPSILON);
+        Ok(())
+    }
+}
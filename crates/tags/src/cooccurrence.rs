@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+/// Counts of how often tags appear together on the same notes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoOccurrence {
+    /// The total number of notes counted, including notes without any tags
+    notes: usize,
+    /// The number of notes each tag is on
+    counts: BTreeMap<String, usize>,
+    /// The number of notes each pair of tags is on, keyed by the first tag of the pair (in sorted
+    /// order) and then the second. Nested maps are used so this can be serialized as JSON
+    pairs: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+/// A tag associated with another tag, as returned by [`CoOccurrence::related`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelatedTag {
+    /// The related tag
+    pub tag: String,
+    /// The number of notes with both tags
+    pub count: usize,
+    /// The fraction of notes with either tag that have both (the Jaccard index), from 0 to 1
+    pub jaccard: f64,
+    /// The correlation between the two tags being on a note (the phi coefficient), from -1 to 1.
+    /// This is 0 if either tag is on every note, since there is nothing to correlate
+    pub correlation: f64,
+}
+
+impl CoOccurrence {
+    /// Count co-occurrences from the tags on each note. Each item is the tags on a single note, and
+    /// notes without tags should still be included so correlations are accurate. Duplicate tags on
+    /// a note are only counted once
+    pub fn from_notes<N, T>(notes: N) -> Self
+    where
+        N: IntoIterator<Item = T>,
+        T: IntoIterator,
+        T::Item: AsRef<str>,
+    {
+        let mut co = Self::default();
+        for tags in notes {
+            co.notes += 1;
+            let tags: BTreeSet<String> = tags
+                .into_iter()
+                .map(|tag| tag.as_ref().to_string())
+                .collect();
+            for (i, tag) in tags.iter().enumerate() {
+                *co.counts.entry(tag.clone()).or_default() += 1;
+                // Tags are sorted, so every pair is already in order
+                for other in tags.iter().skip(i + 1) {
+                    *co.pairs
+                        .entry(tag.clone())
+                        .or_default()
+                        .entry(other.clone())
+                        .or_default() += 1;
+                }
+            }
+        }
+        co
+    }
+
+    /// The total number of notes counted
+    pub fn notes(&self) -> usize {
+        self.notes
+    }
+
+    /// The number of notes the tag is on
+    pub fn count(&self, tag: &str) -> usize {
+        self.counts.get(tag).copied().unwrap_or_default()
+    }
+
+    /// The number of notes both tags are on
+    pub fn pair_count(&self, a: &str, b: &str) -> usize {
+        let (a, b) = if a <= b { (a, b) } else { (b, a) };
+        self.pairs
+            .get(a)
+            .and_then(|others| others.get(b))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Every pair of tags that appear together along with the number of notes they are both on,
+    /// with the most common pairs first
+    pub fn pairs(&self) -> Vec<(&str, &str, usize)> {
+        let mut pairs: Vec<_> = self.pair_counts().collect();
+        pairs.sort_by_key(|pair| std::cmp::Reverse(pair.2));
+        pairs
+    }
+
+    /// The tags that appear on notes with the given tag, with the most strongly associated first.
+    /// Tags are ranked by their Jaccard index, then by the number of notes they share
+    pub fn related(&self, tag: &str) -> Vec<RelatedTag> {
+        let mut related: Vec<_> = self
+            .pair_counts()
+            .filter_map(|(a, b, count)| match (a == tag, b == tag) {
+                (true, _) => Some((b, count)),
+                (_, true) => Some((a, count)),
+                _ => None,
+            })
+            .map(|(other, count)| RelatedTag {
+                tag: other.to_string(),
+                count,
+                jaccard: self.jaccard(tag, other, count),
+                correlation: self.correlation(tag, other, count),
+            })
+            .collect();
+        related.sort_by(|a, b| {
+            b.jaccard
+                .total_cmp(&a.jaccard)
+                .then(b.count.cmp(&a.count))
+                .then_with(|| a.tag.cmp(&b.tag))
+        });
+        related
+    }
+
+    fn pair_counts(&self) -> impl Iterator<Item = (&str, &str, usize)> {
+        self.pairs.iter().flat_map(|(a, others)| {
+            others
+                .iter()
+                .map(move |(b, count)| (a.as_str(), b.as_str(), *count))
+        })
+    }
+
+    fn jaccard(&self, a: &str, b: &str, both: usize) -> f64 {
+        let either = self.count(a) + self.count(b) - both;
+        if either == 0 {
+            0.0
+        } else {
+            both as f64 / either as f64
+        }
+    }
+
+    fn correlation(&self, a: &str, b: &str, both: usize) -> f64 {
+        let (n, a, b, both) = (
+            self.notes as f64,
+            self.count(a) as f64,
+            self.count(b) as f64,
+            both as f64,
+        );
+        let denominator = (a * b * (n - a) * (n - b)).sqrt();
+        if denominator == 0.0 {
+            0.0
+        } else {
+            (both * n - a * b) / denominator
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> CoOccurrence {
+        CoOccurrence::from_notes([
+            vec!["rust", "cli", "cli"],
+            vec!["rust", "cli"],
+            vec!["rust", "wasm"],
+            vec!["cooking"],
+            vec![],
+        ])
+    }
+
+    #[test]
+    fn counts_pairs() {
+        let co = sample();
+        assert_eq!(co.notes(), 5);
+        assert_eq!(co.count("cli"), 2, "duplicate tags are counted once");
+        assert_eq!(co.pair_count("rust", "cli"), 2);
+        assert_eq!(co.pair_count("cli", "rust"), 2);
+        assert_eq!(co.pair_count("cli", "cooking"), 0);
+        assert_eq!(co.pairs(), vec![("cli", "rust", 2), ("rust", "wasm", 1)]);
+    }
+
+    #[test]
+    fn ranks_related_tags() {
+        let co = sample();
+        let related = co.related("rust");
+        let tags: Vec<_> = related.iter().map(|r| r.tag.as_str()).collect();
+        assert_eq!(tags, vec!["cli", "wasm"]);
+        assert_eq!(related[0].count, 2);
+        assert!((related[0].jaccard - 2.0 / 3.0).abs() < 1e-9);
+        assert!(related[0].correlation > related[1].correlation);
+        assert!(co.related("cooking").is_empty());
+        assert!(co.related("missing").is_empty());
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let co = sample();
+        let json = serde_json::to_string(&co).expect("should serialize as JSON");
+        let parsed: CoOccurrence = serde_json::from_str(&json).expect("should deserialize");
+        assert_eq!(parsed, co);
+    }
+}
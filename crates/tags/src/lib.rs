@@ -0,0 +1,88 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+pub mod cooccurrence;
+pub mod timeline;
+
+/// An index of the tags in a set of notes, in both directions: the files that have each tag and
+/// the tags on each file. Only files with at least one tag are included
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagIndex {
+    /// The files with each tag
+    pub by_tag: BTreeMap<String, BTreeSet<PathBuf>>,
+    /// The tags on each file
+    pub by_file: BTreeMap<PathBuf, BTreeSet<String>>,
+}
+
+impl TagIndex {
+    /// Create a new, empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the tags on a file to the index
+    pub fn insert<T: Into<String>>(&mut self, path: PathBuf, tags: impl IntoIterator<Item = T>) {
+        for tag in tags {
+            let tag = tag.into();
+            self.by_tag
+                .entry(tag.clone())
+                .or_default()
+                .insert(path.clone());
+            self.by_file.entry(path.clone()).or_default().insert(tag);
+        }
+    }
+
+    /// The files with the given tag
+    pub fn files(&self, tag: &str) -> Option<&BTreeSet<PathBuf>> {
+        self.by_tag.get(tag)
+    }
+
+    /// The tags on the given file
+    pub fn tags(&self, path: impl AsRef<Path>) -> Option<&BTreeSet<String>> {
+        self.by_file.get(path.as_ref())
+    }
+}
+
+impl<T: Into<String>, I: IntoIterator<Item = T>> FromIterator<(PathBuf, I)> for TagIndex {
+    fn from_iter<N: IntoIterator<Item = (PathBuf, I)>>(notes: N) -> Self {
+        let mut index = Self::new();
+        for (path, tags) in notes {
+            index.insert(path, tags);
+        }
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexes_both_directions() {
+        let index: TagIndex = [
+            (PathBuf::from("a.md"), vec!["rust", "cli"]),
+            (PathBuf::from("b.md"), vec!["rust"]),
+            (PathBuf::from("c.md"), vec![]),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            index.files("rust").unwrap(),
+            &BTreeSet::from([PathBuf::from("a.md"), PathBuf::from("b.md")])
+        );
+        assert_eq!(
+            index.tags("a.md").unwrap(),
+            &BTreeSet::from(["cli".to_string(), "rust".to_string()])
+        );
+        assert!(
+            index.tags("c.md").is_none(),
+            "untagged files aren't indexed"
+        );
+        assert!(index.files("missing").is_none());
+    }
+}
@@ -0,0 +1,125 @@
+use std::{collections::BTreeMap, fs::Metadata, path::Path};
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use obsidian_core::periodic::NoteDateFormat;
+
+/// When a tag has been used over time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagTimeline {
+    /// The date of the oldest note with the tag
+    pub first_used: NaiveDate,
+    /// The date of the newest note with the tag
+    pub last_used: NaiveDate,
+    /// The number of notes with the tag
+    pub notes: usize,
+    /// The number of notes with the tag in each month (formatted as `YYYY-MM`)
+    pub per_month: BTreeMap<String, usize>,
+}
+
+impl TagTimeline {
+    fn new(date: NaiveDate) -> Self {
+        Self {
+            first_used: date,
+            last_used: date,
+            notes: 0,
+            per_month: BTreeMap::new(),
+        }
+    }
+
+    fn add(&mut self, date: NaiveDate) {
+        self.first_used = self.first_used.min(date);
+        self.last_used = self.last_used.max(date);
+        self.notes += 1;
+        let month = format!("{:04}-{:02}", date.year(), date.month());
+        *self.per_month.entry(month).or_default() += 1;
+    }
+}
+
+/// Get the date a note was written. If a daily note format is given and the note is a daily note,
+/// this is the date of the daily note. Otherwise it is the date (in UTC) the file was created, or
+/// last modified if the platform doesn't track creation times
+pub fn note_date(
+    path: impl AsRef<Path>,
+    metadata: &Metadata,
+    daily_notes: Option<&NoteDateFormat>,
+) -> Option<NaiveDate> {
+    daily_notes
+        .and_then(|format| format.note_date(path))
+        .or_else(|| {
+            let created = metadata.created().or_else(|_| metadata.modified()).ok()?;
+            Some(DateTime::<Utc>::from(created).date_naive())
+        })
+}
+
+/// Build the timeline of every tag from the date and tags of each note. Duplicate tags on a note
+/// are only counted once
+pub fn timelines<N, T>(notes: N) -> BTreeMap<String, TagTimeline>
+where
+    N: IntoIterator<Item = (NaiveDate, T)>,
+    T: IntoIterator,
+    T::Item: AsRef<str>,
+{
+    let mut timelines = BTreeMap::new();
+    for (date, tags) in notes {
+        let tags: std::collections::BTreeSet<String> =
+            tags.into_iter().map(|t| t.as_ref().to_string()).collect();
+        for tag in tags {
+            timelines
+                .entry(tag)
+                .or_insert_with(|| TagTimeline::new(date))
+                .add(date);
+        }
+    }
+    timelines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn builds_timelines() {
+        let timelines = timelines([
+            (date(2025, 3, 2), vec!["rust", "rust"]),
+            (date(2025, 1, 15), vec!["rust", "garden"]),
+            (date(2025, 1, 20), vec!["rust"]),
+            (date(2024, 6, 1), vec![]),
+        ]);
+        assert_eq!(timelines.len(), 2);
+        let rust = &timelines["rust"];
+        assert_eq!(rust.first_used, date(2025, 1, 15));
+        assert_eq!(rust.last_used, date(2025, 3, 2));
+        assert_eq!(rust.notes, 3);
+        assert_eq!(
+            rust.per_month,
+            BTreeMap::from([("2025-01".to_string(), 2), ("2025-03".to_string(), 1)])
+        );
+        assert_eq!(timelines["garden"].last_used, date(2025, 1, 15));
+    }
+
+    #[test]
+    fn prefers_daily_note_dates() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let daily = dir.path().join("2024-02-29.md");
+        let other = dir.path().join("Ideas.md");
+        std::fs::write(&daily, "")?;
+        std::fs::write(&other, "")?;
+        let format = NoteDateFormat::new("YYYY-MM-DD")?;
+
+        let metadata = std::fs::metadata(&daily)?;
+        assert_eq!(
+            note_date(&daily, &metadata, Some(&format)),
+            Some(date(2024, 2, 29))
+        );
+        let today = Utc::now().date_naive();
+        let file_date = note_date(&other, &std::fs::metadata(&other)?, Some(&format)).unwrap();
+        assert!((today - file_date).num_days().abs() <= 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+use std::str::FromStr;
+
+use comrak::nodes::{AstNode, NodeValue};
+use serde::{Deserialize, Serialize};
+
+use obsidian_core::parser::{self, ParsedFile, Span};
+
+/// Markers used to annotate a due date on a task. The first is the emoji used by the Tasks plugin
+/// and the second is a Dataview inline field (`[due:: 2025-01-15]` or `due:: 2025-01-15`)
+const DUE_MARKERS: &[&str] = &["📅", "due::"];
+
+/// The status of a task, based on the character between the brackets of the checkbox
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskStatus {
+    /// An open task (`- [ ]`)
+    Todo,
+    /// A completed task (`- [x]`)
+    Done,
+    /// A cancelled task (`- [-]`)
+    Cancelled,
+    /// A task that has been started (`- [/]`)
+    InProgress,
+    /// Any other custom status character used by themes or plugins
+    Other(char),
+}
+
+impl TaskStatus {
+    /// Get the status for the given checkbox symbol. `None` means the checkbox is empty
+    pub fn from_symbol(symbol: Option<char>) -> Self {
+        match symbol {
+            None | Some(' ') => TaskStatus::Todo,
+            Some('x' | 'X') => TaskStatus::Done,
+            Some('-') => TaskStatus::Cancelled,
+            Some('/') => TaskStatus::InProgress,
+            Some(c) => TaskStatus::Other(c),
+        }
+    }
+
+    /// Get the character used between the brackets of the checkbox for this status
+    pub fn symbol(&self) -> char {
+        match self {
+            TaskStatus::Todo => ' ',
+            TaskStatus::Done => 'x',
+            TaskStatus::Cancelled => '-',
+            TaskStatus::InProgress => '/',
+            TaskStatus::Other(c) => *c,
+        }
+    }
+
+    /// Returns true if this task still needs to be done (i.e. it isn't done or cancelled)
+    pub fn is_open(&self) -> bool {
+        !matches!(self, TaskStatus::Done | TaskStatus::Cancelled)
+    }
+}
+
+impl FromStr for TaskStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "todo" => Ok(TaskStatus::Todo),
+            "done" => Ok(TaskStatus::Done),
+            "cancelled" | "canceled" => Ok(TaskStatus::Cancelled),
+            "in-progress" | "in_progress" => Ok(TaskStatus::InProgress),
+            _ => {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(TaskStatus::from_symbol(Some(c))),
+                    _ => Err(anyhow::anyhow!("Unknown task status: {}", s)),
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}]", self.symbol())
+    }
+}
+
+/// A single task (checkbox list item) found in a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Task {
+    /// The status of the task
+    pub status: TaskStatus,
+    /// The plain text of the task, not including any nested list items
+    pub text: String,
+    /// Inline tags (without the leading `#`) found in the task text
+    pub tags: Vec<String>,
+    /// The due date annotation on the task, if any. This is returned as written (generally
+    /// `YYYY-MM-DD`) and is not validated
+    pub due: Option<String>,
+    /// The location of the task in the file
+    pub span: Span,
+}
+
+/// Parse the tasks from a list of ParsedFiles, returning an iterator of tuples of the
+/// [`ParsedFile`] returned as is and all tasks found in the file, in document order. Nested tasks
+/// are returned as their own entries.
+pub fn parse_tasks<'a>(
+    entries: impl IntoIterator<Item = ParsedFile<'a>>,
+) -> impl Iterator<Item = (ParsedFile<'a>, Vec<Task>)> {
+    entries.into_iter().map(|pf| {
+        let tasks = parse_tasks_from_ast(pf.ast);
+        (pf, tasks)
+    })
+}
+
+/// Parse the tasks from the AST of a markdown file
+pub fn parse_tasks_from_ast<'a>(ast: &'a AstNode<'a>) -> Vec<Task> {
+    ast.descendants()
+        .filter_map(|node| {
+            let symbol = task_symbol(&node.data.borrow().value)?;
+            // Only the first paragraph is the task itself. Anything after that (like nested lists)
+            // belongs to child items
+            let text = node
+                .children()
+                .find(|child| matches!(child.data.borrow().value, NodeValue::Paragraph))
+                .map(parser::node_text)
+                .unwrap_or_default();
+            Some(Task {
+                status: TaskStatus::from_symbol(symbol),
+                tags: inline_tags(&text),
+                due: due_date(&text),
+                text: text.trim().to_string(),
+                span: node.into(),
+            })
+        })
+        .collect()
+}
+
+fn task_symbol(value: &NodeValue) -> Option<Option<char>> {
+    match value {
+        NodeValue::TaskItem(item) => Some(item.symbol),
+        _ => None,
+    }
+}
+
+/// Find all inline tags (e.g. `#project/work`) in some text. A tag must start at the beginning of
+/// the text or after whitespace and, like in Obsidian, can't be purely numeric
+fn inline_tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut prev = None;
+    for (idx, c) in text.char_indices() {
+        if c == '#' && prev.is_none_or(char::is_whitespace) {
+            let rest = &text[idx + 1..];
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '/')))
+                .unwrap_or(rest.len());
+            let tag = &rest[..end];
+            if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) {
+                tags.push(tag.to_string());
+            }
+        }
+        prev = Some(c);
+    }
+    tags
+}
+
+fn due_date(text: &str) -> Option<String> {
+    DUE_MARKERS.iter().find_map(|marker| {
+        let (_, rest) = text.split_once(marker)?;
+        let date = rest
+            .trim_start()
+            .split(|c: char| c.is_whitespace() || c == ']')
+            .next()?;
+        (!date.is_empty()).then(|| date.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use comrak::Arena;
+
+    fn tasks_for(input: &str) -> Vec<Task> {
+        let arena = Arena::new();
+        let ast = parser::parse_content(&arena, input);
+        parse_tasks_from_ast(ast)
+    }
+
+    #[test]
+    fn parses_task_statuses() {
+        let tasks = tasks_for(
+            "- [ ] open\n- [x] done\n- [X] also done\n- [-] nope\n- [/] going\n- [?] question\n- not a task\n",
+        );
+        let statuses: Vec<_> = tasks.iter().map(|t| t.status).collect();
+
+        assert_eq!(
+            statuses,
+            vec![
+                TaskStatus::Todo,
+                TaskStatus::Done,
+                TaskStatus::Done,
+                TaskStatus::Cancelled,
+                TaskStatus::InProgress,
+                TaskStatus::Other('?'),
+            ]
+        );
+        assert_eq!(tasks[0].text, "open");
+        assert_eq!(tasks[0].span.start_line, 1);
+        assert_eq!(tasks[5].span.start_line, 6);
+    }
+
+    #[test]
+    fn nested_tasks_are_separate_entries() {
+        let tasks = tasks_for("- [ ] parent\n  - [x] child\n");
+
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].text, "parent");
+        assert_eq!(tasks[1].text, "child");
+        assert_eq!(tasks[1].status, TaskStatus::Done);
+    }
+
+    #[test]
+    fn extracts_tags_and_due_dates() {
+        let tasks = tasks_for(
+            "- [ ] Write report #work/reports #urgent 📅 2025-01-15\n- [ ] Call mom [due:: 2025-02-01] #123\n",
+        );
+
+        assert_eq!(tasks[0].tags, vec!["work/reports", "urgent"]);
+        assert_eq!(tasks[0].due.as_deref(), Some("2025-01-15"));
+        assert!(tasks[1].tags.is_empty(), "numeric tags should be ignored");
+        assert_eq!(tasks[1].due.as_deref(), Some("2025-02-01"));
+    }
+
+    #[test]
+    fn status_from_str() {
+        assert_eq!("todo".parse::<TaskStatus>().unwrap(), TaskStatus::Todo);
+        assert_eq!("x".parse::<TaskStatus>().unwrap(), TaskStatus::Done);
+        assert_eq!("!".parse::<TaskStatus>().unwrap(), TaskStatus::Other('!'));
+        assert!("nope".parse::<TaskStatus>().is_err());
+    }
+}
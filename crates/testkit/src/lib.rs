@@ -0,0 +1,386 @@
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{Context, Result};
+
+mod rng;
+
+pub use rng::Rng;
+
+const WORDS: &[&str] = &[
+    "vault",
+    "note",
+    "idea",
+    "project",
+    "garden",
+    "link",
+    "graph",
+    "daily",
+    "review",
+    "draft",
+    "reference",
+    "meeting",
+    "summary",
+    "question",
+    "answer",
+    "research",
+    "outline",
+    "journal",
+    "insight",
+    "pattern",
+    "system",
+    "habit",
+    "reading",
+    "writing",
+    "focus",
+    "archive",
+    "inbox",
+    "topic",
+    "context",
+    "detail",
+];
+
+/// The type of a generated frontmatter property. These mirror the property types Obsidian uses in
+/// its `types.json` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyKind {
+    Text,
+    Number,
+    Checkbox,
+    Date,
+    DateTime,
+    List,
+}
+
+impl FromStr for PropertyKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(PropertyKind::Text),
+            "number" => Ok(PropertyKind::Number),
+            "checkbox" => Ok(PropertyKind::Checkbox),
+            "date" => Ok(PropertyKind::Date),
+            "datetime" => Ok(PropertyKind::DateTime),
+            "list" | "multitext" => Ok(PropertyKind::List),
+            _ => Err(anyhow::anyhow!("Unknown property type: {}", s)),
+        }
+    }
+}
+
+/// A frontmatter property that should be generated for notes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertySpec {
+    /// The name of the property
+    pub name: String,
+    /// The type of value to generate
+    pub kind: PropertyKind,
+}
+
+impl FromStr for PropertySpec {
+    type Err = anyhow::Error;
+
+    /// Parse a property spec in the form `name:type`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, kind) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("Invalid property format. Expected '<name>:<type>'"))?;
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("Property name cannot be empty");
+        }
+        Ok(PropertySpec {
+            name: name.to_string(),
+            kind: kind.trim().parse()?,
+        })
+    }
+}
+
+/// The configuration for a generated vault. The same spec (including the seed) always generates
+/// exactly the same vault, so a spec is all that is needed to reproduce a benchmark or an issue.
+#[derive(Debug, Clone)]
+pub struct VaultSpec {
+    /// The number of notes to generate
+    pub notes: usize,
+    /// The number of folders to spread notes across. Notes are placed either in the vault root or
+    /// one level deep in one of these folders
+    pub folders: usize,
+    /// The average number of outgoing links per note
+    pub links_per_note: f64,
+    /// The fraction (0.0-1.0) of links that use wikilink syntax instead of markdown links
+    pub wikilink_ratio: f64,
+    /// The fraction (0.0-1.0) of links that point to notes that don't exist
+    pub broken_link_ratio: f64,
+    /// The total number of distinct tags in the vault
+    pub tags: usize,
+    /// The maximum number of tags on a single note
+    pub tags_per_note: usize,
+    /// Additional frontmatter properties to generate for every note
+    pub properties: Vec<PropertySpec>,
+    /// The approximate number of words in the body of each note
+    pub words_per_note: usize,
+    /// The seed for the random number generator
+    pub seed: u64,
+}
+
+impl Default for VaultSpec {
+    fn default() -> Self {
+        Self {
+            notes: 100,
+            folders: 5,
+            links_per_note: 3.0,
+            wikilink_ratio: 0.5,
+            broken_link_ratio: 0.05,
+            tags: 20,
+            tags_per_note: 3,
+            properties: Vec::new(),
+            words_per_note: 100,
+            seed: 0,
+        }
+    }
+}
+
+/// A single generated note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedNote {
+    /// The path of the note relative to the vault root
+    pub path: PathBuf,
+    /// The full markdown content of the note
+    pub content: String,
+}
+
+impl VaultSpec {
+    /// Generate all notes for this spec in memory
+    pub fn generate(&self) -> Vec<GeneratedNote> {
+        let mut rng = Rng::new(self.seed);
+        let paths: Vec<PathBuf> = (0..self.notes)
+            .map(|i| {
+                let name = format!("Note {i}.md");
+                // Leave roughly one folder's worth of notes in the root
+                match rng.below(self.folders + 1) {
+                    0 => PathBuf::from(name),
+                    folder => PathBuf::from(format!("folder{}", folder - 1)).join(name),
+                }
+            })
+            .collect();
+
+        paths
+            .iter()
+            .map(|path| GeneratedNote {
+                path: path.clone(),
+                content: self.generate_content(&mut rng, path, &paths),
+            })
+            .collect()
+    }
+
+    /// Generate the vault and write it to the given directory, returning the paths of all written
+    /// files
+    pub fn write_to(&self, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let dir = dir.as_ref();
+        self.generate()
+            .into_iter()
+            .map(|note| {
+                let path = dir.join(&note.path);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent).with_context(|| {
+                        format!("Failed to create directory {}", parent.display())
+                    })?;
+                }
+                std::fs::write(&path, note.content)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                Ok(path)
+            })
+            .collect()
+    }
+
+    fn generate_content(&self, rng: &mut Rng, path: &Path, all: &[PathBuf]) -> String {
+        let mut content = String::from("---\n");
+        let tag_count = rng.below(self.tags_per_note + 1).min(self.tags);
+        if tag_count > 0 {
+            content.push_str("tags:\n");
+            let mut tags: Vec<usize> = (0..tag_count).map(|_| rng.skewed(self.tags)).collect();
+            tags.sort_unstable();
+            tags.dedup();
+            for tag in tags {
+                let _ = writeln!(content, "  - topic-{tag}");
+            }
+        }
+        for prop in &self.properties {
+            let _ = writeln!(content, "{}: {}", prop.name, property_value(rng, prop.kind));
+        }
+        content.push_str("---\n");
+
+        let title = path.file_stem().unwrap_or_default().to_string_lossy();
+        let _ = writeln!(content, "# {title}\n");
+
+        // Sprinkle links evenly through the body paragraphs
+        let max_links = (self.links_per_note * 2.0).round() as usize;
+        let link_count = rng.below(max_links + 1);
+        let mut links: Vec<String> = (0..link_count)
+            .map(|_| self.generate_link(rng, path, all))
+            .collect();
+        let paragraphs = (self.words_per_note / 50).max(1);
+        for p in 0..paragraphs {
+            if p > 0 && p % 3 == 0 {
+                let _ = writeln!(content, "## Section {p}\n");
+            }
+            let words = self.words_per_note / paragraphs;
+            let paragraph: Vec<&str> = (0..words.max(1))
+                .map(|_| WORDS[rng.below(WORDS.len())])
+                .collect();
+            let remaining = paragraphs - p;
+            let take = links.len().div_ceil(remaining);
+            let paragraph_links: Vec<String> = links.drain(..take).collect();
+            content.push_str(&paragraph.join(" "));
+            for link in paragraph_links {
+                content.push(' ');
+                content.push_str(&link);
+            }
+            content.push_str("\n\n");
+        }
+        content
+    }
+
+    fn generate_link(&self, rng: &mut Rng, from: &Path, all: &[PathBuf]) -> String {
+        let target = if all.is_empty() || rng.chance(self.broken_link_ratio) {
+            PathBuf::from(format!("Missing {}.md", rng.below(self.notes.max(1))))
+        } else {
+            all[rng.below(all.len())].clone()
+        };
+        let relative = relative_link(from, &target);
+        let name = target.file_stem().unwrap_or_default().to_string_lossy();
+        if rng.chance(self.wikilink_ratio) {
+            format!("[[{}]]", relative.trim_end_matches(".md"))
+        } else {
+            format!("[{name}]({})", relative.replace(' ', "%20"))
+        }
+    }
+}
+
+/// Build a link from one vault-relative path to another that resolves relative to the linking
+/// file. Generated notes are at most one folder deep, so this doesn't need to be fully general
+fn relative_link(from: &Path, to: &Path) -> String {
+    let from_dir = from.parent().unwrap_or(Path::new(""));
+    let to_dir = to.parent().unwrap_or(Path::new(""));
+    let file_name = to.file_name().unwrap_or_default().to_string_lossy();
+    if from_dir == to_dir {
+        format!("./{file_name}")
+    } else if from_dir.as_os_str().is_empty() {
+        format!("{}/{file_name}", to_dir.display())
+    } else if to_dir.as_os_str().is_empty() {
+        format!("../{file_name}")
+    } else {
+        format!("../{}/{file_name}", to_dir.display())
+    }
+}
+
+fn property_value(rng: &mut Rng, kind: PropertyKind) -> String {
+    match kind {
+        PropertyKind::Text => format!("\"{} {}\"", WORDS[rng.below(WORDS.len())], rng.below(100)),
+        PropertyKind::Number => rng.below(1000).to_string(),
+        PropertyKind::Checkbox => rng.chance(0.5).to_string(),
+        PropertyKind::Date => random_date(rng),
+        PropertyKind::DateTime => format!(
+            "{}T{:02}:{:02}:00",
+            random_date(rng),
+            rng.below(24),
+            rng.below(60)
+        ),
+        PropertyKind::List => {
+            let items: Vec<&str> = (0..rng.below(4) + 1)
+                .map(|_| WORDS[rng.below(WORDS.len())])
+                .collect();
+            format!("[{}]", items.join(", "))
+        }
+    }
+}
+
+fn random_date(rng: &mut Rng) -> String {
+    // Sticking to 28 days a month keeps every generated date valid
+    format!(
+        "{}-{:02}-{:02}",
+        2020 + rng.below(6),
+        rng.below(12) + 1,
+        rng.below(28) + 1
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generation_is_deterministic_for_a_seed() {
+        let spec = VaultSpec {
+            notes: 20,
+            seed: 42,
+            ..Default::default()
+        };
+        assert_eq!(spec.generate(), spec.generate());
+
+        let other = VaultSpec {
+            seed: 43,
+            ..spec.clone()
+        };
+        assert_ne!(spec.generate(), other.generate());
+    }
+
+    #[test]
+    fn generated_notes_have_frontmatter_schema() {
+        let spec = VaultSpec {
+            notes: 10,
+            tags_per_note: 0,
+            properties: vec![
+                "rating:number".parse().unwrap(),
+                "done:checkbox".parse().unwrap(),
+                "due:date".parse().unwrap(),
+            ],
+            ..Default::default()
+        };
+        for note in spec.generate() {
+            let (fm, _) = note
+                .content
+                .trim_start_matches("---\n")
+                .split_once("---\n")
+                .expect("expected frontmatter block");
+            let values: serde_norway::Mapping =
+                serde_norway::from_str(fm).expect("frontmatter should be valid YAML");
+            assert!(values.get("rating").is_some_and(|v| v.is_u64()));
+            assert!(values.get("done").is_some_and(|v| v.is_bool()));
+            assert!(values.get("due").is_some_and(|v| v.is_string()));
+            assert!(!values.contains_key("tags"));
+        }
+    }
+
+    #[test]
+    fn relative_links_resolve_between_folders() {
+        assert_eq!(
+            relative_link(Path::new("a/One.md"), Path::new("a/Two.md")),
+            "./Two.md"
+        );
+        assert_eq!(
+            relative_link(Path::new("One.md"), Path::new("b/Two.md")),
+            "b/Two.md"
+        );
+        assert_eq!(
+            relative_link(Path::new("a/One.md"), Path::new("Two.md")),
+            "../Two.md"
+        );
+        assert_eq!(
+            relative_link(Path::new("a/One.md"), Path::new("b/Two.md")),
+            "../b/Two.md"
+        );
+    }
+
+    #[test]
+    fn property_spec_parsing() {
+        let spec: PropertySpec = "due date: date".parse().unwrap();
+        assert_eq!(spec.name, "due date");
+        assert_eq!(spec.kind, PropertyKind::Date);
+        assert!("nope".parse::<PropertySpec>().is_err());
+        assert!("x:unknown".parse::<PropertySpec>().is_err());
+    }
+}
@@ -0,0 +1,50 @@
+/// A tiny, deterministic pseudo-random number generator (SplitMix64).
+///
+/// We intentionally don't depend on an external RNG crate here. Generated vaults need to be
+/// byte-for-byte reproducible from a seed across versions of this tool, which is much easier to
+/// guarantee when the algorithm is fixed in our own code.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Create a new generator from the given seed
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Get the next random u64
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Get a random float in the range [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Get a random number in the range [0, upper). Returns 0 if upper is 0
+    pub fn below(&mut self, upper: usize) -> usize {
+        if upper == 0 {
+            return 0;
+        }
+        (self.next_u64() % upper as u64) as usize
+    }
+
+    /// Get a random number in the range [0, upper) that is skewed towards lower values. This is
+    /// useful for things like tags where a few are used very often and most are used rarely
+    pub fn skewed(&mut self, upper: usize) -> usize {
+        let f = self.next_f64();
+        ((f * f) * upper as f64) as usize
+    }
+
+    /// Returns true with the given probability (0.0-1.0)
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+}